@@ -44,9 +44,7 @@ document.getElementById('caculator-output').innerHTML = mycalc.get_html();
 function update_caculator(){
     debugger;
     let value = document.getElementById('caculator-input').innerText;
-    mycalc.new_parser(value);
-    mycalc.parse();
-    mycalc.calc();
+    mycalc.update(value);
     document.getElementById('caculator-output').innerHTML = mycalc.get_html();
 }
 document.getElementById('caculator-input').oninput = update_caculator;
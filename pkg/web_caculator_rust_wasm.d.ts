@@ -1,108 +1,434 @@
 /* tslint:disable */
 /* eslint-disable */
-/**
-* @returns {MyStruct}
-*/
-export function create_struct(): MyStruct;
-/**
-* @param {string} input
-* @param {number} level
-* @returns {string}
-*/
-export function parse(input: string, level: number): string;
-/**
-* @param {string} input
-* @returns {string}
-*/
-export function pares_and_print_html(input: string): string;
-/**
-*/
+
+export interface FunctionInfo {
+    name: string;
+    aliases: string[];
+    /** like `log(x), log(b, x)` */
+    signature: string;
+    description: string;
+    /** empty for any argument */
+    domain: string;
+    /** like `sin(pi / 2) = 1` */
+    example: string;
+    minArgs: number;
+    maxArgs: number;
+}
+
+export interface ConstantInfo {
+    name: string;
+    /** formatted by the settings */
+    value: string;
+    description: string;
+}
+
+export interface Reference {
+    functions: FunctionInfo[];
+    constants: ConstantInfo[];
+}
+
+
+
+export type CompletionKind = "variable" | "function" | "builtin" | "constant" | "keyword";
+
+export interface Completion {
+    label: string;
+    kind: CompletionKind;
+    /** the value, or the signature of a function */
+    detail: string;
+}
+
+export interface SignatureHelp {
+    /** like `log(b, x)` */
+    label: string;
+    params: string[];
+    /** the parameter at the cursor, counted from 0 */
+    active: number;
+    description: string;
+}
+
+export interface Completions {
+    /** `[start, end)` of the text a completion replaces, in characters */
+    span: [number, number];
+    items: Completion[];
+    /** missing outside of `f(...)` */
+    signature?: SignatureHelp;
+}
+
+
+
+export type OutputKind = "real" | "complex" | "function" | "text" | "svg";
+
+/** a value written as `[out n]` */
+export interface Output {
+    /** `n` of `[out n]` */
+    index: number;
+    kind: OutputKind;
+    /** `[re]`, `[re, im]`, or empty for functions and texts */
+    value: number[];
+    /** by the format settings, not escaped as in `get_html` */
+    text: string;
+    /** `[start, end)` of the sentence it comes from, in characters of the input */
+    span: [number, number];
+}
+
+export interface OutputError {
+    kind: "syntax" | "include" | "call";
+    /** the included file, missing for the input */
+    file?: string;
+    /** counted from 1, missing for call errors */
+    line?: number;
+    /** counted from 1, missing for call errors */
+    column?: number;
+    /** `[start, end)` in characters of the input, missing for include errors */
+    span?: [number, number];
+    message: string;
+    /** for an unknown name, defined names like it, closest first */
+    suggestions: string[];
+}
+
+export interface Results {
+    outputs: Output[];
+    errors: OutputError[];
+}
+
+
+
 export class Caculator {
-  free(): void;
-/**
-* @param {string} input
-* @returns {Caculator}
-*/
-  static new(input: string): Caculator;
-/**
-* @param {string} input
-*/
-  new_parser(input: string): void;
-/**
-*/
-  parse(): void;
+    private constructor();
+    free(): void;
+    [Symbol.dispose](): void;
+    calc(): void;
+    /**
+     * functions that failed in the last calculation, as `name: message`
+     */
+    call_errors(): string[];
+    /**
+     * forget `ans` and `out[n]`, numbering starts over
+     */
+    clear_history(): void;
+    /**
+     * names for the cursor at `cursor` characters into `input`, from the
+     * variables so far and the assignments of `input`, and the function called
+     * around the cursor. see `Completions` in the typescript definitions
+     */
+    complete(input: string, cursor: number): Completions;
+    /**
+     * variables of the last parsed input that depend on `name`, in calculating order
+     */
+    dependents_of(name: string): string[];
+    /**
+     * `[start, end]` of each diagnostic in characters of the input, flattened
+     */
+    diagnostic_spans(): Uint32Array;
+    /**
+     * why sentences of the last parsed input were skipped, as `line:column: message`
+     */
+    diagnostics(): string[];
+    /**
+     * vars, functions, settings and history as JSON, see `Runtime::export_state`
+     */
+    export_state(): string;
+    get_html(): string;
+    /**
+     * a variable or constant as `set_variable` takes it,
+     * `undefined` for functions and unknown names
+     */
+    get_variable(name: string): any;
+    /**
+     * every output so far, formatted, `out[1]` first
+     */
+    history(): string[];
+    /**
+     * restore a state written by `export_state`
+     */
+    import_state(state: string): void;
+    /**
+     * errors of `include` in the last calculation, as `file:line:column: message`
+     */
+    include_errors(): string[];
+    static new(input: string): Caculator;
+    new_parser(input: string): void;
+    parse(): void;
+    /**
+     * svg of `y = f(x)` for `a ≤ x ≤ b` of each function, given by its name
+     * or as an expression of `x`, like `plot(["sin", "x^2 - 1"], -3, 3)`
+     */
+    plot(functions: string[], a: number, b: number): string;
+    /**
+     * count of sentences calculated by the last `update`
+     */
+    recomputed(): number;
+    /**
+     * every function and constant with what it is, for a reference panel,
+     * see `Reference` in the typescript definitions
+     */
+    reference(): Reference;
+    /**
+     * `name(...)` calls `f` with `arity` arguments, numbers or arrays
+     * as `set_variable` takes them, and what `f` throws is a call error
+     */
+    register_function(name: string, arity: number, f: Function): void;
+    /**
+     * the outputs of the last calculation and the errors of the input,
+     * as plain objects, see `Results` in the typescript definitions
+     */
+    results(): Results;
+    /**
+     * tolerance used by rounding and `zero(x)`
+     */
+    set_epsilon(epsilon: number): void;
+    /**
+     * name of the input as a file, `include` paths are relative to its folder.
+     * empty string for none
+     */
+    set_file(name: string): void;
+    /**
+     * `include("file")` reads files by `load(name)`, which returns
+     * the text or `undefined`. names are `/` separated paths
+     */
+    set_include_resolver(load: Function): void;
+    /**
+     * `notation`: `auto`, `fixed`, `sig`, `sci` or `eng`,
+     * `digits`: decimals or significant figures.
+     * returns false for unknown notations and more than 17 digits
+     */
+    set_notation(notation: string, digits: number): boolean;
+    /**
+     * write multiples of `π` like `3π/4`
+     */
+    set_pi(pi: boolean): void;
+    /**
+     * round away float noise like `0.30000000000000004`
+     */
+    set_rounding(rounding: boolean): void;
+    /**
+     * thousands separator, empty string for none
+     */
+    set_separator(separator: string): void;
+    /**
+     * a variable for the sentences to read, kept by `update` and `import_state`.
+     * numbers are reals, `{ re, im }` complex numbers, strings texts,
+     * arrays, `Float64Array`s and nested arrays tuples
+     */
+    set_variable(name: string, value: any): void;
+    /**
+     * calculate `input` as a whole, as if in a new runtime,
+     * but only the sentences affected by the edit are calculated again
+     */
+    update(input: string): void;
+    /**
+     * calculate `input` like a spreadsheet: sentences may use variables
+     * assigned after them, and like `update` only the sentences affected
+     * by the edit are calculated again.
+     * returns the error for cycles
+     */
+    update_reactive(input: string): void;
+}
+
 /**
-*/
-  calc(): void;
+ * how `format` lays out the text
+ */
+export class FormatOptions {
+    free(): void;
+    [Symbol.dispose](): void;
+    constructor();
+    /**
+     * spaces for each level of blocks and wrapped arguments
+     */
+    indent: number;
+    /**
+     * spaces around binary operators, `1 + 2` rather than `1+2`
+     */
+    spaced: boolean;
+    /**
+     * wanted length of lines, longer argument lists are put one per line
+     */
+    width: number;
+}
+
 /**
-* @returns {string}
-*/
-  get_html(): string;
+ * `[start, end)` in characters of the input
+ */
+export class Highlight {
+    private constructor();
+    free(): void;
+    [Symbol.dispose](): void;
+    end: number;
+    kind: HighlightKind;
+    start: number;
 }
+
 /**
-*/
+ * what a piece of the text is, for colouring it
+ */
+export enum HighlightKind {
+    Number = 0,
+    String = 1,
+    Char = 2,
+    Bool = 3,
+    /**
+     * a name that is read or assigned
+     */
+    Variable = 4,
+    /**
+     * a name that is called or defined as a function
+     */
+    Function = 5,
+    /**
+     * a name in `f(x, y) =`
+     */
+    Parameter = 6,
+    Operator = 7,
+    /**
+     * brackets, `,` and `;`
+     */
+    Punctuation = 8,
+    Comment = 9,
+    /**
+     * malformed tokens and unterminated comments
+     */
+    Error = 10,
+}
+
 export class MyStruct {
-  free(): void;
+    private constructor();
+    free(): void;
+    [Symbol.dispose](): void;
+    add_value(v: number): void;
+    get_value(): number;
+    static new(value: number): MyStruct;
+    flag: boolean;
+}
+
+export function create_struct(): MyStruct;
+
 /**
-* @param {number} value
-* @returns {MyStruct}
-*/
-  static new(value: number): MyStruct;
+ * the input pretty-printed: a sentence a line, blocks indented, comments kept
+ */
+export function format(input: string): string;
+
+export function format_with(input: string, options: FormatOptions): string;
+
 /**
-* @returns {number}
-*/
-  get_value(): number;
+ * `[start, end)` and kind of every token but whitespaces, in characters of the input
+ */
+export function highlight(input: string): Highlight[];
+
 /**
-* @param {number} v
-*/
-  add_value(v: number): void;
+ * the input as html, each token in a `<span>` with a class of `highlight.css`
+ */
+export function highlight_html(input: string): string;
+
+export function pares_and_print_html(input: string): string;
+
+export function parse(input: string, level: number): string;
+
 /**
-*/
-  flag: boolean;
-}
+ * the syntax tree as plain text
+ */
+export function tree(input: string): string;
+
+/**
+ * the input needs more lines to close a `{ }` block or a multi-line comment
+ */
+export function unclosed(input: string): boolean;
 
 export type InitInput = RequestInfo | URL | Response | BufferSource | WebAssembly.Module;
 
 export interface InitOutput {
-  readonly memory: WebAssembly.Memory;
-  readonly __wbg_mystruct_free: (a: number) => void;
-  readonly __wbg_get_mystruct_flag: (a: number) => number;
-  readonly __wbg_set_mystruct_flag: (a: number, b: number) => void;
-  readonly mystruct_new: (a: number) => number;
-  readonly mystruct_get_value: (a: number) => number;
-  readonly mystruct_add_value: (a: number, b: number) => void;
-  readonly create_struct: () => number;
-  readonly parse: (a: number, b: number, c: number, d: number) => void;
-  readonly pares_and_print_html: (a: number, b: number, c: number) => void;
-  readonly __wbg_caculator_free: (a: number) => void;
-  readonly caculator_new: (a: number, b: number) => number;
-  readonly caculator_new_parser: (a: number, b: number, c: number) => void;
-  readonly caculator_parse: (a: number) => void;
-  readonly caculator_calc: (a: number) => void;
-  readonly caculator_get_html: (a: number, b: number) => void;
-  readonly __wbindgen_add_to_stack_pointer: (a: number) => number;
-  readonly __wbindgen_malloc: (a: number, b: number) => number;
-  readonly __wbindgen_realloc: (a: number, b: number, c: number, d: number) => number;
-  readonly __wbindgen_free: (a: number, b: number, c: number) => void;
+    readonly memory: WebAssembly.Memory;
+    readonly __wbg_caculator_free: (a: number, b: number) => void;
+    readonly __wbg_formatoptions_free: (a: number, b: number) => void;
+    readonly __wbg_get_formatoptions_indent: (a: number) => number;
+    readonly __wbg_get_formatoptions_spaced: (a: number) => number;
+    readonly __wbg_get_formatoptions_width: (a: number) => number;
+    readonly __wbg_get_highlight_end: (a: number) => number;
+    readonly __wbg_get_highlight_kind: (a: number) => number;
+    readonly __wbg_get_highlight_start: (a: number) => number;
+    readonly __wbg_get_mystruct_flag: (a: number) => number;
+    readonly __wbg_highlight_free: (a: number, b: number) => void;
+    readonly __wbg_mystruct_free: (a: number, b: number) => void;
+    readonly __wbg_set_formatoptions_indent: (a: number, b: number) => void;
+    readonly __wbg_set_formatoptions_spaced: (a: number, b: number) => void;
+    readonly __wbg_set_formatoptions_width: (a: number, b: number) => void;
+    readonly __wbg_set_highlight_end: (a: number, b: number) => void;
+    readonly __wbg_set_highlight_kind: (a: number, b: number) => void;
+    readonly __wbg_set_highlight_start: (a: number, b: number) => void;
+    readonly __wbg_set_mystruct_flag: (a: number, b: number) => void;
+    readonly caculator_calc: (a: number) => void;
+    readonly caculator_call_errors: (a: number) => [number, number];
+    readonly caculator_clear_history: (a: number) => void;
+    readonly caculator_complete: (a: number, b: number, c: number, d: number) => any;
+    readonly caculator_dependents_of: (a: number, b: number, c: number) => [number, number];
+    readonly caculator_diagnostic_spans: (a: number) => [number, number];
+    readonly caculator_diagnostics: (a: number) => [number, number];
+    readonly caculator_export_state: (a: number) => [number, number];
+    readonly caculator_get_html: (a: number) => [number, number];
+    readonly caculator_get_variable: (a: number, b: number, c: number) => any;
+    readonly caculator_history: (a: number) => [number, number];
+    readonly caculator_import_state: (a: number, b: number, c: number) => [number, number];
+    readonly caculator_include_errors: (a: number) => [number, number];
+    readonly caculator_new: (a: number, b: number) => number;
+    readonly caculator_new_parser: (a: number, b: number, c: number) => void;
+    readonly caculator_parse: (a: number) => void;
+    readonly caculator_plot: (a: number, b: number, c: number, d: number, e: number) => [number, number, number, number];
+    readonly caculator_recomputed: (a: number) => number;
+    readonly caculator_reference: (a: number) => any;
+    readonly caculator_register_function: (a: number, b: number, c: number, d: number, e: any) => [number, number];
+    readonly caculator_results: (a: number) => any;
+    readonly caculator_set_epsilon: (a: number, b: number) => void;
+    readonly caculator_set_file: (a: number, b: number, c: number) => void;
+    readonly caculator_set_include_resolver: (a: number, b: any) => void;
+    readonly caculator_set_notation: (a: number, b: number, c: number, d: number) => number;
+    readonly caculator_set_pi: (a: number, b: number) => void;
+    readonly caculator_set_rounding: (a: number, b: number) => void;
+    readonly caculator_set_separator: (a: number, b: number, c: number) => void;
+    readonly caculator_set_variable: (a: number, b: number, c: number, d: any) => [number, number];
+    readonly caculator_update: (a: number, b: number, c: number) => void;
+    readonly caculator_update_reactive: (a: number, b: number, c: number) => [number, number];
+    readonly create_struct: () => number;
+    readonly format: (a: number, b: number) => [number, number];
+    readonly format_with: (a: number, b: number, c: number) => [number, number];
+    readonly formatoptions_new: () => number;
+    readonly highlight: (a: number, b: number) => [number, number];
+    readonly highlight_html: (a: number, b: number) => [number, number];
+    readonly mystruct_add_value: (a: number, b: number) => void;
+    readonly mystruct_get_value: (a: number) => number;
+    readonly mystruct_new: (a: number) => number;
+    readonly pares_and_print_html: (a: number, b: number) => [number, number];
+    readonly parse: (a: number, b: number, c: number) => [number, number];
+    readonly tree: (a: number, b: number) => [number, number];
+    readonly unclosed: (a: number, b: number) => number;
+    readonly __wbindgen_malloc: (a: number, b: number) => number;
+    readonly __wbindgen_realloc: (a: number, b: number, c: number, d: number) => number;
+    readonly __wbindgen_exn_store: (a: number) => void;
+    readonly __externref_table_alloc: () => number;
+    readonly __wbindgen_externrefs: WebAssembly.Table;
+    readonly __externref_drop_slice: (a: number, b: number) => void;
+    readonly __wbindgen_free: (a: number, b: number, c: number) => void;
+    readonly __externref_table_dealloc: (a: number) => void;
+    readonly __wbindgen_start: () => void;
 }
 
 export type SyncInitInput = BufferSource | WebAssembly.Module;
+
 /**
-* Instantiates the given `module`, which can either be bytes or
-* a precompiled `WebAssembly.Module`.
-*
-* @param {SyncInitInput} module
-*
-* @returns {InitOutput}
-*/
-export function initSync(module: SyncInitInput): InitOutput;
+ * Instantiates the given `module`, which can either be bytes or
+ * a precompiled `WebAssembly.Module`.
+ *
+ * @param {{ module: SyncInitInput }} module - Passing `SyncInitInput` directly is deprecated.
+ *
+ * @returns {InitOutput}
+ */
+export function initSync(module: { module: SyncInitInput } | SyncInitInput): InitOutput;
 
 /**
-* If `module_or_path` is {RequestInfo} or {URL}, makes a request and
-* for everything else, calls `WebAssembly.instantiate` directly.
-*
-* @param {InitInput | Promise<InitInput>} module_or_path
-*
-* @returns {Promise<InitOutput>}
-*/
-export default function __wbg_init (module_or_path?: InitInput | Promise<InitInput>): Promise<InitOutput>;
+ * If `module_or_path` is {RequestInfo} or {URL}, makes a request and
+ * for everything else, calls `WebAssembly.instantiate` directly.
+ *
+ * @param {{ module_or_path: InitInput | Promise<InitInput> }} module_or_path - Passing `InitInput` directly is deprecated.
+ *
+ * @returns {Promise<InitOutput>}
+ */
+export default function __wbg_init (module_or_path?: { module_or_path: InitInput | Promise<InitInput> } | InitInput | Promise<InitInput>): Promise<InitOutput>;
@@ -1,370 +1,1210 @@
-let wasm;
+/* @ts-self-types="./web_caculator_rust_wasm.d.ts" */
 
-const cachedTextDecoder = (typeof TextDecoder !== 'undefined' ? new TextDecoder('utf-8', { ignoreBOM: true, fatal: true }) : { decode: () => { throw Error('TextDecoder not available') } } );
-
-if (typeof TextDecoder !== 'undefined') { cachedTextDecoder.decode(); };
-
-let cachedUint8Memory0 = null;
-
-function getUint8Memory0() {
-    if (cachedUint8Memory0 === null || cachedUint8Memory0.byteLength === 0) {
-        cachedUint8Memory0 = new Uint8Array(wasm.memory.buffer);
+export class Caculator {
+    static __wrap(ptr) {
+        const obj = Object.create(Caculator.prototype);
+        obj.__wbg_ptr = ptr;
+        CaculatorFinalization.register(obj, obj.__wbg_ptr, obj);
+        return obj;
     }
-    return cachedUint8Memory0;
-}
-
-function getStringFromWasm0(ptr, len) {
-    ptr = ptr >>> 0;
-    return cachedTextDecoder.decode(getUint8Memory0().subarray(ptr, ptr + len));
-}
-/**
-* @returns {MyStruct}
-*/
-export function create_struct() {
-    const ret = wasm.create_struct();
-    return MyStruct.__wrap(ret);
-}
-
-let WASM_VECTOR_LEN = 0;
-
-const cachedTextEncoder = (typeof TextEncoder !== 'undefined' ? new TextEncoder('utf-8') : { encode: () => { throw Error('TextEncoder not available') } } );
-
-const encodeString = (typeof cachedTextEncoder.encodeInto === 'function'
-    ? function (arg, view) {
-    return cachedTextEncoder.encodeInto(arg, view);
-}
-    : function (arg, view) {
-    const buf = cachedTextEncoder.encode(arg);
-    view.set(buf);
-    return {
-        read: arg.length,
-        written: buf.length
-    };
-});
-
-function passStringToWasm0(arg, malloc, realloc) {
-
-    if (realloc === undefined) {
-        const buf = cachedTextEncoder.encode(arg);
-        const ptr = malloc(buf.length, 1) >>> 0;
-        getUint8Memory0().subarray(ptr, ptr + buf.length).set(buf);
-        WASM_VECTOR_LEN = buf.length;
+    __destroy_into_raw() {
+        const ptr = this.__wbg_ptr;
+        this.__wbg_ptr = 0;
+        CaculatorFinalization.unregister(this);
         return ptr;
     }
-
-    let len = arg.length;
-    let ptr = malloc(len, 1) >>> 0;
-
-    const mem = getUint8Memory0();
-
-    let offset = 0;
-
-    for (; offset < len; offset++) {
-        const code = arg.charCodeAt(offset);
-        if (code > 0x7F) break;
-        mem[ptr + offset] = code;
+    free() {
+        const ptr = this.__destroy_into_raw();
+        wasm.__wbg_caculator_free(ptr, 0);
     }
-
-    if (offset !== len) {
-        if (offset !== 0) {
-            arg = arg.slice(offset);
+    calc() {
+        wasm.caculator_calc(this.__wbg_ptr);
+    }
+    /**
+     * functions that failed in the last calculation, as `name: message`
+     * @returns {string[]}
+     */
+    call_errors() {
+        const ret = wasm.caculator_call_errors(this.__wbg_ptr);
+        var v1 = getArrayJsValueFromWasm0(ret[0], ret[1]);
+        wasm.__wbindgen_free(ret[0], ret[1] * 4, 4);
+        return v1;
+    }
+    /**
+     * forget `ans` and `out[n]`, numbering starts over
+     */
+    clear_history() {
+        wasm.caculator_clear_history(this.__wbg_ptr);
+    }
+    /**
+     * names for the cursor at `cursor` characters into `input`, from the
+     * variables so far and the assignments of `input`, and the function called
+     * around the cursor. see `Completions` in the typescript definitions
+     * @param {string} input
+     * @param {number} cursor
+     * @returns {Completions}
+     */
+    complete(input, cursor) {
+        const ptr0 = passStringToWasm0(input, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ret = wasm.caculator_complete(this.__wbg_ptr, ptr0, len0, cursor);
+        return ret;
+    }
+    /**
+     * variables of the last parsed input that depend on `name`, in calculating order
+     * @param {string} name
+     * @returns {string[]}
+     */
+    dependents_of(name) {
+        const ptr0 = passStringToWasm0(name, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ret = wasm.caculator_dependents_of(this.__wbg_ptr, ptr0, len0);
+        var v2 = getArrayJsValueFromWasm0(ret[0], ret[1]);
+        wasm.__wbindgen_free(ret[0], ret[1] * 4, 4);
+        return v2;
+    }
+    /**
+     * `[start, end]` of each diagnostic in characters of the input, flattened
+     * @returns {Uint32Array}
+     */
+    diagnostic_spans() {
+        const ret = wasm.caculator_diagnostic_spans(this.__wbg_ptr);
+        var v1 = getArrayU32FromWasm0(ret[0], ret[1]).slice();
+        wasm.__wbindgen_free(ret[0], ret[1] * 4, 4);
+        return v1;
+    }
+    /**
+     * why sentences of the last parsed input were skipped, as `line:column: message`
+     * @returns {string[]}
+     */
+    diagnostics() {
+        const ret = wasm.caculator_diagnostics(this.__wbg_ptr);
+        var v1 = getArrayJsValueFromWasm0(ret[0], ret[1]);
+        wasm.__wbindgen_free(ret[0], ret[1] * 4, 4);
+        return v1;
+    }
+    /**
+     * vars, functions, settings and history as JSON, see `Runtime::export_state`
+     * @returns {string}
+     */
+    export_state() {
+        let deferred1_0;
+        let deferred1_1;
+        try {
+            const ret = wasm.caculator_export_state(this.__wbg_ptr);
+            deferred1_0 = ret[0];
+            deferred1_1 = ret[1];
+            return getStringFromWasm0(ret[0], ret[1]);
+        } finally {
+            wasm.__wbindgen_free(deferred1_0, deferred1_1, 1);
         }
-        ptr = realloc(ptr, len, len = offset + arg.length * 3, 1) >>> 0;
-        const view = getUint8Memory0().subarray(ptr + offset, ptr + len);
-        const ret = encodeString(arg, view);
-
-        offset += ret.written;
-        ptr = realloc(ptr, len, offset, 1) >>> 0;
     }
-
-    WASM_VECTOR_LEN = offset;
-    return ptr;
-}
-
-let cachedInt32Memory0 = null;
-
-function getInt32Memory0() {
-    if (cachedInt32Memory0 === null || cachedInt32Memory0.byteLength === 0) {
-        cachedInt32Memory0 = new Int32Array(wasm.memory.buffer);
+    /**
+     * @returns {string}
+     */
+    get_html() {
+        let deferred1_0;
+        let deferred1_1;
+        try {
+            const ret = wasm.caculator_get_html(this.__wbg_ptr);
+            deferred1_0 = ret[0];
+            deferred1_1 = ret[1];
+            return getStringFromWasm0(ret[0], ret[1]);
+        } finally {
+            wasm.__wbindgen_free(deferred1_0, deferred1_1, 1);
+        }
     }
-    return cachedInt32Memory0;
-}
-/**
-* @param {string} input
-* @param {number} level
-* @returns {string}
-*/
-export function parse(input, level) {
-    let deferred2_0;
-    let deferred2_1;
-    try {
-        const retptr = wasm.__wbindgen_add_to_stack_pointer(-16);
+    /**
+     * a variable or constant as `set_variable` takes it,
+     * `undefined` for functions and unknown names
+     * @param {string} name
+     * @returns {any}
+     */
+    get_variable(name) {
+        const ptr0 = passStringToWasm0(name, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ret = wasm.caculator_get_variable(this.__wbg_ptr, ptr0, len0);
+        return ret;
+    }
+    /**
+     * every output so far, formatted, `out[1]` first
+     * @returns {string[]}
+     */
+    history() {
+        const ret = wasm.caculator_history(this.__wbg_ptr);
+        var v1 = getArrayJsValueFromWasm0(ret[0], ret[1]);
+        wasm.__wbindgen_free(ret[0], ret[1] * 4, 4);
+        return v1;
+    }
+    /**
+     * restore a state written by `export_state`
+     * @param {string} state
+     */
+    import_state(state) {
+        const ptr0 = passStringToWasm0(state, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ret = wasm.caculator_import_state(this.__wbg_ptr, ptr0, len0);
+        if (ret[1]) {
+            throw takeFromExternrefTable0(ret[0]);
+        }
+    }
+    /**
+     * errors of `include` in the last calculation, as `file:line:column: message`
+     * @returns {string[]}
+     */
+    include_errors() {
+        const ret = wasm.caculator_include_errors(this.__wbg_ptr);
+        var v1 = getArrayJsValueFromWasm0(ret[0], ret[1]);
+        wasm.__wbindgen_free(ret[0], ret[1] * 4, 4);
+        return v1;
+    }
+    /**
+     * @param {string} input
+     * @returns {Caculator}
+     */
+    static new(input) {
         const ptr0 = passStringToWasm0(input, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
         const len0 = WASM_VECTOR_LEN;
-        wasm.parse(retptr, ptr0, len0, level);
-        var r0 = getInt32Memory0()[retptr / 4 + 0];
-        var r1 = getInt32Memory0()[retptr / 4 + 1];
-        deferred2_0 = r0;
-        deferred2_1 = r1;
-        return getStringFromWasm0(r0, r1);
-    } finally {
-        wasm.__wbindgen_add_to_stack_pointer(16);
-        wasm.__wbindgen_free(deferred2_0, deferred2_1, 1);
+        const ret = wasm.caculator_new(ptr0, len0);
+        return Caculator.__wrap(ret);
     }
-}
-
-/**
-* @param {string} input
-* @returns {string}
-*/
-export function pares_and_print_html(input) {
-    let deferred2_0;
-    let deferred2_1;
-    try {
-        const retptr = wasm.__wbindgen_add_to_stack_pointer(-16);
+    /**
+     * @param {string} input
+     */
+    new_parser(input) {
         const ptr0 = passStringToWasm0(input, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
         const len0 = WASM_VECTOR_LEN;
-        wasm.pares_and_print_html(retptr, ptr0, len0);
-        var r0 = getInt32Memory0()[retptr / 4 + 0];
-        var r1 = getInt32Memory0()[retptr / 4 + 1];
-        deferred2_0 = r0;
-        deferred2_1 = r1;
-        return getStringFromWasm0(r0, r1);
-    } finally {
-        wasm.__wbindgen_add_to_stack_pointer(16);
-        wasm.__wbindgen_free(deferred2_0, deferred2_1, 1);
+        wasm.caculator_new_parser(this.__wbg_ptr, ptr0, len0);
+    }
+    parse() {
+        wasm.caculator_parse(this.__wbg_ptr);
+    }
+    /**
+     * svg of `y = f(x)` for `a ≤ x ≤ b` of each function, given by its name
+     * or as an expression of `x`, like `plot(["sin", "x^2 - 1"], -3, 3)`
+     * @param {string[]} functions
+     * @param {number} a
+     * @param {number} b
+     * @returns {string}
+     */
+    plot(functions, a, b) {
+        let deferred3_0;
+        let deferred3_1;
+        try {
+            const ptr0 = passArrayJsValueToWasm0(functions, wasm.__wbindgen_malloc);
+            const len0 = WASM_VECTOR_LEN;
+            const ret = wasm.caculator_plot(this.__wbg_ptr, ptr0, len0, a, b);
+            var ptr2 = ret[0];
+            var len2 = ret[1];
+            if (ret[3]) {
+                ptr2 = 0; len2 = 0;
+                throw takeFromExternrefTable0(ret[2]);
+            }
+            deferred3_0 = ptr2;
+            deferred3_1 = len2;
+            return getStringFromWasm0(ptr2, len2);
+        } finally {
+            wasm.__wbindgen_free(deferred3_0, deferred3_1, 1);
+        }
+    }
+    /**
+     * count of sentences calculated by the last `update`
+     * @returns {number}
+     */
+    recomputed() {
+        const ret = wasm.caculator_recomputed(this.__wbg_ptr);
+        return ret >>> 0;
+    }
+    /**
+     * every function and constant with what it is, for a reference panel,
+     * see `Reference` in the typescript definitions
+     * @returns {Reference}
+     */
+    reference() {
+        const ret = wasm.caculator_reference(this.__wbg_ptr);
+        return ret;
+    }
+    /**
+     * `name(...)` calls `f` with `arity` arguments, numbers or arrays
+     * as `set_variable` takes them, and what `f` throws is a call error
+     * @param {string} name
+     * @param {number} arity
+     * @param {Function} f
+     */
+    register_function(name, arity, f) {
+        const ptr0 = passStringToWasm0(name, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ret = wasm.caculator_register_function(this.__wbg_ptr, ptr0, len0, arity, f);
+        if (ret[1]) {
+            throw takeFromExternrefTable0(ret[0]);
+        }
+    }
+    /**
+     * the outputs of the last calculation and the errors of the input,
+     * as plain objects, see `Results` in the typescript definitions
+     * @returns {Results}
+     */
+    results() {
+        const ret = wasm.caculator_results(this.__wbg_ptr);
+        return ret;
+    }
+    /**
+     * tolerance used by rounding and `zero(x)`
+     * @param {number} epsilon
+     */
+    set_epsilon(epsilon) {
+        wasm.caculator_set_epsilon(this.__wbg_ptr, epsilon);
+    }
+    /**
+     * name of the input as a file, `include` paths are relative to its folder.
+     * empty string for none
+     * @param {string} name
+     */
+    set_file(name) {
+        const ptr0 = passStringToWasm0(name, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        wasm.caculator_set_file(this.__wbg_ptr, ptr0, len0);
+    }
+    /**
+     * `include("file")` reads files by `load(name)`, which returns
+     * the text or `undefined`. names are `/` separated paths
+     * @param {Function} load
+     */
+    set_include_resolver(load) {
+        wasm.caculator_set_include_resolver(this.__wbg_ptr, load);
+    }
+    /**
+     * `notation`: `auto`, `fixed`, `sig`, `sci` or `eng`,
+     * `digits`: decimals or significant figures.
+     * returns false for unknown notations and more than 17 digits
+     * @param {string} notation
+     * @param {number} digits
+     * @returns {boolean}
+     */
+    set_notation(notation, digits) {
+        const ptr0 = passStringToWasm0(notation, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ret = wasm.caculator_set_notation(this.__wbg_ptr, ptr0, len0, digits);
+        return ret !== 0;
+    }
+    /**
+     * write multiples of `π` like `3π/4`
+     * @param {boolean} pi
+     */
+    set_pi(pi) {
+        wasm.caculator_set_pi(this.__wbg_ptr, pi);
+    }
+    /**
+     * round away float noise like `0.30000000000000004`
+     * @param {boolean} rounding
+     */
+    set_rounding(rounding) {
+        wasm.caculator_set_rounding(this.__wbg_ptr, rounding);
+    }
+    /**
+     * thousands separator, empty string for none
+     * @param {string} separator
+     */
+    set_separator(separator) {
+        const ptr0 = passStringToWasm0(separator, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        wasm.caculator_set_separator(this.__wbg_ptr, ptr0, len0);
+    }
+    /**
+     * a variable for the sentences to read, kept by `update` and `import_state`.
+     * numbers are reals, `{ re, im }` complex numbers, strings texts,
+     * arrays, `Float64Array`s and nested arrays tuples
+     * @param {string} name
+     * @param {any} value
+     */
+    set_variable(name, value) {
+        const ptr0 = passStringToWasm0(name, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ret = wasm.caculator_set_variable(this.__wbg_ptr, ptr0, len0, value);
+        if (ret[1]) {
+            throw takeFromExternrefTable0(ret[0]);
+        }
+    }
+    /**
+     * calculate `input` as a whole, as if in a new runtime,
+     * but only the sentences affected by the edit are calculated again
+     * @param {string} input
+     */
+    update(input) {
+        const ptr0 = passStringToWasm0(input, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        wasm.caculator_update(this.__wbg_ptr, ptr0, len0);
+    }
+    /**
+     * calculate `input` like a spreadsheet: sentences may use variables
+     * assigned after them, and like `update` only the sentences affected
+     * by the edit are calculated again.
+     * returns the error for cycles
+     * @param {string} input
+     */
+    update_reactive(input) {
+        const ptr0 = passStringToWasm0(input, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ret = wasm.caculator_update_reactive(this.__wbg_ptr, ptr0, len0);
+        if (ret[1]) {
+            throw takeFromExternrefTable0(ret[0]);
+        }
     }
 }
+if (Symbol.dispose) Caculator.prototype[Symbol.dispose] = Caculator.prototype.free;
 
-const CaculatorFinalization = (typeof FinalizationRegistry === 'undefined')
-    ? { register: () => {}, unregister: () => {} }
-    : new FinalizationRegistry(ptr => wasm.__wbg_caculator_free(ptr >>> 0));
 /**
-*/
-export class Caculator {
+ * how `format` lays out the text
+ */
+export class FormatOptions {
+    __destroy_into_raw() {
+        const ptr = this.__wbg_ptr;
+        this.__wbg_ptr = 0;
+        FormatOptionsFinalization.unregister(this);
+        return ptr;
+    }
+    free() {
+        const ptr = this.__destroy_into_raw();
+        wasm.__wbg_formatoptions_free(ptr, 0);
+    }
+    constructor() {
+        const ret = wasm.formatoptions_new();
+        this.__wbg_ptr = ret;
+        FormatOptionsFinalization.register(this, this.__wbg_ptr, this);
+        return this;
+    }
+    /**
+     * spaces for each level of blocks and wrapped arguments
+     * @returns {number}
+     */
+    get indent() {
+        const ret = wasm.__wbg_get_formatoptions_indent(this.__wbg_ptr);
+        return ret >>> 0;
+    }
+    /**
+     * spaces around binary operators, `1 + 2` rather than `1+2`
+     * @returns {boolean}
+     */
+    get spaced() {
+        const ret = wasm.__wbg_get_formatoptions_spaced(this.__wbg_ptr);
+        return ret !== 0;
+    }
+    /**
+     * wanted length of lines, longer argument lists are put one per line
+     * @returns {number}
+     */
+    get width() {
+        const ret = wasm.__wbg_get_formatoptions_width(this.__wbg_ptr);
+        return ret >>> 0;
+    }
+    /**
+     * spaces for each level of blocks and wrapped arguments
+     * @param {number} arg0
+     */
+    set indent(arg0) {
+        wasm.__wbg_set_formatoptions_indent(this.__wbg_ptr, arg0);
+    }
+    /**
+     * spaces around binary operators, `1 + 2` rather than `1+2`
+     * @param {boolean} arg0
+     */
+    set spaced(arg0) {
+        wasm.__wbg_set_formatoptions_spaced(this.__wbg_ptr, arg0);
+    }
+    /**
+     * wanted length of lines, longer argument lists are put one per line
+     * @param {number} arg0
+     */
+    set width(arg0) {
+        wasm.__wbg_set_formatoptions_width(this.__wbg_ptr, arg0);
+    }
+}
+if (Symbol.dispose) FormatOptions.prototype[Symbol.dispose] = FormatOptions.prototype.free;
 
+/**
+ * `[start, end)` in characters of the input
+ */
+export class Highlight {
     static __wrap(ptr) {
-        ptr = ptr >>> 0;
-        const obj = Object.create(Caculator.prototype);
+        const obj = Object.create(Highlight.prototype);
         obj.__wbg_ptr = ptr;
-        CaculatorFinalization.register(obj, obj.__wbg_ptr, obj);
+        HighlightFinalization.register(obj, obj.__wbg_ptr, obj);
         return obj;
     }
-
     __destroy_into_raw() {
         const ptr = this.__wbg_ptr;
         this.__wbg_ptr = 0;
-        CaculatorFinalization.unregister(this);
+        HighlightFinalization.unregister(this);
         return ptr;
     }
-
     free() {
         const ptr = this.__destroy_into_raw();
-        wasm.__wbg_caculator_free(ptr);
+        wasm.__wbg_highlight_free(ptr, 0);
     }
     /**
-    * @param {string} input
-    * @returns {Caculator}
-    */
-    static new(input) {
-        const ptr0 = passStringToWasm0(input, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
-        const len0 = WASM_VECTOR_LEN;
-        const ret = wasm.caculator_new(ptr0, len0);
-        return Caculator.__wrap(ret);
+     * @returns {number}
+     */
+    get end() {
+        const ret = wasm.__wbg_get_highlight_end(this.__wbg_ptr);
+        return ret >>> 0;
     }
     /**
-    * @param {string} input
-    */
-    new_parser(input) {
-        const ptr0 = passStringToWasm0(input, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
-        const len0 = WASM_VECTOR_LEN;
-        wasm.caculator_new_parser(this.__wbg_ptr, ptr0, len0);
+     * @returns {HighlightKind}
+     */
+    get kind() {
+        const ret = wasm.__wbg_get_highlight_kind(this.__wbg_ptr);
+        return ret;
     }
     /**
-    */
-    parse() {
-        wasm.caculator_parse(this.__wbg_ptr);
+     * @returns {number}
+     */
+    get start() {
+        const ret = wasm.__wbg_get_highlight_start(this.__wbg_ptr);
+        return ret >>> 0;
     }
     /**
-    */
-    calc() {
-        wasm.caculator_calc(this.__wbg_ptr);
+     * @param {number} arg0
+     */
+    set end(arg0) {
+        wasm.__wbg_set_highlight_end(this.__wbg_ptr, arg0);
     }
     /**
-    * @returns {string}
-    */
-    get_html() {
-        let deferred1_0;
-        let deferred1_1;
-        try {
-            const retptr = wasm.__wbindgen_add_to_stack_pointer(-16);
-            wasm.caculator_get_html(retptr, this.__wbg_ptr);
-            var r0 = getInt32Memory0()[retptr / 4 + 0];
-            var r1 = getInt32Memory0()[retptr / 4 + 1];
-            deferred1_0 = r0;
-            deferred1_1 = r1;
-            return getStringFromWasm0(r0, r1);
-        } finally {
-            wasm.__wbindgen_add_to_stack_pointer(16);
-            wasm.__wbindgen_free(deferred1_0, deferred1_1, 1);
-        }
+     * @param {HighlightKind} arg0
+     */
+    set kind(arg0) {
+        wasm.__wbg_set_highlight_kind(this.__wbg_ptr, arg0);
+    }
+    /**
+     * @param {number} arg0
+     */
+    set start(arg0) {
+        wasm.__wbg_set_highlight_start(this.__wbg_ptr, arg0);
     }
 }
+if (Symbol.dispose) Highlight.prototype[Symbol.dispose] = Highlight.prototype.free;
 
-const MyStructFinalization = (typeof FinalizationRegistry === 'undefined')
-    ? { register: () => {}, unregister: () => {} }
-    : new FinalizationRegistry(ptr => wasm.__wbg_mystruct_free(ptr >>> 0));
 /**
-*/
-export class MyStruct {
+ * what a piece of the text is, for colouring it
+ * @enum {0 | 1 | 2 | 3 | 4 | 5 | 6 | 7 | 8 | 9 | 10}
+ */
+export const HighlightKind = Object.freeze({
+    Number: 0, "0": "Number",
+    String: 1, "1": "String",
+    Char: 2, "2": "Char",
+    Bool: 3, "3": "Bool",
+    /**
+     * a name that is read or assigned
+     */
+    Variable: 4, "4": "Variable",
+    /**
+     * a name that is called or defined as a function
+     */
+    Function: 5, "5": "Function",
+    /**
+     * a name in `f(x, y) =`
+     */
+    Parameter: 6, "6": "Parameter",
+    Operator: 7, "7": "Operator",
+    /**
+     * brackets, `,` and `;`
+     */
+    Punctuation: 8, "8": "Punctuation",
+    Comment: 9, "9": "Comment",
+    /**
+     * malformed tokens and unterminated comments
+     */
+    Error: 10, "10": "Error",
+});
 
+export class MyStruct {
     static __wrap(ptr) {
-        ptr = ptr >>> 0;
         const obj = Object.create(MyStruct.prototype);
         obj.__wbg_ptr = ptr;
         MyStructFinalization.register(obj, obj.__wbg_ptr, obj);
         return obj;
     }
-
     __destroy_into_raw() {
         const ptr = this.__wbg_ptr;
         this.__wbg_ptr = 0;
         MyStructFinalization.unregister(this);
         return ptr;
     }
-
     free() {
         const ptr = this.__destroy_into_raw();
-        wasm.__wbg_mystruct_free(ptr);
+        wasm.__wbg_mystruct_free(ptr, 0);
     }
     /**
-    * @returns {boolean}
-    */
+     * @returns {boolean}
+     */
     get flag() {
         const ret = wasm.__wbg_get_mystruct_flag(this.__wbg_ptr);
         return ret !== 0;
     }
     /**
-    * @param {boolean} arg0
-    */
-    set flag(arg0) {
-        wasm.__wbg_set_mystruct_flag(this.__wbg_ptr, arg0);
+     * @param {number} v
+     */
+    add_value(v) {
+        wasm.mystruct_add_value(this.__wbg_ptr, v);
+    }
+    /**
+     * @returns {number}
+     */
+    get_value() {
+        const ret = wasm.mystruct_get_value(this.__wbg_ptr);
+        return ret;
     }
     /**
-    * @param {number} value
-    * @returns {MyStruct}
-    */
+     * @param {number} value
+     * @returns {MyStruct}
+     */
     static new(value) {
         const ret = wasm.mystruct_new(value);
         return MyStruct.__wrap(ret);
     }
     /**
-    * @returns {number}
-    */
-    get_value() {
-        const ret = wasm.mystruct_get_value(this.__wbg_ptr);
-        return ret;
+     * @param {boolean} arg0
+     */
+    set flag(arg0) {
+        wasm.__wbg_set_mystruct_flag(this.__wbg_ptr, arg0);
     }
-    /**
-    * @param {number} v
-    */
-    add_value(v) {
-        wasm.mystruct_add_value(this.__wbg_ptr, v);
+}
+if (Symbol.dispose) MyStruct.prototype[Symbol.dispose] = MyStruct.prototype.free;
+
+/**
+ * @returns {MyStruct}
+ */
+export function create_struct() {
+    const ret = wasm.create_struct();
+    return MyStruct.__wrap(ret);
+}
+
+/**
+ * the input pretty-printed: a sentence a line, blocks indented, comments kept
+ * @param {string} input
+ * @returns {string}
+ */
+export function format(input) {
+    let deferred2_0;
+    let deferred2_1;
+    try {
+        const ptr0 = passStringToWasm0(input, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ret = wasm.format(ptr0, len0);
+        deferred2_0 = ret[0];
+        deferred2_1 = ret[1];
+        return getStringFromWasm0(ret[0], ret[1]);
+    } finally {
+        wasm.__wbindgen_free(deferred2_0, deferred2_1, 1);
     }
 }
 
-async function __wbg_load(module, imports) {
-    if (typeof Response === 'function' && module instanceof Response) {
-        if (typeof WebAssembly.instantiateStreaming === 'function') {
-            try {
-                return await WebAssembly.instantiateStreaming(module, imports);
+/**
+ * @param {string} input
+ * @param {FormatOptions} options
+ * @returns {string}
+ */
+export function format_with(input, options) {
+    let deferred2_0;
+    let deferred2_1;
+    try {
+        const ptr0 = passStringToWasm0(input, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        _assertClass(options, FormatOptions);
+        const ret = wasm.format_with(ptr0, len0, options.__wbg_ptr);
+        deferred2_0 = ret[0];
+        deferred2_1 = ret[1];
+        return getStringFromWasm0(ret[0], ret[1]);
+    } finally {
+        wasm.__wbindgen_free(deferred2_0, deferred2_1, 1);
+    }
+}
 
-            } catch (e) {
-                if (module.headers.get('Content-Type') != 'application/wasm') {
-                    console.warn("`WebAssembly.instantiateStreaming` failed because your server does not serve wasm with `application/wasm` MIME type. Falling back to `WebAssembly.instantiate` which is slower. Original error:\n", e);
+/**
+ * `[start, end)` and kind of every token but whitespaces, in characters of the input
+ * @param {string} input
+ * @returns {Highlight[]}
+ */
+export function highlight(input) {
+    const ptr0 = passStringToWasm0(input, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+    const len0 = WASM_VECTOR_LEN;
+    const ret = wasm.highlight(ptr0, len0);
+    var v2 = getArrayJsValueFromWasm0(ret[0], ret[1]);
+    wasm.__wbindgen_free(ret[0], ret[1] * 4, 4);
+    return v2;
+}
+
+/**
+ * the input as html, each token in a `<span>` with a class of `highlight.css`
+ * @param {string} input
+ * @returns {string}
+ */
+export function highlight_html(input) {
+    let deferred2_0;
+    let deferred2_1;
+    try {
+        const ptr0 = passStringToWasm0(input, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ret = wasm.highlight_html(ptr0, len0);
+        deferred2_0 = ret[0];
+        deferred2_1 = ret[1];
+        return getStringFromWasm0(ret[0], ret[1]);
+    } finally {
+        wasm.__wbindgen_free(deferred2_0, deferred2_1, 1);
+    }
+}
+
+/**
+ * @param {string} input
+ * @returns {string}
+ */
+export function pares_and_print_html(input) {
+    let deferred2_0;
+    let deferred2_1;
+    try {
+        const ptr0 = passStringToWasm0(input, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ret = wasm.pares_and_print_html(ptr0, len0);
+        deferred2_0 = ret[0];
+        deferred2_1 = ret[1];
+        return getStringFromWasm0(ret[0], ret[1]);
+    } finally {
+        wasm.__wbindgen_free(deferred2_0, deferred2_1, 1);
+    }
+}
+
+/**
+ * @param {string} input
+ * @param {number} level
+ * @returns {string}
+ */
+export function parse(input, level) {
+    let deferred2_0;
+    let deferred2_1;
+    try {
+        const ptr0 = passStringToWasm0(input, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ret = wasm.parse(ptr0, len0, level);
+        deferred2_0 = ret[0];
+        deferred2_1 = ret[1];
+        return getStringFromWasm0(ret[0], ret[1]);
+    } finally {
+        wasm.__wbindgen_free(deferred2_0, deferred2_1, 1);
+    }
+}
+
+/**
+ * the syntax tree as plain text
+ * @param {string} input
+ * @returns {string}
+ */
+export function tree(input) {
+    let deferred2_0;
+    let deferred2_1;
+    try {
+        const ptr0 = passStringToWasm0(input, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ret = wasm.tree(ptr0, len0);
+        deferred2_0 = ret[0];
+        deferred2_1 = ret[1];
+        return getStringFromWasm0(ret[0], ret[1]);
+    } finally {
+        wasm.__wbindgen_free(deferred2_0, deferred2_1, 1);
+    }
+}
 
-                } else {
-                    throw e;
-                }
+/**
+ * the input needs more lines to close a `{ }` block or a multi-line comment
+ * @param {string} input
+ * @returns {boolean}
+ */
+export function unclosed(input) {
+    const ptr0 = passStringToWasm0(input, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+    const len0 = WASM_VECTOR_LEN;
+    const ret = wasm.unclosed(ptr0, len0);
+    return ret !== 0;
+}
+function __wbg_get_imports() {
+    const import0 = {
+        __proto__: null,
+        __wbg___wbindgen_debug_string_4687d8d8c2017d52: function(arg0, arg1) {
+            const ret = debugString(arg1);
+            const ptr1 = passStringToWasm0(ret, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+            const len1 = WASM_VECTOR_LEN;
+            getDataViewMemory0().setInt32(arg0 + 4 * 1, len1, true);
+            getDataViewMemory0().setInt32(arg0 + 4 * 0, ptr1, true);
+        },
+        __wbg___wbindgen_is_object_3c45d4f2dde4e749: function(arg0) {
+            const val = arg0;
+            const ret = typeof(val) === 'object' && val !== null;
+            return ret;
+        },
+        __wbg___wbindgen_number_get_2e0e7dee9f701a71: function(arg0, arg1) {
+            const obj = arg1;
+            const ret = typeof(obj) === 'number' ? obj : undefined;
+            getDataViewMemory0().setFloat64(arg0 + 8 * 1, isLikeNone(ret) ? 0 : ret, true);
+            getDataViewMemory0().setInt32(arg0 + 4 * 0, !isLikeNone(ret), true);
+        },
+        __wbg___wbindgen_string_get_0380ccaa2f57f0d9: function(arg0, arg1) {
+            const obj = arg1;
+            const ret = typeof(obj) === 'string' ? obj : undefined;
+            var ptr1 = isLikeNone(ret) ? 0 : passStringToWasm0(ret, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+            var len1 = WASM_VECTOR_LEN;
+            getDataViewMemory0().setInt32(arg0 + 4 * 1, len1, true);
+            getDataViewMemory0().setInt32(arg0 + 4 * 0, ptr1, true);
+        },
+        __wbg___wbindgen_throw_41e9ee4f547fc59a: function(arg0, arg1) {
+            throw new Error(getStringFromWasm0(arg0, arg1));
+        },
+        __wbg___wbindgen_typeof_e777a26e115d416b: function(arg0) {
+            const ret = typeof arg0;
+            return ret;
+        },
+        __wbg_addValue_96be240d8262599d: function(arg0, arg1) {
+            addValue(getStringFromWasm0(arg0, arg1));
+        },
+        __wbg_apply_a910804df6e1e433: function() { return handleError(function (arg0, arg1, arg2) {
+            const ret = arg0.apply(arg1, arg2);
+            return ret;
+        }, arguments); },
+        __wbg_call_187d372bd5fdd4aa: function() { return handleError(function (arg0, arg1, arg2) {
+            const ret = arg0.call(arg1, arg2);
+            return ret;
+        }, arguments); },
+        __wbg_get_31af05bd4842a84f: function() { return handleError(function (arg0, arg1) {
+            const ret = Reflect.get(arg0, arg1);
+            return ret;
+        }, arguments); },
+        __wbg_get_unchecked_288889d017702237: function(arg0, arg1) {
+            const ret = arg0[arg1 >>> 0];
+            return ret;
+        },
+        __wbg_highlight_new: function(arg0) {
+            const ret = Highlight.__wrap(arg0);
+            return ret;
+        },
+        __wbg_instanceof_Error_80a725f81f2e102d: function(arg0) {
+            let result;
+            try {
+                result = arg0 instanceof Error;
+            } catch (_) {
+                result = false;
             }
-        }
+            const ret = result;
+            return ret;
+        },
+        __wbg_instanceof_Float64Array_8a59377f9eec1b8b: function(arg0) {
+            let result;
+            try {
+                result = arg0 instanceof Float64Array;
+            } catch (_) {
+                result = false;
+            }
+            const ret = result;
+            return ret;
+        },
+        __wbg_isArray_e15a2ff68ffdbef2: function(arg0) {
+            const ret = Array.isArray(arg0);
+            return ret;
+        },
+        __wbg_length_b5f0008bbf60cf59: function(arg0) {
+            const ret = arg0.length;
+            return ret;
+        },
+        __wbg_length_d4bdea10311bd9cf: function(arg0) {
+            const ret = arg0.length;
+            return ret;
+        },
+        __wbg_message_5f8387f0c32b90a7: function(arg0) {
+            const ret = arg0.message;
+            return ret;
+        },
+        __wbg_new_617a8cdb8bb1130e: function() {
+            const ret = new Object();
+            return ret;
+        },
+        __wbg_new_ee2291f50781bf1d: function() {
+            const ret = new Array();
+            return ret;
+        },
+        __wbg_of_20798cb14708764f: function(arg0, arg1) {
+            const ret = Array.of(arg0, arg1);
+            return ret;
+        },
+        __wbg_prototypesetcall_d49a4fab5ca427bc: function(arg0, arg1, arg2) {
+            Float64Array.prototype.set.call(getArrayF64FromWasm0(arg0, arg1), arg2);
+        },
+        __wbg_push_2baf45db356cf468: function(arg0, arg1) {
+            const ret = arg0.push(arg1);
+            return ret;
+        },
+        __wbg_set_145a351398b48c65: function() { return handleError(function (arg0, arg1, arg2) {
+            const ret = Reflect.set(arg0, arg1, arg2);
+            return ret;
+        }, arguments); },
+        __wbindgen_generic_0000000000000001: function(arg0) {
+            // Cast intrinsic for `F64 -> Externref`.
+            const ret = arg0;
+            return ret;
+        },
+        __wbindgen_generic_0000000000000002: function(arg0, arg1) {
+            // Cast intrinsic for `Ref(String) -> Externref`.
+            const ret = getStringFromWasm0(arg0, arg1);
+            return ret;
+        },
+        __wbindgen_init_externref_table: function() {
+            const table = wasm.__wbindgen_externrefs;
+            const offset = table.grow(4);
+            table.set(0, undefined);
+            table.set(offset + 0, undefined);
+            table.set(offset + 1, null);
+            table.set(offset + 2, true);
+            table.set(offset + 3, false);
+        },
+    };
+    return {
+        __proto__: null,
+        "./web_caculator_rust_wasm_bg.js": import0,
+    };
+}
 
-        const bytes = await module.arrayBuffer();
-        return await WebAssembly.instantiate(bytes, imports);
+const CaculatorFinalization = (typeof FinalizationRegistry === 'undefined')
+    ? { register: () => {}, unregister: () => {} }
+    : new FinalizationRegistry(ptr => wasm.__wbg_caculator_free(ptr, 1));
+const FormatOptionsFinalization = (typeof FinalizationRegistry === 'undefined')
+    ? { register: () => {}, unregister: () => {} }
+    : new FinalizationRegistry(ptr => wasm.__wbg_formatoptions_free(ptr, 1));
+const HighlightFinalization = (typeof FinalizationRegistry === 'undefined')
+    ? { register: () => {}, unregister: () => {} }
+    : new FinalizationRegistry(ptr => wasm.__wbg_highlight_free(ptr, 1));
+const MyStructFinalization = (typeof FinalizationRegistry === 'undefined')
+    ? { register: () => {}, unregister: () => {} }
+    : new FinalizationRegistry(ptr => wasm.__wbg_mystruct_free(ptr, 1));
 
-    } else {
-        const instance = await WebAssembly.instantiate(module, imports);
+function addToExternrefTable0(obj) {
+    const idx = wasm.__externref_table_alloc();
+    wasm.__wbindgen_externrefs.set(idx, obj);
+    return idx;
+}
 
-        if (instance instanceof WebAssembly.Instance) {
-            return { instance, module };
+function _assertClass(instance, klass) {
+    if (!(instance instanceof klass)) {
+        throw new Error(`expected instance of ${klass.name}`);
+    }
+}
 
+function debugString(val) {
+    // primitive types
+    const type = typeof val;
+    if (type == 'number' || type == 'boolean' || val == null) {
+        return  `${val}`;
+    }
+    if (type == 'string') {
+        return `"${val}"`;
+    }
+    if (type == 'symbol') {
+        const description = val.description;
+        if (description == null) {
+            return 'Symbol';
         } else {
-            return instance;
+            return `Symbol(${description})`;
         }
     }
+    if (type == 'function') {
+        const name = val.name;
+        if (typeof name == 'string' && name.length > 0) {
+            return `Function(${name})`;
+        } else {
+            return 'Function';
+        }
+    }
+    // objects
+    if (Array.isArray(val)) {
+        const length = val.length;
+        let debug = '[';
+        if (length > 0) {
+            debug += debugString(val[0]);
+        }
+        for(let i = 1; i < length; i++) {
+            debug += ', ' + debugString(val[i]);
+        }
+        debug += ']';
+        return debug;
+    }
+    // Test for built-in
+    const builtInMatches = /\[object ([^\]]+)\]/.exec(toString.call(val));
+    let className;
+    if (builtInMatches && builtInMatches.length > 1) {
+        className = builtInMatches[1];
+    } else {
+        // Failed to match the standard '[object ClassName]'
+        return toString.call(val);
+    }
+    if (className == 'Object') {
+        // we're a user defined class or Object
+        // JSON.stringify avoids problems with cycles, and is generally much
+        // easier than looping through ownProperties of `val`.
+        try {
+            return 'Object(' + JSON.stringify(val) + ')';
+        } catch (_) {
+            return 'Object';
+        }
+    }
+    // errors
+    if (val instanceof Error) {
+        return `${val.name}: ${val.message}\n${val.stack}`;
+    }
+    // TODO we could test for more things here, like `Set`s and `Map`s.
+    return className;
 }
 
-function __wbg_get_imports() {
-    const imports = {};
-    imports.wbg = {};
-    imports.wbg.__wbg_addValue_562403e2bfb750d0 = function(arg0, arg1) {
-        addValue(getStringFromWasm0(arg0, arg1));
-    };
-    imports.wbg.__wbindgen_throw = function(arg0, arg1) {
-        throw new Error(getStringFromWasm0(arg0, arg1));
-    };
+function getArrayF64FromWasm0(ptr, len) {
+    ptr = ptr >>> 0;
+    return getFloat64ArrayMemory0().subarray(ptr / 8, ptr / 8 + len);
+}
+
+function getArrayJsValueFromWasm0(ptr, len) {
+    ptr = ptr >>> 0;
+    const mem = getDataViewMemory0();
+    const result = [];
+    for (let i = ptr; i < ptr + 4 * len; i += 4) {
+        result.push(wasm.__wbindgen_externrefs.get(mem.getUint32(i, true)));
+    }
+    wasm.__externref_drop_slice(ptr, len);
+    return result;
+}
+
+function getArrayU32FromWasm0(ptr, len) {
+    ptr = ptr >>> 0;
+    return getUint32ArrayMemory0().subarray(ptr / 4, ptr / 4 + len);
+}
+
+let cachedDataViewMemory0 = null;
+function getDataViewMemory0() {
+    if (cachedDataViewMemory0 === null || cachedDataViewMemory0.buffer.detached === true || (cachedDataViewMemory0.buffer.detached === undefined && cachedDataViewMemory0.buffer !== wasm.memory.buffer)) {
+        cachedDataViewMemory0 = new DataView(wasm.memory.buffer);
+    }
+    return cachedDataViewMemory0;
+}
+
+let cachedFloat64ArrayMemory0 = null;
+function getFloat64ArrayMemory0() {
+    if (cachedFloat64ArrayMemory0 === null || cachedFloat64ArrayMemory0.byteLength === 0) {
+        cachedFloat64ArrayMemory0 = new Float64Array(wasm.memory.buffer);
+    }
+    return cachedFloat64ArrayMemory0;
+}
+
+function getStringFromWasm0(ptr, len) {
+    return decodeText(ptr >>> 0, len);
+}
+
+let cachedUint32ArrayMemory0 = null;
+function getUint32ArrayMemory0() {
+    if (cachedUint32ArrayMemory0 === null || cachedUint32ArrayMemory0.byteLength === 0) {
+        cachedUint32ArrayMemory0 = new Uint32Array(wasm.memory.buffer);
+    }
+    return cachedUint32ArrayMemory0;
+}
 
-    return imports;
+let cachedUint8ArrayMemory0 = null;
+function getUint8ArrayMemory0() {
+    if (cachedUint8ArrayMemory0 === null || cachedUint8ArrayMemory0.byteLength === 0) {
+        cachedUint8ArrayMemory0 = new Uint8Array(wasm.memory.buffer);
+    }
+    return cachedUint8ArrayMemory0;
+}
+
+function handleError(f, args) {
+    try {
+        return f.apply(this, args);
+    } catch (e) {
+        const idx = addToExternrefTable0(e);
+        wasm.__wbindgen_exn_store(idx);
+    }
 }
 
-function __wbg_init_memory(imports, maybe_memory) {
+function isLikeNone(x) {
+    return x === undefined || x === null;
+}
 
+function passArrayJsValueToWasm0(array, malloc) {
+    const ptr = malloc(array.length * 4, 4) >>> 0;
+    for (let i = 0; i < array.length; i++) {
+        const add = addToExternrefTable0(array[i]);
+        getDataViewMemory0().setUint32(ptr + 4 * i, add, true);
+    }
+    WASM_VECTOR_LEN = array.length;
+    return ptr;
 }
 
+function passStringToWasm0(arg, malloc, realloc) {
+    if (realloc === undefined) {
+        const buf = cachedTextEncoder.encode(arg);
+        const ptr = malloc(buf.length, 1) >>> 0;
+        getUint8ArrayMemory0().subarray(ptr, ptr + buf.length).set(buf);
+        WASM_VECTOR_LEN = buf.length;
+        return ptr;
+    }
+
+    let len = arg.length;
+    let ptr = malloc(len, 1) >>> 0;
+
+    const mem = getUint8ArrayMemory0();
+
+    let offset = 0;
+
+    for (; offset < len; offset++) {
+        const code = arg.charCodeAt(offset);
+        if (code > 0x7F) break;
+        mem[ptr + offset] = code;
+    }
+    if (offset !== len) {
+        if (offset !== 0) {
+            arg = arg.slice(offset);
+        }
+        ptr = realloc(ptr, len, len = offset + arg.length * 3, 1) >>> 0;
+        const view = getUint8ArrayMemory0().subarray(ptr + offset, ptr + len);
+        const ret = cachedTextEncoder.encodeInto(arg, view);
+
+        offset += ret.written;
+        ptr = realloc(ptr, len, offset, 1) >>> 0;
+    }
+
+    WASM_VECTOR_LEN = offset;
+    return ptr;
+}
+
+function takeFromExternrefTable0(idx) {
+    const value = wasm.__wbindgen_externrefs.get(idx);
+    wasm.__externref_table_dealloc(idx);
+    return value;
+}
+
+let cachedTextDecoder = new TextDecoder('utf-8', { ignoreBOM: true, fatal: true });
+cachedTextDecoder.decode();
+const MAX_SAFARI_DECODE_BYTES = 2146435072;
+let numBytesDecoded = 0;
+function decodeText(ptr, len) {
+    numBytesDecoded += len;
+    if (numBytesDecoded >= MAX_SAFARI_DECODE_BYTES) {
+        cachedTextDecoder = new TextDecoder('utf-8', { ignoreBOM: true, fatal: true });
+        cachedTextDecoder.decode();
+        numBytesDecoded = len;
+    }
+    return cachedTextDecoder.decode(getUint8ArrayMemory0().subarray(ptr, ptr + len));
+}
+
+const cachedTextEncoder = new TextEncoder();
+
+if (!('encodeInto' in cachedTextEncoder)) {
+    cachedTextEncoder.encodeInto = function (arg, view) {
+        const buf = cachedTextEncoder.encode(arg);
+        view.set(buf);
+        return {
+            read: arg.length,
+            written: buf.length
+        };
+    };
+}
+
+let WASM_VECTOR_LEN = 0;
+
+let wasmModule, wasmInstance, wasm;
 function __wbg_finalize_init(instance, module) {
+    wasmInstance = instance;
     wasm = instance.exports;
-    __wbg_init.__wbindgen_wasm_module = module;
-    cachedInt32Memory0 = null;
-    cachedUint8Memory0 = null;
+    wasmModule = module;
+    cachedDataViewMemory0 = null;
+    cachedFloat64ArrayMemory0 = null;
+    cachedUint32ArrayMemory0 = null;
+    cachedUint8ArrayMemory0 = null;
+    wasm.__wbindgen_start();
+    return wasm;
+}
+
+async function __wbg_load(module, imports) {
+    if (typeof Response === 'function' && module instanceof Response) {
+        if (!module.ok) {
+            throw new Error(`failed to fetch Wasm: ${module.status} ${module.statusText} fetching '${module.url}'`);
+        }
 
+        if (typeof WebAssembly.instantiateStreaming === 'function') {
+            try {
+                return await WebAssembly.instantiateStreaming(module, imports);
+            } catch (e) {
+                const validResponse = expectedResponseType(module.type);
 
-    return wasm;
+                if (validResponse && module.headers.get('Content-Type') !== 'application/wasm') {
+                    console.warn("`WebAssembly.instantiateStreaming` failed because your server does not serve Wasm with `application/wasm` MIME type. Falling back to `WebAssembly.instantiate` which is slower. Original error:\n", e);
+
+                } else { throw e; }
+            }
+        }
+
+        const bytes = await module.arrayBuffer();
+        return await WebAssembly.instantiate(bytes, imports);
+    } else {
+        const instance = await WebAssembly.instantiate(module, imports);
+
+        if (instance instanceof WebAssembly.Instance) {
+            return { instance, module };
+        } else {
+            return instance;
+        }
+    }
+
+    function expectedResponseType(type) {
+        switch (type) {
+            case 'basic': case 'cors': case 'default': return true;
+        }
+        return false;
+    }
 }
 
 function initSync(module) {
     if (wasm !== undefined) return wasm;
 
-    const imports = __wbg_get_imports();
 
-    __wbg_init_memory(imports);
+    if (module !== undefined) {
+        if (Object.getPrototypeOf(module) === Object.prototype) {
+            ({module} = module)
+        } else {
+            console.warn('using deprecated parameters for `initSync()`; pass a single object instead')
+        }
+    }
 
+    const imports = __wbg_get_imports();
     if (!(module instanceof WebAssembly.Module)) {
         module = new WebAssembly.Module(module);
     }
-
     const instance = new WebAssembly.Instance(module, imports);
-
     return __wbg_finalize_init(instance, module);
 }
 
-async function __wbg_init(input) {
+async function __wbg_init(module_or_path) {
     if (wasm !== undefined) return wasm;
 
-    if (typeof input === 'undefined') {
-        input = new URL('web_caculator_rust_wasm_bg.wasm', import.meta.url);
+
+    if (module_or_path !== undefined) {
+        if (Object.getPrototypeOf(module_or_path) === Object.prototype) {
+            ({module_or_path} = module_or_path)
+        } else {
+            console.warn('using deprecated parameters for the initialization function; pass a single object instead')
+        }
     }
-    const imports = __wbg_get_imports();
 
-    if (typeof input === 'string' || (typeof Request === 'function' && input instanceof Request) || (typeof URL === 'function' && input instanceof URL)) {
-        input = fetch(input);
+    if (module_or_path === undefined) {
+        module_or_path = new URL('web_caculator_rust_wasm_bg.wasm', import.meta.url);
     }
+    const imports = __wbg_get_imports();
 
-    __wbg_init_memory(imports);
+    if (typeof module_or_path === 'string' || (typeof Request === 'function' && module_or_path instanceof Request) || (typeof URL === 'function' && module_or_path instanceof URL)) {
+        module_or_path = fetch(module_or_path);
+    }
 
-    const { instance, module } = await __wbg_load(await input, imports);
+    const { instance, module } = await __wbg_load(await module_or_path, imports);
 
     return __wbg_finalize_init(instance, module);
 }
 
-export { initSync }
-export default __wbg_init;
+export { initSync, __wbg_init as default };
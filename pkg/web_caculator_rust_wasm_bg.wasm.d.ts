@@ -1,22 +1,74 @@
 /* tslint:disable */
 /* eslint-disable */
 export const memory: WebAssembly.Memory;
-export function __wbg_mystruct_free(a: number): void;
-export function __wbg_get_mystruct_flag(a: number): number;
-export function __wbg_set_mystruct_flag(a: number, b: number): void;
-export function mystruct_new(a: number): number;
-export function mystruct_get_value(a: number): number;
-export function mystruct_add_value(a: number, b: number): void;
-export function create_struct(): number;
-export function parse(a: number, b: number, c: number, d: number): void;
-export function pares_and_print_html(a: number, b: number, c: number): void;
-export function __wbg_caculator_free(a: number): void;
-export function caculator_new(a: number, b: number): number;
-export function caculator_new_parser(a: number, b: number, c: number): void;
-export function caculator_parse(a: number): void;
-export function caculator_calc(a: number): void;
-export function caculator_get_html(a: number, b: number): void;
-export function __wbindgen_add_to_stack_pointer(a: number): number;
-export function __wbindgen_malloc(a: number, b: number): number;
-export function __wbindgen_realloc(a: number, b: number, c: number, d: number): number;
-export function __wbindgen_free(a: number, b: number, c: number): void;
+export const __wbg_caculator_free: (a: number, b: number) => void;
+export const __wbg_formatoptions_free: (a: number, b: number) => void;
+export const __wbg_get_formatoptions_indent: (a: number) => number;
+export const __wbg_get_formatoptions_spaced: (a: number) => number;
+export const __wbg_get_formatoptions_width: (a: number) => number;
+export const __wbg_get_highlight_end: (a: number) => number;
+export const __wbg_get_highlight_kind: (a: number) => number;
+export const __wbg_get_highlight_start: (a: number) => number;
+export const __wbg_get_mystruct_flag: (a: number) => number;
+export const __wbg_highlight_free: (a: number, b: number) => void;
+export const __wbg_mystruct_free: (a: number, b: number) => void;
+export const __wbg_set_formatoptions_indent: (a: number, b: number) => void;
+export const __wbg_set_formatoptions_spaced: (a: number, b: number) => void;
+export const __wbg_set_formatoptions_width: (a: number, b: number) => void;
+export const __wbg_set_highlight_end: (a: number, b: number) => void;
+export const __wbg_set_highlight_kind: (a: number, b: number) => void;
+export const __wbg_set_highlight_start: (a: number, b: number) => void;
+export const __wbg_set_mystruct_flag: (a: number, b: number) => void;
+export const caculator_calc: (a: number) => void;
+export const caculator_call_errors: (a: number) => [number, number];
+export const caculator_clear_history: (a: number) => void;
+export const caculator_complete: (a: number, b: number, c: number, d: number) => any;
+export const caculator_dependents_of: (a: number, b: number, c: number) => [number, number];
+export const caculator_diagnostic_spans: (a: number) => [number, number];
+export const caculator_diagnostics: (a: number) => [number, number];
+export const caculator_export_state: (a: number) => [number, number];
+export const caculator_get_html: (a: number) => [number, number];
+export const caculator_get_variable: (a: number, b: number, c: number) => any;
+export const caculator_history: (a: number) => [number, number];
+export const caculator_import_state: (a: number, b: number, c: number) => [number, number];
+export const caculator_include_errors: (a: number) => [number, number];
+export const caculator_new: (a: number, b: number) => number;
+export const caculator_new_parser: (a: number, b: number, c: number) => void;
+export const caculator_parse: (a: number) => void;
+export const caculator_plot: (a: number, b: number, c: number, d: number, e: number) => [number, number, number, number];
+export const caculator_recomputed: (a: number) => number;
+export const caculator_reference: (a: number) => any;
+export const caculator_register_function: (a: number, b: number, c: number, d: number, e: any) => [number, number];
+export const caculator_results: (a: number) => any;
+export const caculator_set_epsilon: (a: number, b: number) => void;
+export const caculator_set_file: (a: number, b: number, c: number) => void;
+export const caculator_set_include_resolver: (a: number, b: any) => void;
+export const caculator_set_notation: (a: number, b: number, c: number, d: number) => number;
+export const caculator_set_pi: (a: number, b: number) => void;
+export const caculator_set_rounding: (a: number, b: number) => void;
+export const caculator_set_separator: (a: number, b: number, c: number) => void;
+export const caculator_set_variable: (a: number, b: number, c: number, d: any) => [number, number];
+export const caculator_update: (a: number, b: number, c: number) => void;
+export const caculator_update_reactive: (a: number, b: number, c: number) => [number, number];
+export const create_struct: () => number;
+export const format: (a: number, b: number) => [number, number];
+export const format_with: (a: number, b: number, c: number) => [number, number];
+export const formatoptions_new: () => number;
+export const highlight: (a: number, b: number) => [number, number];
+export const highlight_html: (a: number, b: number) => [number, number];
+export const mystruct_add_value: (a: number, b: number) => void;
+export const mystruct_get_value: (a: number) => number;
+export const mystruct_new: (a: number) => number;
+export const pares_and_print_html: (a: number, b: number) => [number, number];
+export const parse: (a: number, b: number, c: number) => [number, number];
+export const tree: (a: number, b: number) => [number, number];
+export const unclosed: (a: number, b: number) => number;
+export const __wbindgen_malloc: (a: number, b: number) => number;
+export const __wbindgen_realloc: (a: number, b: number, c: number, d: number) => number;
+export const __wbindgen_exn_store: (a: number) => void;
+export const __externref_table_alloc: () => number;
+export const __wbindgen_externrefs: WebAssembly.Table;
+export const __externref_drop_slice: (a: number, b: number) => void;
+export const __wbindgen_free: (a: number, b: number, c: number) => void;
+export const __externref_table_dealloc: (a: number) => void;
+export const __wbindgen_start: () => void;
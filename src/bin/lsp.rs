@@ -0,0 +1,101 @@
+//! Language server of calculator scripts, talking LSP over stdin and stdout.
+//!
+//! * `caculator-lsp` serves until the client sends `exit`
+//! * messages are framed by `Content-Length` headers, as in the protocol
+//! * a body over 64 MiB ends the server instead of being read
+
+use std::io::{self, BufRead, Read, Write};
+
+use web_caculator_rust_wasm::LanguageServer;
+
+fn main() {
+    let mut server = LanguageServer::new();
+    let mut input = io::stdin().lock();
+    let mut output = io::stdout().lock();
+    loop {
+        let message = match read_message(&mut input) {
+            Ok(Some(message)) => message,
+            // the client is gone
+            Ok(None) => std::process::exit(1),
+            Err(e) => {
+                eprintln!("caculator-lsp: {e}");
+                std::process::exit(1);
+            }
+        };
+        for reply in server.handle(&message) {
+            let sent = write!(output, "Content-Length: {}\r\n\r\n{reply}", reply.len())
+                .and_then(|_| output.flush());
+            if let Err(e) = sent {
+                eprintln!("caculator-lsp: {e}");
+                std::process::exit(1);
+            }
+        }
+        if let Some(code) = server.exit_code() {
+            std::process::exit(code);
+        }
+    }
+}
+
+/// longest body taken from the client, longer ones are refused before reading
+const MAX_MESSAGE: usize = 64 << 20;
+/// longest header line
+const MAX_HEADER: u64 = 1 << 10;
+
+/// the body of the next message, `None` at the end of input
+fn read_message(input: &mut impl BufRead) -> io::Result<Option<String>> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if input.by_ref().take(MAX_HEADER).read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        if !line.ends_with('\n') && line.len() as u64 == MAX_HEADER {
+            return Err(invalid(format!("header longer than {MAX_HEADER} bytes")));
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+    let Some(length) = length else {
+        return Err(invalid("message without Content-Length".to_string()));
+    };
+    if length > MAX_MESSAGE {
+        return Err(invalid(format!(
+            "message of {length} bytes, more than {MAX_MESSAGE}"
+        )));
+    }
+    // read as it comes, not allocated up front
+    let mut body = vec![];
+    input.take(length as u64).read_to_end(&mut body)?;
+    if body.len() < length {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    String::from_utf8(body)
+        .map(Some)
+        .map_err(|e| invalid(e.to_string()))
+}
+
+#[test]
+fn test_read_message() {
+    let read = |text: &str| read_message(&mut io::Cursor::new(text.as_bytes()));
+    let message = read("Content-Length: 2\r\nContent-Type: x\r\n\r\n{}{}").unwrap();
+    assert_eq!(message.as_deref(), Some("{}"));
+    assert!(read("").unwrap().is_none());
+    // a length the body does not have is refused, not allocated
+    let large = format!("Content-Length: {}\r\n\r\n{{}}", usize::MAX);
+    assert_eq!(read(&large).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    let short = read("Content-Length: 9\r\n\r\n{}").unwrap_err();
+    assert_eq!(short.kind(), io::ErrorKind::UnexpectedEof);
+    let header = format!("X: {}\r\n", "a".repeat(2000));
+    assert_eq!(
+        read(&header).unwrap_err().kind(),
+        io::ErrorKind::InvalidData
+    );
+}
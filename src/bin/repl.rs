@@ -0,0 +1,181 @@
+//! Command-line REPL of the calculator.
+//!
+//! * `caculator` starts an interactive session
+//! * `caculator FILE`, `caculator -` or `caculator < FILE` calculates
+//!   a whole file or stdin and prints the outputs
+//! * `caculator --format [--indent N] [--width N] [--compact] [FILE | -]`
+//!   prints the file or stdin formatted
+
+use std::io::{self, IsTerminal, Read};
+
+use rustyline::{error::ReadlineError, DefaultEditor};
+use web_caculator_rust_wasm::{
+    format, format_with, parse, tree, unclosed, Caculator, FormatOptions,
+};
+
+const HELP: &str = "\
+Type sentences to calculate them, `{` continues on the next lines until `}`.
+`functions()` and `constants()` list what is built in, `help(sin)` tells about one.
+  :tokens <input>   show the tokens of the input
+  :tree <input>     show the syntax tree of the input
+  :format <input>   show the input formatted
+  :history          list every output so far
+  :clear            forget the history
+  :reset            forget all variables and the history
+  :help             show this help
+  :quit             leave (or Ctrl-D)";
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut calc = Caculator::new("");
+    match args.first().map(|a| a.as_str()) {
+        Some("-h" | "--help") => {
+            println!("usage: caculator [FILE | -]\n       caculator --format [--indent N] [--width N] [--compact] [FILE | -]\n\n{HELP}");
+        }
+        Some("--format") => format_file(&args[1..]),
+        Some(path) if path != "-" => match std::fs::read_to_string(path) {
+            Ok(text) => {
+                // includes are relative to the file
+                calc.set_file(path);
+                run(&mut calc, &text)
+            }
+            Err(e) => {
+                eprintln!("{path}: {e}");
+                std::process::exit(1);
+            }
+        },
+        Some(_) => run_stdin(&mut calc),
+        None if !io::stdin().is_terminal() => run_stdin(&mut calc),
+        None => repl(&mut calc),
+    }
+}
+
+/// calculate `text` in the session and print the outputs
+fn run(calc: &mut Caculator, text: &str) {
+    calc.new_parser(text);
+    calc.parse();
+    calc.calc();
+    for d in calc.diagnostics() {
+        eprintln!("{d}");
+    }
+    // `get_html` escapes the texts for a page
+    for o in calc.outputs() {
+        println!("[out {}] {}", o.index, o.text);
+    }
+    for e in calc.include_errors() {
+        eprintln!("{e}");
+    }
+    for e in calc.call_errors() {
+        eprintln!("{e}");
+    }
+}
+
+fn run_stdin(calc: &mut Caculator) {
+    run(calc, &read_stdin());
+}
+
+fn read_stdin() -> String {
+    let mut text = String::new();
+    if let Err(e) = io::stdin().read_to_string(&mut text) {
+        eprintln!("stdin: {e}");
+        std::process::exit(1);
+    }
+    text
+}
+
+/// `--format [--indent N] [--width N] [--compact] [FILE | -]`
+fn format_file(args: &[String]) {
+    let mut options = FormatOptions::default();
+    let mut path = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut number = |name: &str| match args.next().and_then(|n| n.parse().ok()) {
+            Some(n) => n,
+            None => {
+                eprintln!("{name} needs a number");
+                std::process::exit(2);
+            }
+        };
+        match arg.as_str() {
+            "--indent" => options.indent = number("--indent"),
+            "--width" => options.width = number("--width"),
+            "--compact" => options.spaced = false,
+            _ => path = Some(arg.as_str()),
+        }
+    }
+    let text = match path {
+        Some(path) if path != "-" => match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) => {
+                eprintln!("{path}: {e}");
+                std::process::exit(1);
+            }
+        },
+        _ => read_stdin(),
+    };
+    print!("{}", format_with(&text, &options));
+}
+
+fn repl(calc: &mut Caculator) {
+    let mut rl = match DefaultEditor::new() {
+        Ok(rl) => rl,
+        Err(e) => {
+            eprintln!("cannot start line editing: {e}");
+            return run_stdin(calc);
+        }
+    };
+    println!("Web-Caculator REPL, :help for commands");
+    let mut input = String::new();
+    loop {
+        let prompt = if input.is_empty() { ">>> " } else { "... " };
+        match rl.readline(prompt) {
+            Ok(line) => {
+                if input.is_empty() {
+                    if let Some(cmd) = line.trim().strip_prefix(':') {
+                        let _ = rl.add_history_entry(line.as_str());
+                        if !command(calc, cmd) {
+                            break;
+                        }
+                        continue;
+                    }
+                }
+                input += &line;
+                input.push('\n');
+                if unclosed(&input) {
+                    continue;
+                }
+                let _ = rl.add_history_entry(input.trim_end());
+                run(calc, &input);
+                input.clear();
+            }
+            // Ctrl-C drops the unfinished input
+            Err(ReadlineError::Interrupted) => input.clear(),
+            Err(ReadlineError::Eof) => break,
+            Err(e) => {
+                eprintln!("{e}");
+                break;
+            }
+        }
+    }
+}
+
+/// run a `:command`, returns false to leave
+fn command(calc: &mut Caculator, cmd: &str) -> bool {
+    let (name, arg) = cmd.split_once(char::is_whitespace).unwrap_or((cmd, ""));
+    match name {
+        "tokens" | "t" => print!("{}", parse(arg, 2)),
+        "tree" => println!("{}", tree(arg)),
+        "format" | "f" => print!("{}", format(arg)),
+        "history" | "h" => {
+            for (i, v) in calc.history().iter().enumerate() {
+                println!("[out {}] {v}", i + 1);
+            }
+        }
+        "clear" => calc.clear_history(),
+        "reset" => *calc = Caculator::new(""),
+        "help" | "?" => println!("{HELP}"),
+        "quit" | "q" | "exit" => return false,
+        _ => println!("unknown command :{name}, :help for commands"),
+    }
+    true
+}
@@ -1,12 +1,45 @@
-use std::fmt::format;
-
-use my_parser::{Article, LexicalParser};
+use my_graph::DepGraph;
+use my_math::my_format::Notation;
+use my_notebook::Notebook;
+use my_parser::{Article, Diagnostic, LexicalParser};
 use my_runtime::Runtime;
 use wasm_bindgen::prelude::*;
 
+pub use my_complete::{Completion, CompletionKind, Completions, JsCompletions, SignatureHelp};
+pub use my_cst::{Child, Cst, Node, SyntaxKind};
+pub use my_engine::{Engine, EngineBuilder, Evaluation};
+pub use my_formatter::FormatOptions;
+pub use my_help::Reference;
+pub use my_highlight::{Highlight, HighlightKind};
+#[cfg(not(target_arch = "wasm32"))]
+pub use my_include::FsResolver;
+pub use my_include::{IncludeError, IncludeResolver, MemoryResolver};
+#[cfg(not(target_arch = "wasm32"))]
+pub use my_lsp::LanguageServer;
+pub use my_math::{my_builtins::NativeFunction, Fun, Im, Re, Tuple, Val};
+pub use my_output::{ErrorKind, Output, OutputError, OutputKind, Results};
+pub use my_parser::{Token, TokenType};
+pub use my_runtime::{CallError, System};
+
+mod my_complete;
+mod my_cst;
+mod my_engine;
+mod my_formatter;
+mod my_graph;
+mod my_help;
+mod my_highlight;
+mod my_include;
+mod my_js;
+mod my_json;
+#[cfg(not(target_arch = "wasm32"))]
+mod my_lsp;
 mod my_math;
+mod my_notebook;
+mod my_output;
 mod my_parser;
+mod my_plot;
 mod my_runtime;
+mod my_suggest;
 
 // 导入函数
 #[wasm_bindgen]
@@ -64,18 +97,66 @@ pub fn create_struct() -> MyStruct {
 
 #[wasm_bindgen]
 pub fn parse(input: &str, level: u32) -> String {
-    LexicalParser::new_inline(input.to_string()).print(level as usize)
+    let p = LexicalParser::new_inline(input.to_string());
+    let mut res = p.print(level as usize);
+    // malformed tokens after the list
+    for d in p.diagnostics() {
+        res += &format!("{d}\n");
+    }
+    res
 }
 
+/// the syntax tree as plain text
 #[wasm_bindgen]
-pub fn pares_and_print_html(input: &str) -> String {
-    let line1 = LexicalParser::new_inline(input.to_string())
-        .parse()
-        .print(11);
-    let line2 = LexicalParser::new_inline(input.to_string())
+pub fn tree(input: &str) -> String {
+    LexicalParser::new_inline(input.to_string())
         .parse()
-        .tree(0, true);
-    line1 + "\n<span class='tree_syntax'>" + &line2 + "</span>"
+        .tree(0, false)
+}
+
+/// the input needs more lines to close a `{ }` block or a multi-line comment
+#[wasm_bindgen]
+pub fn unclosed(input: &str) -> bool {
+    LexicalParser::new_inline(input.to_string()).unclosed()
+}
+
+/// the lossless syntax tree, for tools like formatters and editors
+pub fn syntax(input: &str) -> Cst {
+    LexicalParser::new_inline(input.to_string()).parse().cst
+}
+
+/// `[start, end)` and kind of every token but whitespaces, in characters of the input
+#[wasm_bindgen]
+pub fn highlight(input: &str) -> Vec<Highlight> {
+    my_highlight::highlight(&syntax(input))
+}
+
+/// the input as html, each token in a `<span>` with a class of `highlight.css`
+#[wasm_bindgen]
+pub fn highlight_html(input: &str) -> String {
+    my_highlight::to_html(input, &highlight(input))
+}
+
+/// the input pretty-printed: a sentence a line, blocks indented, comments kept
+#[wasm_bindgen]
+pub fn format(input: &str) -> String {
+    format_with(input, &FormatOptions::default())
+}
+
+#[wasm_bindgen]
+pub fn format_with(input: &str, options: &FormatOptions) -> String {
+    my_formatter::format(&syntax(input), options)
+}
+
+#[wasm_bindgen]
+pub fn pares_and_print_html(input: &str) -> String {
+    let (a, diagnostics) = LexicalParser::new_inline(input.to_string()).parse_with_diagnostics();
+    let mut res = a.print(11) + "\n<span class='tree_syntax'>" + &a.tree(0, true) + "</span>";
+    for d in diagnostics {
+        let d = my_highlight::escape_html(&d.to_string());
+        res += &format!("\n<span class='syntax_error'>{d}</span>");
+    }
+    res
 }
 
 #[wasm_bindgen]
@@ -84,34 +165,721 @@ pub struct Caculator {
     res: my_math::Val,
     a: Article,
     p: LexicalParser,
+    /// history length before the last calc, so outputs are numbered as `out[n]`
+    first: usize,
+    /// cached sentences for `update`
+    nb: Notebook,
+    /// malformed sentences of the last parsed input
+    diagnostics: Vec<Diagnostic>,
 }
 
 #[wasm_bindgen]
 impl Caculator {
     pub fn new(input: &str) -> Self {
         let p = LexicalParser::new_inline(input.to_string());
-        let a = p.parse();
+        let (a, diagnostics) = p.parse_with_diagnostics();
         let mut rt = Runtime::new();
         let res = a.calc(&mut rt);
-        Self { rt, res, a, p }
+        Self {
+            rt,
+            res,
+            a,
+            p,
+            first: 0,
+            nb: Notebook::new(),
+            diagnostics,
+        }
+    }
+    /// calculate `input` as a whole, as if in a new runtime,
+    /// but only the sentences affected by the edit are calculated again
+    pub fn update(&mut self, input: &str) {
+        self.p = LexicalParser::new_inline(input.to_string());
+        (self.res, self.a) = self.nb.update(self.p.tokens(), &mut self.rt);
+        self.diagnostics = self.nb.diagnostics().to_vec();
+        self.first = 0;
+    }
+    /// calculate `input` like a spreadsheet: sentences may use variables
+    /// assigned after them, and like `update` only the sentences affected
+    /// by the edit are calculated again.
+    /// returns the error for cycles
+    pub fn update_reactive(&mut self, input: &str) -> Result<(), String> {
+        self.p = LexicalParser::new_inline(input.to_string());
+        self.first = 0;
+        match self.nb.update_reactive(self.p.tokens(), &mut self.rt) {
+            Ok((res, a)) => {
+                self.res = res;
+                self.a = a;
+                self.diagnostics = self.nb.diagnostics().to_vec();
+                Ok(())
+            }
+            Err(e) => {
+                self.res = my_math::Val::NONE;
+                (self.a, self.diagnostics) = self.p.parse_with_diagnostics();
+                Err(e.to_string())
+            }
+        }
+    }
+    /// variables of the last parsed input that depend on `name`, in calculating order
+    pub fn dependents_of(&self, name: &str) -> Vec<String> {
+        DepGraph::from(&self.a.sentences).dependents_of(name)
+    }
+    /// vars, functions, settings and history as JSON, see `Runtime::export_state`
+    pub fn export_state(&self) -> String {
+        self.rt.export_state()
+    }
+    /// restore a state written by `export_state`
+    pub fn import_state(&mut self, state: &str) -> Result<(), String> {
+        self.rt.import_state(state)?;
+        self.nb.clear();
+        self.first = self.rt.history_len();
+        self.res = my_math::Val::NONE;
+        Ok(())
+    }
+    /// count of sentences calculated by the last `update`
+    pub fn recomputed(&self) -> usize {
+        self.nb.recomputed()
     }
     pub fn new_parser(&mut self, input: &str) {
         self.p = LexicalParser::new_inline(input.to_string());
     }
     pub fn parse(&mut self) {
-        self.a = self.p.parse();
+        (self.a, self.diagnostics) = self.p.parse_with_diagnostics();
+    }
+    /// why sentences of the last parsed input were skipped, as `line:column: message`
+    pub fn diagnostics(&self) -> Vec<String> {
+        self.diagnostics.iter().map(|d| d.to_string()).collect()
+    }
+    /// `[start, end]` of each diagnostic in characters of the input, flattened
+    pub fn diagnostic_spans(&self) -> Vec<u32> {
+        self.diagnostics
+            .iter()
+            .flat_map(|d| d.span.map(|i| i as u32))
+            .collect()
     }
     pub fn calc(&mut self) {
+        self.rt.include.clear_errors();
+        self.rt.clear_call_errors();
+        self.first = self.rt.history_len();
         self.res = self.a.calc(&mut self.rt);
     }
     pub fn get_html(&self) -> String {
         // println!("Hello!!!!!!!");
         // format!("{:#?}", self.res)
         // self.a.tree(0, true)
-        self.res.print(&mut 0)
+        self.res.print(&mut self.first.clone(), &self.rt.sys)
+    }
+    /// the outputs of the last calculation and the errors of the input,
+    /// as plain objects, see `Results` in the typescript definitions
+    pub fn results(&self) -> Results {
+        my_output::to_js(&self.outputs(), &self.errors())
+    }
+    /// a variable for the sentences to read, kept by `update` and `import_state`.
+    /// numbers are reals, `{ re, im }` complex numbers, strings texts,
+    /// arrays, `Float64Array`s and nested arrays tuples
+    pub fn set_variable(&mut self, name: &str, value: JsValue) -> Result<(), String> {
+        self.define(name, my_js::val_from_js(&value)?)
+    }
+    /// a variable or constant as `set_variable` takes it,
+    /// `undefined` for functions and unknown names
+    pub fn get_variable(&self, name: &str) -> JsValue {
+        match self.variable(name) {
+            Some(v) => my_js::val_to_js(&v),
+            None => JsValue::UNDEFINED,
+        }
+    }
+    /// every output so far, formatted, `out[1]` first
+    pub fn history(&self) -> Vec<String> {
+        self.rt
+            .history()
+            .iter()
+            .map(|v| v.text(&self.rt.sys))
+            .collect()
+    }
+    /// forget `ans` and `out[n]`, numbering starts over
+    pub fn clear_history(&mut self) {
+        self.rt.clear_history();
+        self.first = 0;
+    }
+
+    /// `notation`: `auto`, `fixed`, `sig`, `sci` or `eng`,
+    /// `digits`: decimals or significant figures.
+    /// returns false for unknown notations and more than 17 digits
+    pub fn set_notation(&mut self, notation: &str, digits: u32) -> bool {
+        match Notation::from_name(notation, digits as usize) {
+            Some(n) => {
+                self.rt.sys.format.notation = n;
+                true
+            }
+            None => false,
+        }
+    }
+    /// thousands separator, empty string for none
+    pub fn set_separator(&mut self, separator: &str) {
+        self.rt.sys.format.separator = separator.chars().next();
+    }
+    /// round away float noise like `0.30000000000000004`
+    pub fn set_rounding(&mut self, rounding: bool) {
+        self.rt.sys.format.rounding = rounding;
+    }
+    /// write multiples of `π` like `3π/4`
+    pub fn set_pi(&mut self, pi: bool) {
+        self.rt.sys.format.pi = pi;
+    }
+    /// tolerance used by rounding and `zero(x)`
+    pub fn set_epsilon(&mut self, epsilon: f64) {
+        self.rt.sys.set_epsilon(epsilon);
+        self.nb.clear();
+    }
+
+    /// name of the input as a file, `include` paths are relative to its folder.
+    /// empty string for none
+    pub fn set_file(&mut self, name: &str) {
+        self.rt.include.main = (!name.is_empty()).then(|| name.to_string());
+    }
+    /// `include("file")` reads files by `load(name)`, which returns
+    /// the text or `undefined`. names are `/` separated paths
+    pub fn set_include_resolver(&mut self, load: js_sys::Function) {
+        self.set_resolver(my_include::JsResolver(load));
+    }
+    /// `name(...)` calls `f` with `arity` arguments, numbers or arrays
+    /// as `set_variable` takes them, and what `f` throws is a call error
+    pub fn register_function(
+        &mut self,
+        name: &str,
+        arity: u32,
+        f: js_sys::Function,
+    ) -> Result<(), String> {
+        self.register(my_js::js_function(name, arity as usize, f))
+    }
+    /// names for the cursor at `cursor` characters into `input`, from the
+    /// variables so far and the assignments of `input`, and the function called
+    /// around the cursor. see `Completions` in the typescript definitions
+    pub fn complete(&self, input: &str, cursor: u32) -> JsCompletions {
+        my_complete::to_js(&self.completions(input, cursor as usize))
+    }
+    /// svg of `y = f(x)` for `a ≤ x ≤ b` of each function, given by its name
+    /// or as an expression of `x`, like `plot(["sin", "x^2 - 1"], -3, 3)`
+    pub fn plot(&mut self, functions: Vec<String>, a: f64, b: f64) -> Result<String, String> {
+        let fs = functions.into_iter().map(my_math::Val::Text).collect();
+        let args = [
+            my_math::Val::Vars(Tuple(fs)),
+            my_math::Val::Re(Re(a)),
+            my_math::Val::Re(Re(b)),
+        ];
+        match my_plot::plot(&mut self.rt, &args)? {
+            my_math::Val::Svg(svg) => Ok(svg),
+            _ => Err("no plot".to_string()),
+        }
+    }
+    /// every function and constant with what it is, for a reference panel,
+    /// see `Reference` in the typescript definitions
+    pub fn reference(&self) -> Reference {
+        my_help::reference(&self.rt.sys)
+    }
+    /// functions that failed in the last calculation, as `name: message`
+    pub fn call_errors(&self) -> Vec<String> {
+        self.rt
+            .call_errors()
+            .iter()
+            .map(|e| e.to_string())
+            .collect()
+    }
+    /// errors of `include` in the last calculation, as `file:line:column: message`
+    pub fn include_errors(&self) -> Vec<String> {
+        self.rt
+            .include
+            .errors()
+            .iter()
+            .map(|e| e.to_string())
+            .collect()
     }
 }
 
+impl Caculator {
+    /// how `include("file")` finds and reads files,
+    /// files on the disk by default, none in wasm
+    pub fn set_resolver(&mut self, resolver: impl IncludeResolver + 'static) {
+        self.rt.include.set_resolver(resolver);
+    }
+    fn define(&mut self, name: &str, val: my_math::Val) -> Result<(), String> {
+        check_name(name)?;
+        self.rt.define(name.to_string(), val);
+        // cached sentences may have read the old value
+        self.nb.clear();
+        Ok(())
+    }
+    fn register(&mut self, f: NativeFunction) -> Result<(), String> {
+        f.names().try_for_each(|name| check_name(name))?;
+        self.rt.sys.register(f);
+        self.nb.clear();
+        Ok(())
+    }
+    /// a variable of the root env or a constant
+    fn variable(&self, name: &str) -> Option<my_math::Val> {
+        let val = self.rt.lookup(name);
+        val.or_else(|| self.rt.sys.get_sys_val(name).cloned())
+    }
+    /// what `complete` returns to js
+    pub fn completions(&self, input: &str, cursor: usize) -> Completions {
+        my_complete::complete(&syntax(input), cursor, &self.rt)
+    }
+    /// the values `get_html` writes, each with the sentence it comes from
+    pub fn outputs(&self) -> Vec<Output> {
+        my_output::outputs(&self.res, &self.a.spans, self.first, &self.rt.sys)
+    }
+    /// malformed sentences, `include` errors and failed functions, in this order
+    pub fn errors(&self) -> Vec<OutputError> {
+        my_output::errors(&self.diagnostics, &self.rt)
+    }
+}
+
+/// a name a sentence can use, not a keyword
+pub(crate) fn check_name(name: &str) -> Result<(), String> {
+    let p = LexicalParser::new_inline(name.to_string());
+    let tks: Vec<_> = p.tokens().iter().filter(|t| !t.is_skipped()).collect();
+    let named = match tks[..] {
+        [t] => t.token_type() == TokenType::Identifier && t.lexeme() == name,
+        _ => false,
+    };
+    // `√` is a name for `sqrt`
+    if !named || name == "√" {
+        return Err(format!("not a name: {name}"));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_history() {
+    let mut c = Caculator::new("1 + 2; 10");
+    c.new_parser("ans * 2; out[1] + _2; -out[-1]");
+    c.parse();
+    c.calc();
+    assert_eq!(c.history(), ["3", "10", "20", "13", "-13"]);
+    assert_eq!(c.get_html(), "[out 3] 20\n[out 4] 13\n[out 5] -13\n");
+}
+
+#[test]
+fn test_update() {
+    let mut c = Caculator::new("");
+    c.update("x = 2\ny = 3\nx * 10\ny + 1\nans");
+    assert_eq!(c.recomputed(), 5);
+    c.update("x = 4\ny = 3\nx * 10\ny + 1\nans");
+    // `y + 1` is reused, `ans` follows a changed output
+    assert_eq!(c.recomputed(), 3);
+    assert_eq!(
+        c.get_html(),
+        Caculator::new("x = 4;y = 3;x * 10;y + 1;ans").get_html()
+    );
+    c.update("f(t) = x * t\nx = 1\nf(2)");
+    c.update("f(t) = x * t\nx = 5\nf(2)");
+    assert_eq!(c.get_html(), "[out 1] 10\n");
+    c.update("y = 3\nx * 10\ny + 1\nans");
+    assert_eq!(
+        c.get_html(),
+        Caculator::new("y = 3;x * 10;y + 1;ans").get_html()
+    );
+    // a line break may end a sentence
+    for (a, b) in [
+        ("{x = 2 (3)}", "{x = 2\n(3)}"),
+        ("{*3y = 1}", "{*3y =\n 1}"),
+    ] {
+        c.update(a);
+        c.update(b);
+        assert_eq!(c.get_html(), Caculator::new(b).get_html());
+    }
+    c.update("{x = 2 (3)}");
+    c.update("{x = 2\n(3)}");
+    assert_eq!(c.get_html(), "[out 1] 3\n");
+    // an edit of a long input calculates only what it touches
+    let input: String = (0..3000)
+        .map(|i| format!("x{i} = {i}\ny{i} = x{i} * 2\ny{i} + 1\n"))
+        .collect();
+    let edited = input.replacen("x1500 = 1500", "x1500 = 0", 1);
+    c.update(&input);
+    c.update(&edited);
+    assert_eq!(c.recomputed(), 3);
+    assert_eq!(c.get_html(), Caculator::new(&edited).get_html());
+}
+
+#[test]
+fn test_reactive() {
+    let mut c = Caculator::new("");
+    assert!(c
+        .update_reactive("y = x * 2\nx = 5\ny + z\nz = y - x")
+        .is_ok());
+    assert_eq!(c.get_html(), "[out 1] 15\n");
+    assert_eq!(c.dependents_of("x"), ["y", "z"]);
+    assert_eq!(
+        c.update_reactive("a = b + 1\nb = c\nc = a"),
+        Err("cyclic dependency: a -> b -> c -> a".to_string())
+    );
+
+    // a variable assigned again is read by the sentences after it
+    let input = "y = x * 2\nx = 5\ny\nx = x + 1\nz = x\nx";
+    assert!(c.update_reactive(input).is_ok());
+    assert_eq!(c.get_html(), "[out 1] 10\n[out 2] 6\n");
+    assert_eq!(c.dependents_of("x"), ["y", "z"]);
+    // an edit calculates only what reads it
+    assert!(c
+        .update_reactive("y = x * 2\nx = 7\ny\nx = x + 1\nz = x\nx")
+        .is_ok());
+    assert_eq!(c.get_html(), "[out 1] 14\n[out 2] 8\n");
+    assert_eq!(c.recomputed(), 6);
+    assert!(c
+        .update_reactive("y = x * 2\nx = 7\ny\nx = x + 1\nz = x\nx\n1")
+        .is_ok());
+    assert_eq!(c.recomputed(), 1);
+    assert!(c
+        .update_reactive("y = x * 2\nx = 7\ny\nx = x + 2\nz = x\nx\n1")
+        .is_ok());
+    assert_eq!(c.get_html(), "[out 1] 14\n[out 2] 9\n[out 3] 1\n");
+    assert_eq!(c.recomputed(), 3);
+    assert!(c.update_reactive("a = ans + 1\n2\n3").is_ok());
+    assert_eq!(c.get_html(), "[out 1] 2\n[out 2] 3\n");
+    assert!(c.update_reactive("2\na = ans + 1\n3\na").is_ok());
+    assert_eq!(c.get_html(), "[out 1] 2\n[out 2] 3\n[out 3] 3\n");
+    // outputs read by `_1` may be gone
+    assert!(c.update_reactive("x\n_1\nx = 1").is_ok());
+    assert!(c.update_reactive("x\n_1").is_ok());
+    assert_eq!(c.get_html(), "");
+    assert_eq!(
+        c.update_reactive("x\nx = ans"),
+        Err("cyclic dependency: [sentence 1] -> x -> [sentence 1]".to_string())
+    );
+    // an edit of a long input calculates only what it touches
+    let input: String = (0..3000)
+        .map(|i| format!("y{i} = x{i} * 2\nx{i} = {i}\ny{i} + 1\n"))
+        .collect();
+    let edited = input.replacen("x1500 = 1500", "x1500 = 0", 1);
+    assert!(c.update_reactive(&input).is_ok());
+    assert!(c.update_reactive(&edited).is_ok());
+    assert_eq!(c.recomputed(), 3);
+    let mut fresh = Caculator::new("");
+    assert!(fresh.update_reactive(&edited).is_ok());
+    assert_eq!(c.get_html(), fresh.get_html());
+    assert_eq!(c.history()[1500], "1");
+}
+
+#[test]
+fn test_state() {
+    let mut c = Caculator::new("k = 3; f(x) = k * x * x; h = 0; {g(y) = f(y) + k; h = g}; 7");
+    c.set_notation("fixed", 2);
+    let state = c.export_state();
+    let mut d = Caculator::new("");
+    assert!(d.import_state(&state).is_ok());
+    assert_eq!(d.export_state(), state);
+    d.new_parser("f(2) + 1; h(1); ans + _1");
+    d.parse();
+    d.calc();
+    assert_eq!(d.get_html(), "[out 2] 13.00\n[out 3] 6.00\n[out 4] 13.00\n");
+    assert!(d
+        .import_state(&state.replace("\"version\":2", "\"version\":3"))
+        .is_err());
+    // too many digits are refused, and the notation is kept
+    assert!(!d.set_notation("sci", 1_000_000));
+    assert!(d
+        .import_state(&state.replace("\"digits\":2", "\"digits\":1e300"))
+        .is_ok());
+    d.update("1/3");
+    assert_eq!(d.get_html(), "[out 1] 0.33\n");
+
+    // a function keeps the variables of its block
+    let mut c = Caculator::new("g = 0; {a = 2; f(x) = x * a; g = f}; g(3)");
+    assert_eq!(c.get_html(), "[out 1] 6\n");
+    let state = c.export_state();
+    assert!(state.contains("\"captured\":{\"a\":{\"re\":2}}") && !state.contains("\"env\""));
+    let mut d = Caculator::new("");
+    assert!(d.import_state(&state).is_ok());
+    d.update("a = 5; g(2)");
+    assert_eq!(d.get_html(), "[out 1] 4\n");
+    // as written by version 1
+    let old = r#"{"format":"web-caculator-state","version":1,"envs":[{"id":1,"parent":0,
+        "vals":{"sq":{"closure":{"params":["x"],"body":"x * x","env":1}}}}]}"#;
+    assert!(d.import_state(old).is_ok());
+    d.update("sq(3)");
+    assert_eq!(d.get_html(), "[out 1] 9\n");
+
+    // and calls itself there
+    c.update("{h(n) = h(n + 1); h(1)}");
+    assert_eq!(c.call_errors()[0].to_string(), "h: recursion too deep");
+}
+
+#[test]
+fn test_include() {
+    let mut files = MemoryResolver::new();
+    files.insert("lib/area.calc", "r = 2\narea(x) = 3 * x * x");
+    let mut c = Caculator::new("");
+    c.set_resolver(files);
+    c.set_file("main.calc");
+    c.new_parser("include(\"lib/area.calc\")\narea(r)\ninclude(\"nope.calc\")");
+    c.parse();
+    c.calc();
+    assert_eq!(c.get_html(), "[out 1] 12\n");
+    assert_eq!(c.include_errors(), ["main.calc:3:1: cannot find nope.calc"]);
+    c.update("include(\"lib/area.calc\")\nr + 1");
+    assert_eq!(c.get_html(), "[out 1] 3\n");
+    assert!(c.include_errors().is_empty());
+}
+
+#[test]
+fn test_diagnostics() {
+    let mut c = Caculator::new("");
+    c.new_parser("1 + ;2\nx = (3, \n4 ))\n{5 * * 6; 7} }\nf(");
+    c.parse();
+    c.calc();
+    assert_eq!(c.get_html(), "[out 1] 2\n[out 2] 7\n");
+    assert_eq!(
+        c.diagnostics(),
+        [
+            "1:5: expected an operand, found `;`",
+            "3:4: expected an operand, found `)`",
+            "4:6: expected an operand, found `*`",
+            "4:14: unmatched `}`",
+            "5:3: expected an operand, found the end",
+        ]
+    );
+    assert_eq!(c.diagnostic_spans()[..4], [4, 5, 19, 20]);
+}
+
+#[test]
+fn test_outputs() {
+    let mut c = Caculator::new("");
+    c.update("x = 2\n{ x; -i }  // c\nsin; 1 +\n");
+    let outputs = c.outputs();
+    let kinds: Vec<_> = outputs.iter().map(|o| o.kind).collect();
+    assert_eq!(
+        kinds,
+        [OutputKind::Real, OutputKind::Complex, OutputKind::Function]
+    );
+    assert_eq!(outputs[1].index, 2);
+    assert_eq!(outputs[1].value, [-0.0, -1.0]);
+    assert_eq!(outputs[1].text, "-i");
+    // both come from the block, the comment is not part of it
+    assert_eq!(outputs[0].span, [6, 15]);
+    assert_eq!(outputs[1].span, [6, 15]);
+    assert_eq!(outputs[2].span, [22, 25]);
+    let errors = c.errors();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, ErrorKind::Syntax);
+    assert_eq!(errors[0].line_colum, Some([3, 0]));
+
+    c.new_parser("out[1] + 1");
+    c.parse();
+    c.calc();
+    assert_eq!(c.outputs()[0].index, 4);
+    assert_eq!(c.outputs()[0].value, [3.0]);
+
+    // texts are escaped in html, plots are not
+    c.update("\"<img src=x onerror=alert(1)> & co\"\nf(x) = \"<b>\"\nf\nplot(sin, 0, 1)");
+    let html = c.get_html();
+    assert!(html.starts_with(
+        "[out 1] &lt;img src=x onerror=alert(1)&gt; &amp; co\n\
+         [out 2] @fun: (x) = &lt;b&gt;\n[out 3] <svg"
+    ));
+    assert_eq!(c.outputs()[0].text, "<img src=x onerror=alert(1)> & co");
+    assert_eq!(c.outputs()[2].kind, OutputKind::Svg);
+    // an imported plot is a text
+    let state = r#"{"format":"web-caculator-state","version":2,
+        "history":[{"svg":"<img src=x onerror=alert(1)>"}]}"#;
+    assert!(c.import_state(state).is_ok());
+    c.update("ans");
+    assert_eq!(c.get_html(), "[out 1] &lt;img src=x onerror=alert(1)&gt;\n");
+    assert_eq!(c.outputs()[0].kind, OutputKind::Text);
+}
+
+#[test]
+fn test_define() {
+    use my_math::{Re, Tuple, Val};
+    let mut c = Caculator::new("");
+    let readings = Val::Vars(Tuple(vec![Val::Re(Re(1.5)), Val::Re(Re(2.5))]));
+    assert!(c.define("θ", readings).is_ok());
+    assert!(c.define("k", Val::Re(Re(10.0))).is_ok());
+    c.update("θ[2] * k");
+    assert_eq!(c.get_html(), "[out 1] 25\n");
+    // the cached sentence is calculated again
+    assert!(c.define("k", Val::Re(Re(2.0))).is_ok());
+    c.update("θ[2] * k");
+    assert_eq!(c.get_html(), "[out 1] 5\n");
+    assert!(matches!(c.variable("k"), Some(Val::Re(Re(2.0)))));
+    assert!(matches!(c.variable("pi"), Some(Val::Re(_))));
+    assert!(c.variable("sin").is_none());
+    for name in ["", "2x", "a b", "true", "√", "x+1"] {
+        assert!(c.define(name, Val::NONE).is_err(), "{name}");
+    }
+}
+
+#[test]
+fn test_native_function() {
+    let lookup = NativeFunction::new("lookup", 1..=1, |_, args| match args {
+        [Val::Re(Re(x))] if *x >= 0.0 => Ok(Val::Re(Re(x * 10.0))),
+        _ => Err("no such row".to_string()),
+    });
+    let hypot = NativeFunction::real("hypot", 2, |x| x[0].hypot(x[1]));
+    let mut c = Caculator::new("");
+    assert!(c.register(lookup).is_ok());
+    assert!(c.register(hypot).is_ok());
+    assert!(c
+        .register(NativeFunction::real("f", 1, |x| x[0]).alias("2f"))
+        .is_err());
+    c.update("lookup(3) + 1\nf = lookup; f(1)\nlookup(-1)\nhypot(3, 4)\nhypot(3)");
+    assert_eq!(c.get_html(), "[out 1] 31\n[out 2] 10\n[out 3] 5\n");
+    assert_eq!(
+        c.call_errors(),
+        ["lookup: no such row", "hypot: expects 2 arguments, found 1"]
+    );
+    assert_eq!(c.errors()[0].kind, ErrorKind::Call);
+    c.update("lookup(2)");
+    assert!(c.call_errors().is_empty());
+}
+
+#[test]
+fn test_unknown_names() {
+    let mut c = Caculator::new("");
+    c.update("speed = 3; sqtr(4)\nspeeed + 1\ndivide(6, 3)\nf(x) = y + x; f(1); f(2)\nsqrt(4)");
+    assert_eq!(c.get_html(), "[out 1] 2\n");
+    assert_eq!(
+        c.call_errors(),
+        [
+            "sqtr: unknown function, did you mean sqrt or sqr?",
+            "speeed: unknown name, did you mean speed?",
+            "divide: unknown function, did you mean devide?",
+            "y: unknown name",
+        ]
+    );
+    let errors = c.errors();
+    assert_eq!(errors[0].suggestions, ["sqrt", "sqr"]);
+    assert!(errors[3].suggestions.is_empty());
+    // functions are values, and history names are known
+    c.update("g = sin; g(0); ans");
+    assert!(c.call_errors().is_empty());
+    // failed calls of the sentences not calculated again are kept
+    let sqtr = ["sqtr: unknown function, did you mean sqrt or sqr?"];
+    c.update("sqtr(4)\n1");
+    c.update("sqtr(4)\n2");
+    assert_eq!(c.recomputed(), 1);
+    assert_eq!(c.call_errors(), sqtr);
+    assert!(c.update_reactive("sqtr(4)\n1").is_ok());
+    assert!(c.update_reactive("sqtr(4)\n2").is_ok());
+    assert_eq!(c.recomputed(), 1);
+    assert_eq!(c.call_errors(), sqtr);
+}
+
+#[test]
+fn test_complete() {
+    let mut c = Caculator::new("");
+    c.update("speed = 3; square_of(t) = t * t");
+    let labels = |cs: &Completions| cs.items.iter().map(|i| i.label.clone()).collect::<Vec<_>>();
+
+    let input = "s = 1; sq";
+    let cs = c.completions(input, input.len());
+    assert_eq!(cs.span, [7, 9]);
+    assert_eq!(labels(&cs), ["square_of", "sqr", "sqrt", "square"]);
+    assert_eq!(cs.items[0].kind, CompletionKind::Function);
+    assert_eq!(cs.items[0].detail, "square_of(t)");
+    assert_eq!(cs.items[3].detail, "square(x)");
+    assert!(cs.signature.is_none());
+
+    // names assigned in the input come after those calculated
+    let input = "spent = 2; s";
+    let cs = c.completions(input, input.len());
+    assert_eq!(labels(&cs)[..2], ["speed", "spent"]);
+    assert!(labels(&cs).contains(&"sin".to_string()));
+
+    let input = "log(2, sp";
+    let cs = c.completions(input, input.len());
+    let sig = cs.signature.unwrap();
+    assert_eq!((sig.params, sig.active), (vec!["b".into(), "x".into()], 1));
+    let cs = c.completions("square_of((1, 2), ", 18);
+    assert_eq!(cs.signature.unwrap().label, "square_of(t)");
+    assert!(c.completions("f(1); x", 5).signature.is_none());
+
+    // none in numbers, comments and strings
+    assert!(c.completions("12", 2).items.is_empty());
+    assert!(c.completions("1 // s", 6).items.is_empty());
+    assert!(c.completions("\"s", 2).items.is_empty());
+    assert!(c.completions("/* s", 4).items.is_empty());
+    assert!(!c.completions("", 0).items.is_empty());
+}
+
+#[test]
+fn test_lexer_diagnostics() {
+    let mut c = Caculator::new("");
+    c.new_parser("3 $ 4\n0x1.5 + 1\ns = \"abc\n2 /* 5");
+    c.parse();
+    c.calc();
+    assert_eq!(
+        c.diagnostics(),
+        [
+            "1:3: unexpected character `$`",
+            "2:1: invalid number `0x1.5`",
+            "3:5: unterminated string",
+            "4:3: unterminated comment",
+        ]
+    );
+    assert_eq!(c.get_html(), "[out 1] 2\n");
+    // a number still being typed, at the end of the input
+    c.update("1e");
+    assert_eq!(c.diagnostics(), ["1:1: invalid number `1e`"]);
+    c.update("x = 2e");
+    assert_eq!(c.diagnostics(), ["1:5: invalid number `2e`"]);
+    c.update("2.5e");
+    assert_eq!(c.diagnostics(), ["1:1: invalid number `2.5e`"]);
+    assert!(parse("1 ? 2", 1).ends_with("1:3: unexpected character `?`\n"));
+}
+
+#[test]
+fn test_unicode() {
+    let mut c = Caculator::new("");
+    c.new_parser("θ = 2; Δx = 3\n面积 = θ × Δx\n√16 + 2³ − 面积 ÷ 3\n2² · 3");
+    c.parse();
+    c.calc();
+    assert!(c.diagnostics().is_empty());
+    assert_eq!(c.get_html(), "[out 1] 10\n[out 2] 12\n");
+    // columns count characters, also after a multi-line comment
+    c.new_parser("θθ = 1 $\n/* é\n */ 2 #");
+    c.parse();
+    assert_eq!(
+        c.diagnostics(),
+        [
+            "1:8: unexpected character `$`",
+            "3:7: unexpected character `#`"
+        ]
+    );
+    // comparisons are 1 or 0, and the glyphs are their operators
+    c.update("1 ≤ 2; 1 <= 2\n3 ≥ 4; 3 >= 4\n1 + 1 ≠ 2; 2 == 1 + 1; 1 < 2 > 0");
+    assert!(c.diagnostics().is_empty());
+    assert_eq!(
+        c.get_html(),
+        "[out 1] 1\n[out 2] 1\n[out 3] 0\n[out 4] 0\n[out 5] 0\n[out 6] 1\n[out 7] 1\n"
+    );
+}
+
+#[test]
+fn test_constants() {
+    let mut c = Caculator::new("");
+    // constants can be shadowed in a block, and the outer scope keeps them
+    c.new_parser("{e = 3; e + 1}\nround(e * 1000)\nf(i) = i + 1\nf(2)\nee = 2; ee\n");
+    c.parse();
+    c.calc();
+    assert_eq!(
+        c.get_html(),
+        "[out 1] 4\n[out 2] 2718\n[out 3] 3\n[out 4] 2\n"
+    );
+    c.new_parser("pi = 3\npi + π");
+    c.parse();
+    c.calc();
+    assert_eq!(c.get_html(), "[out 5] 6.141592653589793\n");
+    // booleans are 1 and 0
+    c.new_parser("true + true\nfalse\n2 true");
+    c.parse();
+    c.calc();
+    assert_eq!(c.get_html(), "[out 6] 2\n[out 7] 0\n[out 8] 2\n");
+    // keywords are never identifiers
+    c.new_parser("true = 1");
+    c.parse();
+    assert_eq!(c.diagnostics(), ["1:6: expected an operand, found `=`"]);
+}
+
 // rust 中的测试
 #[test]
 fn test() {
@@ -131,3 +899,147 @@ fn test() {
     let res: my_math::Val = exp.calc(&mut runtime).reduce();
     println!("{:#?}", res);
 }
+
+#[test]
+fn test_recursion() {
+    // nested expressions count with the calls, so the stack never overflows
+    let run = std::thread::Builder::new()
+        .stack_size(1536 << 10)
+        .spawn(|| {
+            let mut c = Caculator::new("");
+            for n in [0, 10, 200] {
+                let body = format!("{}f(n + 1){}", "(".repeat(n), ")".repeat(n));
+                c.update(&format!("f(n) = {body}; f(1)"));
+                assert_eq!(c.call_errors(), ["f: recursion too deep"]);
+            }
+            let calls = format!("{}1{}", "g(".repeat(100), ")".repeat(100));
+            c.update(&format!("g(x) = x + 1; {calls}"));
+            c.get_html()
+        });
+    assert_eq!(run.unwrap().join().unwrap(), "[out 1] 101\n");
+}
+
+#[test]
+fn test_power() {
+    // `^` is right associative and binds tighter than unary minus
+    let c = Caculator::new("2 ^ 10; 2 ^ 3 ^ 2; -2 ^ 2; (-2) ^ 2; 4 ^ 0.5; 2 ^ -1; x = 3; x²");
+    assert_eq!(
+        c.get_html(),
+        "[out 1] 1024\n[out 2] 512\n[out 3] -4\n[out 4] 4\n[out 5] 2\n[out 6] 0.5\n[out 7] 9\n"
+    );
+}
+
+#[test]
+fn test_repl() {
+    // what the REPL prints for each input, in one session
+    let run = |c: &mut Caculator, text: &str| -> Vec<String> {
+        c.new_parser(text);
+        c.parse();
+        c.calc();
+        let outputs = c.outputs().into_iter();
+        outputs
+            .map(|o| format!("[out {}] {}", o.index, o.text))
+            .collect()
+    };
+    let mut c = Caculator::new("");
+    assert_eq!(run(&mut c, "x = 2\nx * 3"), ["[out 1] 6"]);
+    assert_eq!(run(&mut c, "\"<b>\"; ans"), ["[out 2] <b>", "[out 3] <b>"]);
+    // more lines are read until blocks and comments are closed
+    assert!(unclosed("{ y = 1"));
+    assert!(unclosed("1 /* a"));
+    assert!(!unclosed("{ y = 1\n}"));
+    assert!(!unclosed("\"{\""));
+    assert_eq!(run(&mut c, "{ x = x + 1\n}\nx"), ["[out 4] 3"]);
+    // :history, :clear, :tokens and :tree
+    assert_eq!(c.history(), ["6", "<b>", "<b>", "3"]);
+    c.clear_history();
+    assert_eq!(run(&mut c, "x"), ["[out 1] 3"]);
+    assert_eq!(
+        parse("1 + x", 2),
+        "Number[0, 0](\"1\")\nWhitespace[0, 1](\" \")\nPlus[0, 2](\"+\")\n\
+         Whitespace[0, 3](\" \")\nIdentifier[0, 4](\"x\")\n"
+    );
+    assert_eq!(
+        tree("1 + x"),
+        "+Article Sentences 1\n+---+Expression: 1\n|   +--- Literal 1\n\
+         |   | Operator +\n|   +--- Identifier x"
+    );
+
+    // a file includes the files next to it
+    let dir = std::env::temp_dir().join(format!("caculator-repl-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("area.calc"), "area(x) = 3 * x * x").unwrap();
+    let main = dir.join("main.calc");
+    std::fs::write(&main, "include(\"area.calc\")\narea(2)").unwrap();
+    let mut c = Caculator::new("");
+    c.set_file(main.to_str().unwrap());
+    let text = std::fs::read_to_string(&main).unwrap();
+    assert_eq!(run(&mut c, &text), ["[out 1] 12"]);
+    assert!(c.include_errors().is_empty());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_curves() {
+    // the points of the curves of a plot, in pixels
+    let points = |svg: &str| -> Vec<[f64; 2]> {
+        let mut res = vec![];
+        for p in svg.split("<path d=\"").skip(1) {
+            for xy in p[..p.find('"').unwrap()].split(['M', 'L']).skip(1) {
+                let (x, y) = xy.split_once(',').unwrap();
+                res.push([x.parse().unwrap(), y.parse().unwrap()]);
+            }
+        }
+        res
+    };
+    // a circle, also on the screen as both axes have the same unit
+    let round = |ps: &[[f64; 2]]| {
+        let min = |i: usize| ps.iter().map(|p| p[i]).fold(f64::INFINITY, f64::min);
+        let max = |i: usize| ps.iter().map(|p| p[i]).fold(f64::NEG_INFINITY, f64::max);
+        let r = (max(0) - min(0)) / 2.0;
+        let c = [(max(0) + min(0)) / 2.0, (max(1) + min(1)) / 2.0];
+        ps.len() > 20
+            && (max(1) - min(1) - 2.0 * r).abs() < 1.0
+            && ps
+                .iter()
+                .all(|p| ((p[0] - c[0]).hypot(p[1] - c[1]) - r).abs() < 1.5)
+    };
+    let mut c = Caculator::new("");
+    c.update(
+        "r(t) = 1\nparametric((cos, sin), 0, 2 pi)\npolar(r, 0, 2 pi)\n\
+         implicit(\"x^2 + y^2 - 1\", -2, 2, -2, 2)\nparametric(\"(t, t^2)\", -1, 1)",
+    );
+    let out = c.outputs();
+    assert!(out.iter().all(|o| o.kind == OutputKind::Svg));
+    assert!(c.call_errors().is_empty());
+    for o in &out[..3] {
+        let ps = points(&o.text);
+        assert!(round(&ps), "{}", o.text);
+    }
+    // closed curves end where they start
+    let ps = points(&out[0].text);
+    assert_eq!(ps[0], ps[ps.len() - 1]);
+    // the parabola is symmetric, and labelled by its expression
+    let ps = points(&out[3].text);
+    let (first, last) = (ps[0], ps[ps.len() - 1]);
+    assert_eq!(first[1], last[1]);
+    let vertex = ps
+        .iter()
+        .fold(first, |v, p| if p[1] > v[1] { *p } else { v });
+    assert!((vertex[0] - (first[0] + last[0]) / 2.0).abs() < 1.0);
+    assert!(out[3].text.contains(">(t, t^2)</text>"));
+
+    // no zero, or only a sign change at a pole, draws nothing
+    c.update("implicit(\"x^2 + y^2 + 1\", -2, 2, -2, 2)\nimplicit(\"1 / x\", -1, 1, -1, 1)");
+    assert!(c.outputs().iter().all(|o| points(&o.text).is_empty()));
+    c.update("polar(\"1 +\", 0, 1)\nparametric((cos, sin), 1, 0)\nimplicit(\"x\", -1, 1, 1, -1)");
+    assert!(c.outputs().is_empty());
+    assert_eq!(
+        c.call_errors(),
+        [
+            "polar: not an expression: 1 +",
+            "parametric: expects a < b",
+            "implicit: expects a < b"
+        ]
+    );
+}
@@ -0,0 +1,318 @@
+use js_sys::{Array, Object};
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    my_cst::{Child, Cst, SyntaxKind},
+    my_js::{set, span_to_js},
+    my_math::{Fun, Val},
+    my_parser::TokenType,
+    my_runtime::Runtime,
+};
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_COMPLETIONS: &'static str = r#"
+export type CompletionKind = "variable" | "function" | "builtin" | "constant" | "keyword";
+
+export interface Completion {
+    label: string;
+    kind: CompletionKind;
+    /** the value, or the signature of a function */
+    detail: string;
+}
+
+export interface SignatureHelp {
+    /** like `log(b, x)` */
+    label: string;
+    params: string[];
+    /** the parameter at the cursor, counted from 0 */
+    active: number;
+    description: string;
+}
+
+export interface Completions {
+    /** `[start, end)` of the text a completion replaces, in characters */
+    span: [number, number];
+    items: Completion[];
+    /** missing outside of `f(...)` */
+    signature?: SignatureHelp;
+}
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    /// `{ span, items, signature }` as plain objects
+    #[wasm_bindgen(typescript_type = "Completions")]
+    pub type JsCompletions;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CompletionKind {
+    /// assigned in the runtime or the input
+    Variable,
+    /// `f(x) = ...` in the runtime or the input
+    Function,
+    Builtin,
+    Constant,
+    Keyword,
+}
+
+impl CompletionKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            CompletionKind::Variable => "variable",
+            CompletionKind::Function => "function",
+            CompletionKind::Builtin => "builtin",
+            CompletionKind::Constant => "constant",
+            CompletionKind::Keyword => "keyword",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Completion {
+    pub label: String,
+    pub kind: CompletionKind,
+    /// the value, or the signature of a function
+    pub detail: String,
+}
+
+/// the function called around the cursor
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignatureHelp {
+    pub label: String,
+    pub params: Vec<String>,
+    /// the parameter at the cursor, counted from 0
+    pub active: usize,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Completions {
+    /// [start, end] of the text a completion replaces
+    pub span: [usize; 2],
+    /// the names starting with that text, variables first
+    pub items: Vec<Completion>,
+    pub signature: Option<SignatureHelp>,
+}
+
+/// names for the cursor at `pos` in the text of `cst`, known to `rt` or assigned in the text
+pub fn complete(cst: &Cst, pos: usize, rt: &Runtime) -> Completions {
+    let tokens = cst.tokens();
+    // the token ending at the cursor, or holding it
+    let before = pos.checked_sub(1).and_then(|p| cst.token_at(p));
+    // no names in numbers, comments and open strings
+    let quiet = before.is_some_and(|t| {
+        let open = tokens[t].pos()[1] > pos || tokens[t].has_error();
+        match tokens[t].token_type() {
+            TokenType::Number | TokenType::SingleLineComment => true,
+            TokenType::String | TokenType::Char | TokenType::MuitiLineComment => open,
+            // unterminated, up to the end of the line
+            TokenType::Error => ['"', '\'', '/']
+                .iter()
+                .any(|&c| tokens[t].lexeme().starts_with(c)),
+            _ => false,
+        }
+    });
+    let word = before.filter(|&t| tokens[t].token_type() == TokenType::Identifier);
+    let start = word.map_or(pos, |t| tokens[t].pos()[0]);
+    let prefix: String = match word {
+        Some(t) => tokens[t].lexeme().chars().take(pos - start).collect(),
+        None => String::new(),
+    };
+    let items = if quiet {
+        vec![]
+    } else {
+        candidates(cst, rt)
+            .into_iter()
+            .filter(|c| c.label.starts_with(&prefix))
+            .collect()
+    };
+    let end = word.map_or(pos, |t| tokens[t].pos()[1]);
+    Completions {
+        span: [start, end],
+        items,
+        signature: signature(cst, pos, rt),
+    }
+}
+
+/// every name, each once, by kind and then by name
+fn candidates(cst: &Cst, rt: &Runtime) -> Vec<Completion> {
+    let sys = &rt.sys;
+    let mut res = vec![];
+    for (name, val) in rt.copy_env() {
+        let (kind, detail) = match &val {
+            Val::Fun(Fun::User(c)) => (
+                CompletionKind::Function,
+                format!("{name}({})", c.params.join(", ")),
+            ),
+            _ => (CompletionKind::Variable, val.text(sys)),
+        };
+        res.push(Completion {
+            label: name,
+            kind,
+            detail,
+        });
+    }
+    // names assigned in the text, which may not be calculated yet
+    for node in cst.nodes() {
+        let kind = match node.kind {
+            SyntaxKind::Assignment => CompletionKind::Variable,
+            SyntaxKind::FunctionDef => CompletionKind::Function,
+            _ => continue,
+        };
+        if let Some(&Child::Token(t)) = node.children.first() {
+            let label = cst.tokens()[t].lexeme().to_string();
+            let detail = match kind {
+                CompletionKind::Function => cst_params(cst, node.children.as_slice(), &label),
+                _ => String::new(),
+            };
+            res.push(Completion {
+                label,
+                kind,
+                detail,
+            });
+        }
+    }
+    for f in sys.function_list() {
+        for name in f.names() {
+            res.push(Completion {
+                label: name.clone(),
+                kind: CompletionKind::Builtin,
+                detail: f.signature.clone(),
+            });
+        }
+    }
+    for (name, val, _) in sys.constant_list() {
+        res.push(Completion {
+            label: name.to_string(),
+            kind: CompletionKind::Constant,
+            detail: val.text(sys),
+        });
+    }
+    for word in ["true", "false"] {
+        res.push(Completion {
+            label: word.to_string(),
+            kind: CompletionKind::Keyword,
+            detail: String::new(),
+        });
+    }
+    // the runtime first, it has the values
+    let mut seen = std::collections::HashSet::new();
+    res.retain(|c| seen.insert(c.label.clone()));
+    res.sort_by(|a, b| (a.kind, &a.label).cmp(&(b.kind, &b.label)));
+    res
+}
+
+/// `f(x, y)` of a definition in the text
+fn cst_params(cst: &Cst, children: &[Child], name: &str) -> String {
+    let params: Vec<&str> = children
+        .iter()
+        .map_while(|&c| match c {
+            Child::Token(t) if cst.tokens()[t].token_type() != TokenType::Equal => Some(t),
+            _ => None,
+        })
+        .filter(|&t| cst.tokens()[t].token_type() == TokenType::Identifier)
+        .skip(1)
+        .map(|t| cst.tokens()[t].lexeme())
+        .collect();
+    format!("{name}({})", params.join(", "))
+}
+
+/// the innermost unclosed `name(` before the cursor
+fn signature(cst: &Cst, pos: usize, rt: &Runtime) -> Option<SignatureHelp> {
+    let tokens = cst.tokens();
+    let mut depth = 0;
+    let mut active = 0;
+    let before = tokens
+        .iter()
+        .enumerate()
+        .take_while(|(_, t)| t.pos()[1] <= pos);
+    let mut open = None;
+    for (i, t) in before.collect::<Vec<_>>().into_iter().rev() {
+        match t.token_type() {
+            TokenType::RightParen | TokenType::RightSquare => depth += 1,
+            TokenType::LeftSquare if depth > 0 => depth -= 1,
+            TokenType::LeftParen if depth > 0 => depth -= 1,
+            TokenType::LeftParen => {
+                open = Some(i);
+                break;
+            }
+            TokenType::Comma if depth == 0 => active += 1,
+            // a sentence ends before
+            TokenType::Semicolon
+            | TokenType::LeftBrace
+            | TokenType::RightBrace
+            | TokenType::LeftSquare
+                if depth == 0 =>
+            {
+                return None
+            }
+            _ => {}
+        }
+    }
+    let name = tokens[..open?]
+        .iter()
+        .rev()
+        .find(|t| !t.is_skipped())
+        .filter(|t| t.token_type() == TokenType::Identifier)?
+        .lexeme();
+    if let Some(Val::Fun(Fun::User(c))) = rt.lookup(name) {
+        return Some(SignatureHelp {
+            label: format!("{name}({})", c.params.join(", ")),
+            params: c.params.clone(),
+            active,
+            description: String::new(),
+        });
+    }
+    let f = rt.sys.function(name)?;
+    // the first form taking the active parameter, like `log(b, x)` for the second
+    let forms: Vec<&str> = f.signature.split("), ").collect();
+    let params = forms
+        .iter()
+        .map(|form| {
+            let inner = form
+                .split_once('(')
+                .map_or("", |(_, p)| p.trim_end_matches(')'));
+            inner
+                .split(", ")
+                .filter(|p| !p.is_empty())
+                .map(|p| p.to_string())
+                .collect::<Vec<_>>()
+        })
+        .find(|ps| ps.len() > active)
+        .unwrap_or_default();
+    Some(SignatureHelp {
+        label: f.signature.clone(),
+        params,
+        active,
+        description: f.doc.clone(),
+    })
+}
+
+/// as the `Completions` of the typescript definitions
+pub fn to_js(c: &Completions) -> JsCompletions {
+    let items: Array = c
+        .items
+        .iter()
+        .map(|item| {
+            let res = Object::new();
+            set(&res, "label", item.label.as_str().into());
+            set(&res, "kind", item.kind.name().into());
+            set(&res, "detail", item.detail.as_str().into());
+            res
+        })
+        .collect();
+    let res = Object::new();
+    set(&res, "span", span_to_js(c.span));
+    set(&res, "items", items.into());
+    if let Some(s) = &c.signature {
+        let sig = Object::new();
+        set(&sig, "label", s.label.as_str().into());
+        let params: Array = s.params.iter().map(|p| JsValue::from_str(p)).collect();
+        set(&sig, "params", params.into());
+        set(&sig, "active", (s.active as u32).into());
+        set(&sig, "description", s.description.as_str().into());
+        set(&res, "signature", sig.into());
+    }
+    res.unchecked_into()
+}
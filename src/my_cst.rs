@@ -0,0 +1,216 @@
+use std::cmp::Reverse;
+
+use crate::{my_highlight, my_parser::Token};
+
+/// kinds of the nodes of a `Cst`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyntaxKind {
+    /// the whole text
+    Article,
+    /// { ... }
+    Block,
+    /// x = ...
+    Assignment,
+    /// f(x, y) = ...
+    FunctionDef,
+    /// include("file")
+    Include,
+    /// tokens skipped by a malformed sentence
+    Error,
+    /// a + b - c, one node for the whole chain
+    Binary,
+    /// -x, +x
+    Unary,
+    /// f(...), √x
+    Call,
+    /// x[...]
+    Index,
+    /// (...)
+    Tuple,
+}
+
+/// a node or a token under a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Child {
+    Node(usize),
+    Token(usize),
+}
+
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub kind: SyntaxKind,
+    /// [start, end] in the input, like `Token::pos`
+    pub span: [usize; 2],
+    /// the article is its own parent
+    pub parent: usize,
+    /// in the order of the text
+    pub children: Vec<Child>,
+}
+
+/// Concrete syntax tree, keeping every token of the input together with
+/// whitespaces, new lines and comments, so the text can be spelled back exactly.
+///
+/// Nodes live in one list, the article first; literals and names are tokens.
+#[derive(Debug, Clone)]
+pub struct Cst {
+    tokens: Vec<Token>,
+    /// node holding each token
+    owners: Vec<usize>,
+    nodes: Vec<Node>,
+}
+
+impl Cst {
+    /// put the tokens under the nodes of `spans`, which are nested or apart
+    pub(crate) fn new(tks: &[Token], mut spans: Vec<(SyntaxKind, [usize; 2])>) -> Self {
+        // outer nodes first, equal spans keep their order
+        spans.sort_by_key(|(_, s)| (s[0], Reverse(s[1])));
+        let end = tks.last().map_or(0, |t| t.pos()[1]);
+        let mut nodes = vec![Node {
+            kind: SyntaxKind::Article,
+            span: [0, end],
+            parent: 0,
+            children: vec![],
+        }];
+        let mut owners = vec![];
+        let mut open = vec![0];
+        let mut spans = spans.into_iter().peekable();
+        for (i, t) in tks.iter().enumerate() {
+            let start = t.pos()[0];
+            loop {
+                while open.len() > 1 && nodes[*open.last().unwrap()].span[1] <= start {
+                    open.pop();
+                }
+                let Some((kind, span)) = spans.next_if(|(_, s)| s[0] <= start) else {
+                    break;
+                };
+                let parent = *open.last().unwrap();
+                let id = nodes.len();
+                nodes[parent].children.push(Child::Node(id));
+                nodes.push(Node {
+                    kind,
+                    span,
+                    parent,
+                    children: vec![],
+                });
+                open.push(id);
+            }
+            let owner = *open.last().unwrap();
+            nodes[owner].children.push(Child::Token(i));
+            owners.push(owner);
+        }
+        Cst {
+            tokens: tks.to_vec(),
+            owners,
+            nodes,
+        }
+    }
+
+    /// the article
+    pub fn root(&self) -> &Node {
+        &self.nodes[0]
+    }
+    pub fn node(&self, id: usize) -> &Node {
+        &self.nodes[id]
+    }
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+    pub fn tokens(&self) -> &[Token] {
+        &self.tokens
+    }
+
+    /// the input, exactly
+    pub fn text(&self) -> String {
+        self.tokens.iter().map(|t| t.lexeme()).collect()
+    }
+
+    /// the token covering the character at `pos`
+    pub fn token_at(&self, pos: usize) -> Option<usize> {
+        let i = self.tokens.partition_point(|t| t.pos()[1] <= pos);
+        (i < self.tokens.len() && self.tokens[i].pos()[0] <= pos).then_some(i)
+    }
+
+    /// the node holding the token
+    pub fn owner(&self, token: usize) -> usize {
+        self.owners[token]
+    }
+
+    /// nodes holding the token, the innermost first, the article last
+    pub fn ancestors(&self, token: usize) -> Vec<usize> {
+        let mut res = vec![self.owners[token]];
+        while let Some(&id) = res.last().filter(|&&id| id != 0) {
+            res.push(self.nodes[id].parent);
+        }
+        res
+    }
+
+    /// the text, level 11: as highlighted html
+    pub fn print(&self, level: usize) -> String {
+        if level == 11 {
+            my_highlight::to_html(&self.text(), &my_highlight::highlight(self))
+        } else {
+            self.text()
+        }
+    }
+}
+
+#[test]
+fn test_cst() {
+    use crate::my_parser::LexicalParser;
+    let cst = |text: &str| LexicalParser::new_inline(text.to_string()).parse().cst;
+
+    let texts = [
+        "x = f(1, 2) + 3 // c\n{ y = -x }",
+        "  --x;\t/* a\n b */ 2 x²\n",
+        "g(a) = a ) 2\n} 1 $ \"abc",
+        "θ = √(1 + 2) /* unterminated",
+    ];
+    for text in texts {
+        assert_eq!(cst(text).text(), text);
+    }
+
+    let c = cst(texts[0]);
+    let kinds = |ids: Vec<usize>| ids.iter().map(|&i| c.node(i).kind).collect::<Vec<_>>();
+    let one = c.token_at(6).unwrap();
+    assert_eq!(c.tokens()[one].lexeme(), "1");
+    assert_eq!(
+        kinds(c.ancestors(one)),
+        [
+            SyntaxKind::Call,
+            SyntaxKind::Binary,
+            SyntaxKind::Assignment,
+            SyntaxKind::Article
+        ]
+    );
+    let x = c.token_at(c.text().len() - 3).unwrap();
+    assert_eq!(
+        kinds(c.ancestors(x)),
+        [
+            SyntaxKind::Unary,
+            SyntaxKind::Assignment,
+            SyntaxKind::Block,
+            SyntaxKind::Article
+        ]
+    );
+    // the comment stays between the sentences
+    let root: Vec<_> = c
+        .root()
+        .children
+        .iter()
+        .map(|&ch| match ch {
+            Child::Node(id) => format!("{:?}", c.node(id).kind),
+            Child::Token(t) => c.tokens()[t].lexeme().to_string(),
+        })
+        .collect();
+    assert_eq!(root, ["Assignment", " ", "// c", "\n", "Block"]);
+
+    let c = cst(texts[2]);
+    let errors: Vec<_> = c
+        .nodes()
+        .iter()
+        .filter(|n| n.kind == SyntaxKind::Error)
+        .map(|n| n.span)
+        .collect();
+    assert_eq!(errors, [[9, 12], [13, 14], [15, 23]]);
+    assert!(cst("a < b").print(11).contains("&lt;"));
+}
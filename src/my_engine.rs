@@ -0,0 +1,156 @@
+use crate::{
+    check_name,
+    my_complete::{self, Completions},
+    my_math::{my_builtins::NativeFunction, Val},
+    my_output::{self, Output, OutputError},
+    my_parser::LexicalParser,
+    my_runtime::Runtime,
+};
+
+/// The calculator for embedding in Rust programs.
+///
+/// Every `eval` runs in the same runtime, so variables, user functions and
+/// `out[n]` are kept between calls. Functions and constants of the domain
+/// are given to `Engine::builder` before the first sentence.
+pub struct Engine {
+    rt: Runtime,
+}
+
+/// functions and constants for a new `Engine`
+#[derive(Default)]
+pub struct EngineBuilder {
+    functions: Vec<NativeFunction>,
+    constants: Vec<(String, Val, String)>,
+}
+
+/// what an `eval` writes out
+#[derive(Debug, Clone, PartialEq)]
+pub struct Evaluation {
+    pub outputs: Vec<Output>,
+    pub errors: Vec<OutputError>,
+}
+
+impl EngineBuilder {
+    /// replaces a builtin function of the same name
+    pub fn function(mut self, f: NativeFunction) -> Self {
+        self.functions.push(f);
+        self
+    }
+    /// replaces a builtin constant like `pi`, variables may shadow it
+    pub fn constant(self, name: &str, val: Val) -> Self {
+        self.constant_with_doc(name, val, "")
+    }
+    /// a constant with what it is, for `help` and `constants()`
+    pub fn constant_with_doc(mut self, name: &str, val: Val, doc: &str) -> Self {
+        self.constants
+            .push((name.to_string(), val, doc.to_string()));
+        self
+    }
+    /// fails for names that are not identifiers
+    pub fn build(self) -> Result<Engine, String> {
+        let mut engine = Engine { rt: Runtime::new() };
+        for f in self.functions {
+            engine.register(f)?;
+        }
+        for (name, val, doc) in self.constants {
+            check_name(&name)?;
+            engine.rt.sys.set_constant(name, val, &doc);
+        }
+        Ok(engine)
+    }
+}
+
+impl Engine {
+    /// an engine with the builtin functions and constants
+    pub fn new() -> Self {
+        Engine { rt: Runtime::new() }
+    }
+    pub fn builder() -> EngineBuilder {
+        EngineBuilder::default()
+    }
+
+    /// calculate the sentences of `input` after those of the calls before
+    pub fn eval(&mut self, input: &str) -> Evaluation {
+        let (a, diagnostics) =
+            LexicalParser::new_inline(input.to_string()).parse_with_diagnostics();
+        self.rt.include.clear_errors();
+        self.rt.clear_call_errors();
+        let first = self.rt.history_len();
+        let res = a.calc(&mut self.rt);
+        Evaluation {
+            outputs: my_output::outputs(&res, &a.spans, first, &self.rt.sys),
+            errors: my_output::errors(&diagnostics, &self.rt),
+        }
+    }
+
+    /// a variable or constant
+    pub fn get(&self, name: &str) -> Option<Val> {
+        let val = self.rt.lookup(name);
+        val.or_else(|| self.rt.sys.get_sys_val(name).cloned())
+    }
+    /// assign a variable as if by `name = val`
+    pub fn set(&mut self, name: &str, val: Val) -> Result<(), String> {
+        check_name(name)?;
+        self.rt.set_val(name.to_string(), val);
+        Ok(())
+    }
+    /// add a function, or replace the one of the same name
+    pub fn register(&mut self, f: NativeFunction) -> Result<(), String> {
+        f.names().try_for_each(|name| check_name(name))?;
+        self.rt.sys.register(f);
+        Ok(())
+    }
+
+    /// names for the cursor at `cursor` characters into `input`,
+    /// and the function called around it
+    pub fn complete(&self, input: &str, cursor: usize) -> Completions {
+        my_complete::complete(&crate::syntax(input), cursor, &self.rt)
+    }
+
+    /// `val` written by the format settings, like the outputs
+    pub fn text(&self, val: &Val) -> String {
+        val.text(&self.rt.sys)
+    }
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[test]
+fn test_engine() {
+    use crate::my_math::Re;
+    let rate = NativeFunction::new("rate", 1..=2, |_, args| {
+        let x = crate::my_math::my_builtins::reals(args)?;
+        Ok(Val::Re(Re(x[0] * x.get(1).unwrap_or(&1.0))))
+    })
+    .alias("r")
+    .doc("x, or x times the factor");
+    let mut e = Engine::builder()
+        .function(rate)
+        .constant("g", Val::Re(Re(10.0)))
+        .build()
+        .unwrap();
+    let res = e.eval("m = 2; rate(m * g); r(3, 4)");
+    let texts: Vec<_> = res.outputs.iter().map(|o| o.text.as_str()).collect();
+    assert_eq!(texts, ["20", "12"]);
+    assert!(res.errors.is_empty());
+
+    // variables and outputs stay
+    assert!(e.set("k", Val::Re(Re(0.5))).is_ok());
+    let res = e.eval("out[1] * k + m; rate(1, 2, 3)");
+    assert_eq!(res.outputs[0].index, 3);
+    assert_eq!(res.outputs[0].text, "12");
+    assert_eq!(
+        res.errors[0].message,
+        "rate: expects 1 to 2 arguments, found 3"
+    );
+    assert!(matches!(e.get("g"), Some(Val::Re(Re(10.0)))));
+
+    assert!(Engine::builder()
+        .constant("not a name", Val::NONE)
+        .build()
+        .is_err());
+}
@@ -0,0 +1,471 @@
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    my_cst::{Child, Cst, SyntaxKind},
+    my_parser::{Token, TokenType},
+};
+
+/// how `format` lays out the text
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy)]
+pub struct FormatOptions {
+    /// spaces for each level of blocks and wrapped arguments
+    pub indent: usize,
+    /// wanted length of lines, longer argument lists are put one per line
+    pub width: usize,
+    /// spaces around binary operators, `1 + 2` rather than `1+2`
+    pub spaced: bool,
+}
+
+#[wasm_bindgen]
+impl FormatOptions {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions {
+            indent: 4,
+            width: 80,
+            spaced: true,
+        }
+    }
+}
+
+/// Pretty-print the text of `cst`.
+///
+/// * a sentence a line, unless joined by `;`, with at most one blank line between
+/// * blocks written over lines are indented, one-line blocks stay `{ a; b }`
+/// * comments are kept, malformed sentences are left as written
+pub fn format(cst: &Cst, options: &FormatOptions) -> String {
+    let f = Formatter { cst, options };
+    let lines = f.sentences(&cst.root().children, 0);
+    // an unterminated comment runs to the end, new lines and all
+    let open = cst
+        .tokens()
+        .last()
+        .is_some_and(|t| t.token_type() == TokenType::MuitiLineComment && t.has_error());
+    if lines.is_empty() || open {
+        lines.join("\n")
+    } else {
+        lines.join("\n") + "\n"
+    }
+}
+
+struct Formatter<'a> {
+    cst: &'a Cst,
+    options: &'a FormatOptions,
+}
+
+impl Formatter<'_> {
+    fn token(&self, t: usize) -> &Token {
+        &self.cst.tokens()[t]
+    }
+    fn pad(&self, level: usize) -> String {
+        " ".repeat(level * self.options.indent)
+    }
+    /// the token of a child, `None` for nodes
+    fn ty(&self, c: Child) -> Option<TokenType> {
+        match c {
+            Child::Token(t) => Some(self.token(t).token_type()),
+            Child::Node(_) => None,
+        }
+    }
+    /// the first or the last token of a child
+    fn edge(&self, c: Child, last: bool) -> &Token {
+        match c {
+            Child::Token(t) => self.token(t),
+            Child::Node(id) => {
+                let span = self.cst.node(id).span;
+                let pos = if last { span[1] - 1 } else { span[0] };
+                self.token(self.cst.token_at(pos).unwrap_or_default())
+            }
+        }
+    }
+
+    /// the sentences of an article or a block as lines
+    fn sentences(&self, children: &[Child], level: usize) -> Vec<String> {
+        let pad = self.pad(level);
+        let mut lines: Vec<String> = vec![];
+        let mut line = String::new();
+        // new lines since the last item
+        let mut newlines = 0;
+        // the next item stays on the line, after `;` or `/* */`
+        let mut joined = false;
+        // the line ends with a `//` comment or an unterminated string
+        let mut open = false;
+        for &c in children {
+            let ty = self.ty(c);
+            match ty {
+                Some(TokenType::Whitespace) => continue,
+                Some(TokenType::NewLine) => {
+                    newlines += 1;
+                    joined = false;
+                    continue;
+                }
+                Some(TokenType::Semicolon) => {
+                    if open {
+                        lines.push(pad.clone() + &line);
+                        line.clear();
+                        open = false;
+                    }
+                    line += ";";
+                    joined = true;
+                    newlines = 0;
+                    continue;
+                }
+                _ => {}
+            }
+            // kept after the sentence before it
+            let trailing = match c {
+                Child::Token(_) => matches!(
+                    ty,
+                    Some(TokenType::SingleLineComment | TokenType::MuitiLineComment)
+                ),
+                Child::Node(id) => self.cst.node(id).kind == SyntaxKind::Error,
+            };
+            let stays = joined || (trailing && newlines == 0);
+            if !line.is_empty() && !stays {
+                lines.push(pad.clone() + &line);
+                line.clear();
+            }
+            if line.is_empty() && newlines > 1 && !lines.is_empty() {
+                lines.push(String::new());
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            let col = column(pad.len(), &line);
+            line += &self.sentence(c, level, col);
+            joined = ty == Some(TokenType::MuitiLineComment);
+            open = to_line_end(self.edge(c, true));
+            newlines = 0;
+        }
+        if !line.is_empty() {
+            lines.push(pad + &line);
+        }
+        lines
+    }
+
+    /// a sentence or a comment starting at the column `col`
+    fn sentence(&self, c: Child, level: usize, col: usize) -> String {
+        match c {
+            Child::Token(t) => self.token(t).lexeme().to_string(),
+            Child::Node(id) => match self.cst.node(id).kind {
+                SyntaxKind::Block => self.block(id, level, col),
+                SyntaxKind::Error => self.verbatim(id),
+                _ => self.inline(id, level, col, true),
+            },
+        }
+    }
+
+    /// the text of a node as written
+    fn verbatim(&self, id: usize) -> String {
+        let mut res = String::new();
+        for &c in &self.cst.node(id).children {
+            match c {
+                Child::Token(t) => res += self.token(t).lexeme(),
+                Child::Node(id) => res += &self.verbatim(id),
+            }
+        }
+        res.trim().to_string()
+    }
+
+    fn block(&self, id: usize, level: usize, col: usize) -> String {
+        let children = &self.cst.node(id).children;
+        let closed = children.len() > 1
+            && self.ty(children[children.len() - 1]) == Some(TokenType::RightBrace);
+        let inner = &children[1..children.len() - closed as usize];
+        let close = if closed { "}" } else { "" };
+        let lines = self.sentences(inner, level + 1);
+        // written over lines, or with a comment to the end of the line
+        let multiline = inner.iter().any(|&c| {
+            matches!(
+                self.ty(c),
+                Some(TokenType::NewLine | TokenType::SingleLineComment)
+            )
+        });
+        if !closed && lines.is_empty() {
+            return "{".to_string();
+        }
+        if !multiline && lines.len() <= 1 {
+            let flat = match lines.first() {
+                Some(l) => format!("{{ {} {close}", l.trim_start()),
+                None => format!("{{{close}"),
+            };
+            if !flat.contains('\n') && col + flat.chars().count() <= self.options.width {
+                return flat;
+            }
+        }
+        // an unclosed block ends with its last sentence
+        if !closed {
+            return format!("{{\n{}", lines.join("\n"));
+        }
+        format!("{{\n{}\n{}}}", lines.join("\n"), self.pad(level))
+    }
+
+    /// an expression, assignment or definition on one line,
+    /// unless `wrap` allows long argument lists to be put one per line
+    fn inline(&self, id: usize, level: usize, col: usize, wrap: bool) -> String {
+        let node = self.cst.node(id);
+        let commas = node
+            .children
+            .iter()
+            .any(|&c| self.ty(c) == Some(TokenType::Comma));
+        if wrap && commas && matches!(node.kind, SyntaxKind::Call | SyntaxKind::Tuple) {
+            let flat = self.inline(id, level, col, false);
+            if flat.contains('\n') || col + flat.chars().count() > self.options.width {
+                return self.wrapped(id, level);
+            }
+            return flat;
+        }
+        let mut res = String::new();
+        let mut prev: Option<Child> = None;
+        let mut comments = vec![];
+        for &c in &node.children {
+            match self.ty(c) {
+                Some(TokenType::Whitespace | TokenType::NewLine) => continue,
+                Some(TokenType::SingleLineComment | TokenType::MuitiLineComment) => {
+                    comments.push(c);
+                    continue;
+                }
+                _ => {}
+            }
+            if !comments.is_empty() {
+                for &m in &comments {
+                    res += &format!(" {}", self.edge(m, false).lexeme());
+                    if self.ty(m) == Some(TokenType::SingleLineComment) {
+                        res += &format!("\n{}", self.pad(level + 1));
+                    }
+                }
+                if !res.ends_with(' ') {
+                    res.push(' ');
+                }
+                comments.clear();
+            } else if let Some(p) = prev {
+                res += self.space(node.kind, p, c);
+            }
+            let col = column(col, &res);
+            res += &self.piece(node.kind, c, level, col, wrap);
+            prev = Some(c);
+        }
+        res
+    }
+
+    /// `f(` and `(` followed by one argument a line
+    fn wrapped(&self, id: usize, level: usize) -> String {
+        let node = self.cst.node(id);
+        let pad = self.pad(level + 1);
+        let mut res = String::new();
+        let mut open = false;
+        let mut fresh = false;
+        let mut commented = false;
+        for (i, &c) in node.children.iter().enumerate() {
+            match self.ty(c) {
+                Some(TokenType::Whitespace | TokenType::NewLine) => continue,
+                Some(TokenType::LeftParen) if !open => {
+                    res.push('(');
+                    open = true;
+                    fresh = true;
+                    continue;
+                }
+                Some(TokenType::RightParen) if i + 1 == node.children.len() => {
+                    res += &format!("\n{})", self.pad(level));
+                    continue;
+                }
+                Some(TokenType::Comma) => {
+                    if commented {
+                        res += &format!("\n{pad}");
+                        commented = false;
+                    }
+                    res.push(',');
+                    fresh = true;
+                    continue;
+                }
+                Some(TokenType::SingleLineComment | TokenType::MuitiLineComment) => {
+                    // nothing more on the line of a `//` comment
+                    if commented {
+                        res += &format!("\n{pad}");
+                    }
+                    res += &format!(" {}", self.edge(c, false).lexeme());
+                    commented = self.ty(c) == Some(TokenType::SingleLineComment);
+                    fresh |= commented;
+                    continue;
+                }
+                _ => {}
+            }
+            if fresh {
+                res += &format!("\n{pad}");
+                fresh = false;
+            }
+            commented = false;
+            let col = column(0, &res);
+            res += &self.piece(node.kind, c, level + 1, col, true);
+        }
+        res
+    }
+
+    fn piece(&self, kind: SyntaxKind, c: Child, level: usize, col: usize, wrap: bool) -> String {
+        match c {
+            Child::Node(id) => match self.cst.node(id).kind {
+                SyntaxKind::Block => self.block(id, level, col),
+                SyntaxKind::Error => self.verbatim(id),
+                _ => self.inline(id, level, col, wrap),
+            },
+            Child::Token(t) => {
+                let t = self.token(t);
+                match t.token_type() {
+                    // `a--b` is `a - -b`
+                    TokenType::MinusMinus | TokenType::PlusPlus if kind == SyntaxKind::Binary => {
+                        let op = &t.lexeme()[..1];
+                        format!("{op}{}{op}", self.operator_space())
+                    }
+                    _ => t.lexeme().to_string(),
+                }
+            }
+        }
+    }
+
+    fn operator_space(&self) -> &'static str {
+        if self.options.spaced {
+            " "
+        } else {
+            ""
+        }
+    }
+
+    /// what goes between two parts of a node
+    fn space(&self, kind: SyntaxKind, a: Child, b: Child) -> &'static str {
+        let (ta, tb) = (self.edge(a, true), self.edge(b, false));
+        let (ty_a, ty_b) = (ta.token_type(), tb.token_type());
+        // written apart
+        let apart = ta.pos()[1] < tb.pos()[0];
+        match (ty_a, ty_b) {
+            (_, TokenType::Comma | TokenType::RightParen | TokenType::RightSquare) => "",
+            (TokenType::LeftParen | TokenType::LeftSquare, _) => "",
+            (TokenType::Comma, _) => " ",
+            (TokenType::Equal, _) | (_, TokenType::Equal) => " ",
+            (_, TokenType::LeftParen) if kind == SyntaxKind::FunctionDef => "",
+            _ if kind == SyntaxKind::Unary || kind == SyntaxKind::Call => "",
+            // `x²`
+            (_, TokenType::Caret) if tb.lexeme() != "^" => "",
+            (TokenType::MinusMinus | TokenType::PlusPlus, _)
+                if kind == SyntaxKind::Binary && self.ty(a).is_some() =>
+            {
+                ""
+            }
+            _ if kind == SyntaxKind::Binary
+                && (self.ty(a).is_some_and(is_operator) || self.ty(b).is_some_and(is_operator)) =>
+            {
+                self.operator_space()
+            }
+            _ if apart => " ",
+            _ => "",
+        }
+    }
+}
+
+/// operators between the operands of a chain
+fn is_operator(ty: TokenType) -> bool {
+    matches!(
+        ty,
+        TokenType::Plus
+            | TokenType::Minus
+            | TokenType::Star
+            | TokenType::Slash
+            | TokenType::Percent
+            | TokenType::Caret
+            | TokenType::MinusMinus
+            | TokenType::PlusPlus
+            | TokenType::Less
+            | TokenType::LessEqual
+            | TokenType::Greater
+            | TokenType::GreaterEqual
+            | TokenType::EqualEqual
+            | TokenType::BangEqual
+    )
+}
+
+/// a token running to the end of its line
+fn to_line_end(t: &Token) -> bool {
+    match t.token_type() {
+        TokenType::SingleLineComment => true,
+        TokenType::Error => t.lexeme().starts_with(['"', '\'']),
+        _ => false,
+    }
+}
+
+/// the column after `text` written from the column `col`
+fn column(col: usize, text: &str) -> usize {
+    match text.rfind('\n') {
+        Some(i) => text[i + 1..].chars().count(),
+        None => col + text.chars().count(),
+    }
+}
+
+#[test]
+fn test_format() {
+    use crate::my_parser::LexicalParser;
+    let parse = |text: &str| LexicalParser::new_inline(text.to_string()).parse();
+    let fmt = |text: &str, options: &FormatOptions| format(&parse(text).cst, options);
+    let narrow = FormatOptions {
+        indent: 2,
+        width: 30,
+        spaced: false,
+    };
+
+    assert_eq!(
+        fmt("x=1+2*3;y = x^2\n\n\n\nz=-x--y", &FormatOptions::default()),
+        "x = 1 + 2 * 3; y = x ^ 2\n\nz = -x - -y\n"
+    );
+    assert_eq!(
+        fmt("// head\nf(a,b)=a*b // mul\n{x=5;-2 y + x}2(x+1)", &narrow),
+        "// head\nf(a, b) = a*b // mul\n{ x = 5; -2 y+x }\n2(x+1)\n"
+    );
+    assert_eq!(
+        fmt("{\nx = 1\n  {y=2\n/* c */ z = 3}\n}", &narrow),
+        "{\n  x = 1\n  {\n    y = 2\n    /* c */ z = 3\n  }\n}\n"
+    );
+    assert_eq!(
+        fmt("y = max(aaaaaaaaaa, bbbbbbbbbb, f(c, d))", &narrow),
+        "y = max(\n  aaaaaaaaaa,\n  bbbbbbbbbb,\n  f(c, d)\n)\n"
+    );
+    // nothing follows a `//` comment on its line
+    assert_eq!(
+        fmt("x = 1 // c\n; y = 2\ny", &FormatOptions::default()),
+        "x = 1 // c\n; y = 2\ny\n"
+    );
+    assert_eq!(fmt("x = 2\n/*sin_1\n", &narrow), "x = 2\n/*sin_1\n");
+
+    // the same sentences, and formatting again changes nothing
+    let texts = [
+        "g(x) = x² + 2(x + 1) + x y\na\n+ b\n",
+        "x = ) 2 ;  y=[1\ninclude(\"a.calc\")",
+        "s = \"a<b\"; t = f(1, // one\n 2) - (3,\n4)",
+        "a--b + --c; {{}}; {",
+        "2 ≠ 3\n1 $ 2",
+        "x = 1 // c\n; y = 2\ny",
+        "x = 1 /* a\nb */ y = 2",
+        "{ a // one\n b // two\n",
+        "{ f(1, // one\n 2)",
+        "f(1 // one\n, // two\n /* three */ 2)",
+        "y++ /* a\nb */ 2",
+        "\"s\n; 1",
+        "1 + /*sin_1",
+        "x = 2\n/*sin_1\n\n",
+    ];
+    for text in texts {
+        for options in [FormatOptions::default(), narrow] {
+            let res = fmt(text, &options);
+            let print = |text: &str| {
+                let ss = parse(text).sentences;
+                ss.iter().map(|s| s.print(0)).collect::<String>()
+            };
+            assert_eq!(print(&res), print(text), "{res}");
+            assert_eq!(fmt(&res, &options), res);
+        }
+    }
+}
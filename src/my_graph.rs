@@ -0,0 +1,234 @@
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    fmt,
+};
+
+use crate::my_parser::Sentence;
+
+/// Dependencies between the top-level sentences of an article,
+/// so they can be calculated like spreadsheet cells, in any order.
+///
+/// A sentence reads the last assignment of a variable before it, or the
+/// first one after it when there is none before. Sentences reading `ans`
+/// or `out` depend on every output before them, sentences reading an
+/// unassigned variable depend on every `include`. An assignment comes
+/// after the sentences reading the assignment before it.
+pub struct DepGraph {
+    /// sentences assigning each variable, in the order they are written
+    defs: HashMap<String, Vec<usize>>,
+    /// variables each sentence assigns
+    writes: Vec<HashSet<String>>,
+    /// sentences whose values each sentence reads
+    uses: Vec<BTreeSet<usize>>,
+    /// sentences each sentence comes after, `uses` among them
+    deps: Vec<BTreeSet<usize>>,
+    /// sentences with an output
+    outputs: Vec<bool>,
+    /// sentences reading the outputs before them, which they also come after
+    history: Vec<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DepError {
+    /// the variables forming a loop, the first one repeated at the end
+    Cycle(Vec<String>),
+}
+
+impl fmt::Display for DepError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DepError::Cycle(names) if names.len() <= 2 => {
+                write!(f, "cyclic dependency: {} depends on itself", names[0])
+            }
+            DepError::Cycle(names) => write!(f, "cyclic dependency: {}", names.join(" -> ")),
+        }
+    }
+}
+
+impl DepGraph {
+    pub fn from<'a>(ss: impl IntoIterator<Item = &'a Sentence>) -> Self {
+        let ss: Vec<&Sentence> = ss.into_iter().collect();
+        let mut defs: HashMap<String, Vec<usize>> = HashMap::new();
+        let mut reads = vec![];
+        let mut writes = vec![];
+        for (i, s) in ss.iter().enumerate() {
+            let mut r = HashSet::new();
+            let mut w = HashSet::new();
+            s.reads(&mut r);
+            s.writes(&mut w);
+            for name in &w {
+                defs.entry(name.clone()).or_default().push(i);
+            }
+            reads.push(r);
+            writes.push(w);
+        }
+        let includes: Vec<usize> = (0..ss.len()).filter(|&i| ss[i].includes()).collect();
+        let mut uses = vec![BTreeSet::new(); ss.len()];
+        let mut history = vec![false; ss.len()];
+        // sentences reading each assignment of a variable
+        let mut readers: HashMap<(&str, usize), Vec<usize>> = HashMap::new();
+        for (i, r) in reads.iter().enumerate() {
+            for name in r {
+                if let Some(ds) = defs.get(name) {
+                    let j = match ds.iter().rev().find(|&&j| j < i) {
+                        Some(&j) => j,
+                        None => ds[0],
+                    };
+                    // variables local to a block are read by the block itself
+                    if j != i || !matches!(ss[i], Sentence::Block(_)) {
+                        uses[i].insert(j);
+                        readers.entry((name, j)).or_default().push(i);
+                    }
+                } else {
+                    uses[i].extend(includes.iter().filter(|&&j| j != i));
+                }
+                history[i] |= is_history(name);
+            }
+        }
+        let mut deps = uses.clone();
+        for (name, ds) in &defs {
+            for w in ds.windows(2) {
+                deps[w[1]].insert(w[0]);
+                if let Some(rs) = readers.get(&(name.as_str(), w[0])) {
+                    deps[w[1]].extend(rs.iter().filter(|&&i| i != w[1]));
+                }
+            }
+        }
+        DepGraph {
+            defs,
+            writes,
+            uses,
+            deps,
+            outputs: ss.iter().map(|s| has_output(s)).collect(),
+            history,
+        }
+    }
+
+    /// sentences in an order where each one follows those it depends on,
+    /// otherwise in the order they are written
+    pub fn order(&self) -> Result<Vec<usize>, DepError> {
+        let n = self.deps.len();
+        let mut count: Vec<usize> = self.deps.iter().map(|d| d.len()).collect();
+        let mut users = vec![vec![]; n];
+        for (i, d) in self.deps.iter().enumerate() {
+            for &j in d {
+                users[j].push(i);
+            }
+        }
+        // outputs not calculated yet
+        let mut pending: BTreeSet<usize> = (0..n).filter(|&j| self.outputs[j]).collect();
+        let waits = |i: usize, pending: &BTreeSet<usize>| {
+            self.history[i] && pending.first().is_some_and(|&j| j < i)
+        };
+        let (mut waiting, mut ready): (BTreeSet<usize>, BTreeSet<usize>) = (0..n)
+            .filter(|&i| count[i] == 0)
+            .partition(|&i| waits(i, &pending));
+        let mut res = Vec::with_capacity(n);
+        while let Some(i) = ready.pop_first() {
+            res.push(i);
+            pending.remove(&i);
+            for &u in &users[i] {
+                count[u] -= 1;
+                if count[u] == 0 {
+                    if waits(u, &pending) {
+                        waiting.insert(u);
+                    } else {
+                        ready.insert(u);
+                    }
+                }
+            }
+            // sentences reading only the outputs calculated so far
+            let first = pending.first().copied().unwrap_or(n);
+            while let Some(w) = waiting.pop_first() {
+                if w > first {
+                    waiting.insert(w);
+                    break;
+                }
+                ready.insert(w);
+            }
+        }
+        if res.len() < n {
+            let mut done = vec![false; n];
+            for &i in &res {
+                done[i] = true;
+            }
+            let start = (0..n).find(|&i| !done[i]).unwrap();
+            return Err(DepError::Cycle(self.cycle(start, &done)));
+        }
+        Ok(res)
+    }
+
+    /// every other variable whose value depends on `name`, in calculating order
+    pub fn dependents_of(&self, name: &str) -> Vec<String> {
+        let Some(starts) = self.defs.get(name) else {
+            return vec![];
+        };
+        let mut seen: HashSet<usize> = starts.iter().copied().collect();
+        let mut stack = starts.clone();
+        while let Some(j) = stack.pop() {
+            for (i, u) in self.uses.iter().enumerate() {
+                let history = self.history[i] && self.outputs[j] && j < i;
+                if (u.contains(&j) || history) && seen.insert(i) {
+                    stack.push(i);
+                }
+            }
+        }
+        let order = self
+            .order()
+            .unwrap_or_else(|_| (0..self.deps.len()).collect());
+        let mut res: Vec<String> = vec![];
+        for i in order {
+            if !starts.contains(&i) && seen.contains(&i) {
+                let mut w: Vec<&String> = self.writes[i].iter().collect();
+                w.sort();
+                for n in w {
+                    if n != name && !res.contains(n) {
+                        res.push(n.clone());
+                    }
+                }
+            }
+        }
+        res
+    }
+
+    /// follow unfinished dependencies from `start` until a sentence repeats
+    fn cycle(&self, start: usize, done: &[bool]) -> Vec<String> {
+        let mut path = vec![start];
+        loop {
+            let last = *path.last().unwrap();
+            let next = match self.deps[last].iter().find(|&&j| !done[j]) {
+                Some(&j) => j,
+                // only an output before it is left
+                None => (0..last).find(|&j| self.outputs[j] && !done[j]).unwrap(),
+            };
+            if let Some(p) = path.iter().position(|&i| i == next) {
+                let mut res: Vec<String> = path[p..].iter().map(|&i| self.name_of(i)).collect();
+                res.push(res[0].clone());
+                return res;
+            }
+            path.push(next);
+        }
+    }
+
+    fn name_of(&self, i: usize) -> String {
+        let mut w: Vec<&String> = self.writes[i].iter().collect();
+        w.sort();
+        match w.first() {
+            Some(name) => name.to_string(),
+            None => format!("[sentence {}]", i + 1),
+        }
+    }
+}
+
+/// `ans`, `out` and `_3` read the outputs before them
+pub fn is_history(name: &str) -> bool {
+    name == "ans"
+        || name == "out"
+        || name
+            .strip_prefix('_')
+            .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+}
+
+fn has_output(s: &Sentence) -> bool {
+    matches!(s, Sentence::Expression(_) | Sentence::Block(_))
+}
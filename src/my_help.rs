@@ -0,0 +1,173 @@
+use js_sys::{Array, Object};
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    my_js::set,
+    my_math::{my_builtins::NativeFunction, Fun, Val},
+    my_runtime::System,
+};
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_REFERENCE: &'static str = r#"
+export interface FunctionInfo {
+    name: string;
+    aliases: string[];
+    /** like `log(x), log(b, x)` */
+    signature: string;
+    description: string;
+    /** empty for any argument */
+    domain: string;
+    /** like `sin(pi / 2) = 1` */
+    example: string;
+    minArgs: number;
+    maxArgs: number;
+}
+
+export interface ConstantInfo {
+    name: string;
+    /** formatted by the settings */
+    value: string;
+    description: string;
+}
+
+export interface Reference {
+    functions: FunctionInfo[];
+    constants: ConstantInfo[];
+}
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    /// `{ functions, constants }` as plain objects
+    #[wasm_bindgen(typescript_type = "Reference")]
+    pub type Reference;
+}
+
+/// `help()`, `help(sin)` or `help("pi")`
+pub fn help(sys: &System, args: &[Val]) -> Result<Val, String> {
+    let text = match args {
+        [] => "help(name) tells about a function or a constant, \
+               functions() lists the functions, constants() the constants"
+            .to_string(),
+        [Val::Fun(Fun::System(name)) | Val::Text(name)] => {
+            if let Some(f) = sys.function(name) {
+                function_help(f)
+            } else if let Some(c) = sys.constant_list().iter().find(|c| c.0 == name) {
+                constant_line(sys, *c)
+            } else {
+                return Err(format!("no function or constant named {name}"));
+            }
+        }
+        [f @ Val::Fun(Fun::User(_))] => format!("user function {}", f.text(sys)),
+        _ => return Err("expects a function or a name in quotes".to_string()),
+    };
+    Ok(Val::Text(text))
+}
+
+/// a line for each function
+pub fn functions(sys: &System, _: &[Val]) -> Result<Val, String> {
+    let lines: Vec<_> = sys
+        .function_list()
+        .iter()
+        .map(|f| format!("{}: {}", f.signature, f.doc))
+        .collect();
+    Ok(Val::Text(lines.join("\n")))
+}
+
+/// a line for each constant
+pub fn constants(sys: &System, _: &[Val]) -> Result<Val, String> {
+    let lines: Vec<_> = sys
+        .constant_list()
+        .into_iter()
+        .map(|c| constant_line(sys, c))
+        .collect();
+    Ok(Val::Text(lines.join("\n")))
+}
+
+pub(crate) fn function_help(f: &NativeFunction) -> String {
+    let mut res = format!("{}: {}", f.signature, f.doc);
+    if !f.domain.is_empty() {
+        res += &format!("\ndomain: {}", f.domain);
+    }
+    if !f.example.is_empty() {
+        res += &format!("\nexample: {}", f.example);
+    }
+    if !f.aliases.is_empty() {
+        res += &format!("\naliases: {}", f.aliases.join(", "));
+    }
+    res
+}
+
+pub(crate) fn constant_line(sys: &System, (name, val, doc): (&str, &Val, &str)) -> String {
+    match doc {
+        "" => format!("{name} = {}", val.text(sys)),
+        _ => format!("{name} = {}: {doc}", val.text(sys)),
+    }
+}
+
+/// every function and constant, as the `Reference` of the typescript definitions
+pub fn reference(sys: &System) -> Reference {
+    let functions: Array = sys
+        .function_list()
+        .into_iter()
+        .map(|f| {
+            let res = Object::new();
+            set(&res, "name", f.name.as_str().into());
+            let aliases: Array = f.aliases.iter().map(|a| JsValue::from_str(a)).collect();
+            set(&res, "aliases", aliases.into());
+            set(&res, "signature", f.signature.as_str().into());
+            set(&res, "description", f.doc.as_str().into());
+            set(&res, "domain", f.domain.as_str().into());
+            set(&res, "example", f.example.as_str().into());
+            set(&res, "minArgs", (*f.arity.start() as u32).into());
+            set(&res, "maxArgs", (*f.arity.end() as u32).into());
+            res
+        })
+        .collect();
+    let constants: Array = sys
+        .constant_list()
+        .into_iter()
+        .map(|(name, val, doc)| {
+            let res = Object::new();
+            set(&res, "name", name.into());
+            set(&res, "value", val.text(sys).into());
+            set(&res, "description", doc.into());
+            res
+        })
+        .collect();
+    let res = Object::new();
+    set(&res, "functions", functions.into());
+    set(&res, "constants", constants.into());
+    res.unchecked_into()
+}
+
+#[test]
+fn test_help() {
+    use crate::my_runtime::Runtime;
+    let sys = &Runtime::new().sys;
+    let text = |v: Result<Val, String>| match v {
+        Ok(Val::Text(s)) => s,
+        v => panic!("{v:?}"),
+    };
+    let sin = Val::Fun(Fun::System("arcsin".to_string()));
+    assert_eq!(
+        text(help(sys, &[sin])),
+        "asin(x): inverse sine, in radians\ndomain: -1 ≤ x ≤ 1\n\
+         example: asin(1) = 1.5707963267948966\naliases: arcsin"
+    );
+    let pi = Val::Text("pi".to_string());
+    assert!(text(help(sys, &[pi])).starts_with("pi = 3.141592653589793: ratio"));
+    assert!(help(sys, &[Val::Text("nope".to_string())]).is_err());
+    let list = text(functions(sys, &[]));
+    assert!(list.contains("\nlog(x), log(b, x): natural logarithm"));
+    assert!(!list.contains("arcsin"));
+
+    // every example is what the function writes
+    for f in sys.function_list() {
+        let Some((sentence, output)) = f.example.split_once(" = ") else {
+            continue;
+        };
+        let c = crate::Caculator::new(sentence);
+        assert_eq!(c.get_html(), format!("[out 1] {output}\n"), "{}", f.name);
+    }
+}
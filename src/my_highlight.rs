@@ -0,0 +1,187 @@
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    my_cst::{Child, Cst, SyntaxKind},
+    my_parser::TokenType,
+};
+
+/// what a piece of the text is, for colouring it
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HighlightKind {
+    Number,
+    String,
+    Char,
+    Bool,
+    /// a name that is read or assigned
+    Variable,
+    /// a name that is called or defined as a function
+    Function,
+    /// a name in `f(x, y) =`
+    Parameter,
+    Operator,
+    /// brackets, `,` and `;`
+    Punctuation,
+    Comment,
+    /// malformed tokens and unterminated comments
+    Error,
+}
+
+/// `[start, end)` in characters of the input
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Highlight {
+    pub start: usize,
+    pub end: usize,
+    pub kind: HighlightKind,
+}
+
+impl HighlightKind {
+    /// the class in `highlight.css`
+    pub fn class(self) -> &'static str {
+        match self {
+            HighlightKind::Number => "syntax_number",
+            HighlightKind::String => "syntax_string",
+            HighlightKind::Char => "syntax_char",
+            HighlightKind::Bool => "syntax_bool",
+            HighlightKind::Variable => "syntax_identifier",
+            HighlightKind::Function => "syntax_fun",
+            HighlightKind::Parameter => "syntax_parameter",
+            HighlightKind::Operator => "syntax_operator",
+            HighlightKind::Punctuation => "syntax_punctuation",
+            HighlightKind::Comment => "syntax_comment",
+            HighlightKind::Error => "syntax_error",
+        }
+    }
+}
+
+/// a span for every token but whitespaces, in the order of the text
+pub fn highlight(cst: &Cst) -> Vec<Highlight> {
+    let mut res = vec![];
+    for (i, t) in cst.tokens().iter().enumerate() {
+        let kind = match t.token_type() {
+            TokenType::Whitespace | TokenType::NewLine | TokenType::EOF => continue,
+            _ if t.has_error() => HighlightKind::Error,
+            TokenType::Number => HighlightKind::Number,
+            TokenType::String => HighlightKind::String,
+            TokenType::Char => HighlightKind::Char,
+            TokenType::Bool => HighlightKind::Bool,
+            TokenType::SingleLineComment | TokenType::MuitiLineComment => HighlightKind::Comment,
+            TokenType::Identifier => name_role(cst, i),
+            TokenType::LeftParen
+            | TokenType::RightParen
+            | TokenType::LeftBrace
+            | TokenType::RightBrace
+            | TokenType::LeftSquare
+            | TokenType::RightSquare
+            | TokenType::Comma
+            | TokenType::Semicolon => HighlightKind::Punctuation,
+            _ => HighlightKind::Operator,
+        };
+        let [start, end] = t.pos();
+        res.push(Highlight { start, end, kind });
+    }
+    res
+}
+
+/// a function name is the first token of a call or a definition
+fn name_role(cst: &Cst, token: usize) -> HighlightKind {
+    let node = cst.node(cst.owner(token));
+    let first = node.children.first() == Some(&Child::Token(token));
+    match node.kind {
+        SyntaxKind::Call | SyntaxKind::FunctionDef | SyntaxKind::Include if first => {
+            HighlightKind::Function
+        }
+        SyntaxKind::FunctionDef => {
+            // the parameters come before `=`
+            let eq = node.children.iter().position(|&c| match c {
+                Child::Token(t) => cst.tokens()[t].token_type() == TokenType::Equal,
+                Child::Node(_) => false,
+            });
+            let at = node.children.iter().position(|&c| c == Child::Token(token));
+            if at < eq {
+                HighlightKind::Parameter
+            } else {
+                HighlightKind::Variable
+            }
+        }
+        _ => HighlightKind::Variable,
+    }
+}
+
+/// `text` with each span in a `<span class=...>`, everything escaped
+pub fn to_html(text: &str, spans: &[Highlight]) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let piece = |a: usize, b: usize| escape_html(&chars[a.min(b)..b].iter().collect::<String>());
+    let mut res = String::new();
+    let mut at = 0;
+    for h in spans {
+        let end = h.end.min(chars.len());
+        res += &piece(at, h.start.min(end));
+        res += &format!(
+            "<span class='{}'>{}</span>",
+            h.kind.class(),
+            piece(h.start, end)
+        );
+        at = end;
+    }
+    res + &piece(at, chars.len())
+}
+
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\'', "&#39;")
+        .replace('"', "&quot;")
+}
+
+#[test]
+fn test_highlight() {
+    use crate::my_parser::LexicalParser;
+    let cst = |text: &str| LexicalParser::new_inline(text.to_string()).parse().cst;
+
+    let text = "f(x) = sin(x) + 2 // c\ns = \"<b>\"; 1 $";
+    let kinds: Vec<_> = highlight(&cst(text))
+        .iter()
+        .map(|h| {
+            (
+                text.chars()
+                    .skip(h.start)
+                    .take(h.end - h.start)
+                    .collect::<String>(),
+                h.kind,
+            )
+        })
+        .collect();
+    use HighlightKind as K;
+    let expected = [
+        ("f", K::Function),
+        ("(", K::Punctuation),
+        ("x", K::Parameter),
+        (")", K::Punctuation),
+        ("=", K::Operator),
+        ("sin", K::Function),
+        ("(", K::Punctuation),
+        ("x", K::Variable),
+        (")", K::Punctuation),
+        ("+", K::Operator),
+        ("2", K::Number),
+        ("// c", K::Comment),
+        ("s", K::Variable),
+        ("=", K::Operator),
+        ("\"<b>\"", K::String),
+        (";", K::Punctuation),
+        ("1", K::Number),
+        ("$", K::Error),
+    ];
+    let expected: Vec<_> = expected.iter().map(|(s, k)| (s.to_string(), *k)).collect();
+    assert_eq!(kinds, expected);
+
+    let html = to_html("s = \"<b>\"", &highlight(&cst("s = \"<b>\"")));
+    assert_eq!(
+        html,
+        "<span class='syntax_identifier'>s</span> <span class='syntax_operator'>=</span> \
+         <span class='syntax_string'>&quot;&lt;b&gt;&quot;</span>"
+    );
+}
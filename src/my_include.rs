@@ -0,0 +1,290 @@
+use std::{collections::HashMap, fmt, rc::Rc};
+
+use wasm_bindgen::JsValue;
+
+use crate::{
+    my_math::{Tuple, Val},
+    my_parser::LexicalParser,
+    my_runtime::Runtime,
+};
+
+/// files including each other deeper than this are not read
+const MAX_DEPTH: usize = 64;
+
+/// Finds and reads the files of `include("file")`.
+pub trait IncludeResolver {
+    /// the name of `path` included from the file `from`, `None` for the main input.
+    /// a file is always given the same name, so cycles can be found
+    fn resolve(&self, path: &str, from: Option<&str>) -> Result<String, String>;
+    /// the text of a file named by `resolve`
+    fn load(&self, name: &str) -> Result<String, String>;
+}
+
+/// files on the disk, relative paths start from the folder of the including file,
+/// or from the working directory for the main input
+#[cfg(not(target_arch = "wasm32"))]
+pub struct FsResolver;
+
+#[cfg(not(target_arch = "wasm32"))]
+impl IncludeResolver for FsResolver {
+    fn resolve(&self, path: &str, from: Option<&str>) -> Result<String, String> {
+        let base = from
+            .and_then(|f| std::path::Path::new(f).parent())
+            .unwrap_or(std::path::Path::new(""));
+        base.join(path)
+            .canonicalize()
+            .map(|p| p.to_string_lossy().into_owned())
+            .map_err(|e| format!("cannot find {path}: {e}"))
+    }
+    fn load(&self, name: &str) -> Result<String, String> {
+        std::fs::read_to_string(name).map_err(|e| format!("cannot read {name}: {e}"))
+    }
+}
+
+/// files kept in memory by their `/` separated names
+#[derive(Default)]
+pub struct MemoryResolver {
+    files: HashMap<String, String>,
+}
+
+impl MemoryResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn insert(&mut self, name: &str, text: &str) {
+        self.files.insert(join(name, None), text.to_string());
+    }
+}
+
+impl IncludeResolver for MemoryResolver {
+    fn resolve(&self, path: &str, from: Option<&str>) -> Result<String, String> {
+        let name = join(path, from);
+        if self.files.contains_key(&name) {
+            Ok(name)
+        } else {
+            Err(format!("cannot find {path}"))
+        }
+    }
+    fn load(&self, name: &str) -> Result<String, String> {
+        self.files
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("cannot find {name}"))
+    }
+}
+
+/// files given by a js function `(name) => string | undefined`,
+/// names are `/` separated like `MemoryResolver`
+pub struct JsResolver(pub js_sys::Function);
+
+impl IncludeResolver for JsResolver {
+    fn resolve(&self, path: &str, from: Option<&str>) -> Result<String, String> {
+        Ok(join(path, from))
+    }
+    fn load(&self, name: &str) -> Result<String, String> {
+        match self.0.call1(&JsValue::NULL, &JsValue::from_str(name)) {
+            Ok(text) => text
+                .as_string()
+                .ok_or_else(|| format!("cannot find {name}")),
+            Err(e) => Err(format!("cannot read {name}: {e:?}")),
+        }
+    }
+}
+
+/// `path` relative to the folder of `from`, without `.` and `..`
+pub fn join(path: &str, from: Option<&str>) -> String {
+    let mut parts = vec![];
+    let mut absolute = path.starts_with('/');
+    if let (false, Some(from)) = (absolute, from) {
+        absolute = from.starts_with('/');
+        parts.extend(from.split('/').filter(|p| !p.is_empty()));
+        // the file name
+        parts.pop();
+    }
+    for p in path.split('/') {
+        match p {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            p => parts.push(p),
+        }
+    }
+    let res = parts.join("/");
+    if absolute {
+        "/".to_string() + &res
+    } else {
+        res
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IncludeError {
+    /// `None` for the main input
+    pub file: Option<String>,
+    /// [line, column], both counted from 0
+    pub line_colum: [usize; 2],
+    pub message: String,
+}
+
+impl fmt::Display for IncludeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {}",
+            self.file.as_deref().unwrap_or("<input>"),
+            self.line_colum[0] + 1,
+            self.line_colum[1] + 1,
+            self.message
+        )
+    }
+}
+
+/// state of `include` kept by the runtime
+pub struct Includes {
+    resolver: Rc<dyn IncludeResolver>,
+    /// name of the main input, its includes start from its folder
+    pub main: Option<String>,
+    /// files being calculated, the innermost last
+    stack: Vec<String>,
+    errors: Vec<IncludeError>,
+}
+
+impl Includes {
+    pub fn new() -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        let resolver: Rc<dyn IncludeResolver> = Rc::new(FsResolver);
+        #[cfg(target_arch = "wasm32")]
+        let resolver: Rc<dyn IncludeResolver> = Rc::new(MemoryResolver::new());
+        Includes {
+            resolver,
+            main: None,
+            stack: vec![],
+            errors: vec![],
+        }
+    }
+    pub fn set_resolver(&mut self, resolver: impl IncludeResolver + 'static) {
+        self.resolver = Rc::new(resolver);
+    }
+    /// errors of every include since the last `clear_errors`
+    pub fn errors(&self) -> &[IncludeError] {
+        &self.errors
+    }
+    pub fn clear_errors(&mut self) {
+        self.errors.clear();
+    }
+    /// forget unfinished includes and errors
+    pub fn restart(&mut self) {
+        self.stack.clear();
+        self.errors.clear();
+    }
+
+    /// the file being calculated
+    fn current(&self) -> Option<&String> {
+        self.stack.last().or(self.main.as_ref())
+    }
+    fn error(&mut self, file: Option<String>, line_colum: [usize; 2], message: String) {
+        self.errors.push(IncludeError {
+            file,
+            line_colum,
+            message,
+        });
+    }
+}
+
+impl Default for Includes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// calculate the file `path` in the current env, `at` is the place of `include`.
+/// returns its outputs, errors are kept in `rt.include`
+pub fn include(rt: &mut Runtime, path: &str, at: [usize; 2]) -> Val {
+    let inc = &mut rt.include;
+    let from = inc.current().cloned();
+    let resolver = inc.resolver.clone();
+    let name = match resolver.resolve(path, from.as_deref()) {
+        Ok(name) => name,
+        Err(e) => {
+            inc.error(from, at, e);
+            return Val::NONE;
+        }
+    };
+    let files: Vec<&String> = inc.main.iter().chain(inc.stack.iter()).collect();
+    if let Some(i) = files.iter().position(|&f| *f == name) {
+        let mut cycle: Vec<&str> = files[i..].iter().map(|f| f.as_str()).collect();
+        cycle.push(&name);
+        let e = format!("include cycle: {}", cycle.join(" -> "));
+        inc.error(from, at, e);
+        return Val::NONE;
+    }
+    if inc.stack.len() >= MAX_DEPTH {
+        inc.error(from, at, "includes nested too deep".to_string());
+        return Val::NONE;
+    }
+    let text = match resolver.load(&name) {
+        Ok(text) => text,
+        Err(e) => {
+            inc.error(from, at, e);
+            return Val::NONE;
+        }
+    };
+
+    let (article, diagnostics) = LexicalParser::new_inline(text).parse_with_diagnostics();
+    for d in diagnostics {
+        inc.error(Some(name.clone()), d.line_colum, d.message);
+    }
+    inc.stack.push(name);
+    let res = article.sentences.iter().map(|s| s.calc(rt)).collect();
+    rt.include.stack.pop();
+    Val::Vars(Tuple(res))
+}
+
+#[test]
+fn test_include() {
+    assert_eq!(join("b.calc", Some("lib/a.calc")), "lib/b.calc");
+    assert_eq!(join("../c.calc", Some("/lib/a.calc")), "/c.calc");
+    assert_eq!(join("./x/./y.calc", None), "x/y.calc");
+
+    let mut files = MemoryResolver::new();
+    files.insert("lib/consts.calc", "g = 9.8\nsq(x) = x * x");
+    files.insert(
+        "lib/phys.calc",
+        "include(\"consts.calc\")\nfall(t) = g * sq(t) / 2",
+    );
+    files.insert("a.calc", "include(\"b.calc\")");
+    files.insert("b.calc", "1\ninclude(\"a.calc\")");
+    files.insert("bad.calc", "x = 1\n  ) 2");
+    let mut rt = Runtime::new();
+    rt.include.set_resolver(files);
+    let calc = |rt: &mut Runtime, input: &str| {
+        let res = LexicalParser::new_inline(input.to_string())
+            .parse()
+            .calc(rt)
+            .leaves();
+        let errors = rt.include.errors().iter().map(|e| e.to_string()).collect();
+        rt.include.clear_errors();
+        (res, errors)
+    };
+
+    let (res, errors): (_, Vec<String>) = calc(&mut rt, "include(\"lib/phys.calc\")\nfall(2)");
+    assert!(errors.is_empty());
+    assert!(matches!(&res[..], [Val::Re(r)] if r.0 == 19.6));
+
+    let (res, errors) = calc(&mut rt, "x = 0\n  include(\"a.calc\")");
+    assert_eq!(res.len(), 1);
+    assert_eq!(
+        errors,
+        ["b.calc:2:1: include cycle: a.calc -> b.calc -> a.calc"]
+    );
+
+    let (_, errors) = calc(&mut rt, "include(\"bad.calc\")\ninclude(\"none.calc\")");
+    assert_eq!(
+        errors,
+        [
+            "bad.calc:2:3: expected an operand, found `)`",
+            "<input>:2:1: cannot find none.calc"
+        ]
+    );
+}
@@ -0,0 +1,90 @@
+use js_sys::{Array, Float64Array, Function, Object, Reflect};
+use wasm_bindgen::{JsCast, JsValue};
+
+use crate::my_math::{my_builtins::NativeFunction, Im, Re, Tuple, Val};
+
+/// arrays deeper than this are not read
+const MAX_NESTING: usize = 64;
+
+/// numbers are reals, `{ re, im }` complex numbers, strings texts,
+/// arrays and `Float64Array`s tuples, nested arrays nested tuples
+pub fn val_from_js(value: &JsValue) -> Result<Val, String> {
+    from_js(value, 0)
+}
+
+fn from_js(value: &JsValue, depth: usize) -> Result<Val, String> {
+    if depth > MAX_NESTING {
+        return Err("arrays nested too deep".to_string());
+    }
+    if let Some(v) = value.as_f64() {
+        return Ok(Val::Re(Re(v)));
+    }
+    if let Some(s) = value.as_string() {
+        return Ok(Val::Text(s));
+    }
+    if let Some(v) = value.dyn_ref::<Float64Array>() {
+        return Ok(Val::Vars(Tuple(
+            v.to_vec().into_iter().map(|v| Val::Re(Re(v))).collect(),
+        )));
+    }
+    if let Some(v) = value.dyn_ref::<Array>() {
+        let v = v.iter().map(|v| from_js(&v, depth + 1));
+        return Ok(Val::Vars(Tuple(v.collect::<Result<_, _>>()?)));
+    }
+    if value.is_object() {
+        let part = |key: &str| Reflect::get(value, &key.into()).ok()?.as_f64();
+        if let (Some(u), Some(v)) = (part("re"), part("im")) {
+            return Ok(Val::Im(Im(u, v)));
+        }
+    }
+    Err(format!(
+        "expected a number, a string, an array or {{ re, im }}, found {}",
+        value.js_typeof().as_string().unwrap_or_default()
+    ))
+}
+
+/// the other way of `val_from_js`, functions are `undefined`
+pub fn val_to_js(val: &Val) -> JsValue {
+    match val {
+        Val::Re(Re(v)) => JsValue::from(*v),
+        Val::Im(Im(u, v)) => {
+            let res = Object::new();
+            set(&res, "re", JsValue::from(*u));
+            set(&res, "im", JsValue::from(*v));
+            res.into()
+        }
+        Val::Vars(Tuple(v)) => v.iter().map(val_to_js).collect::<Array>().into(),
+        Val::Text(s) | Val::Svg(s) => JsValue::from_str(s),
+        Val::Fun(_) => JsValue::UNDEFINED,
+    }
+}
+
+/// `f` called with its arguments as `val_to_js` writes them,
+/// its result read by `val_from_js`, what it throws is the error
+pub fn js_function(name: &str, arity: usize, f: Function) -> NativeFunction {
+    NativeFunction::new(name, arity..=arity, move |_, args| {
+        let args: Array = args.iter().map(val_to_js).collect();
+        match f.apply(&JsValue::NULL, &args) {
+            Ok(res) => val_from_js(&res),
+            Err(e) => Err(thrown(&e)),
+        }
+    })
+}
+
+fn thrown(e: &JsValue) -> String {
+    if let Some(e) = e.dyn_ref::<js_sys::Error>() {
+        return e.message().into();
+    }
+    e.as_string()
+        .unwrap_or_else(|| "threw an exception".to_string())
+}
+
+pub fn set(obj: &Object, key: &str, value: JsValue) {
+    // setting a property of a plain object never fails
+    let _ = Reflect::set(obj, &key.into(), &value);
+}
+
+/// `[start, end]` as a js array
+pub fn span_to_js([start, end]: [usize; 2]) -> JsValue {
+    Array::of2(&(start as u32).into(), &(end as u32).into()).into()
+}
@@ -0,0 +1,255 @@
+use std::fmt::{self, Write};
+
+/// a small JSON tree, enough for saving states and talking to editors
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Num(f64),
+    Str(String),
+    Arr(Vec<Json>),
+    /// keeps the order of keys
+    Obj(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn parse(text: &str) -> Result<Json, String> {
+        let cs: Vec<char> = text.chars().collect();
+        let mut p = JsonParser { cs: &cs, pos: 0 };
+        let res = p.value()?;
+        p.skip();
+        if p.pos < cs.len() {
+            return Err(p.error("end of input"));
+        }
+        Ok(res)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Obj(kv) => kv.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Json::Num(n) => Some(*n),
+            _ => None,
+        }
+    }
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Json::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+    pub fn as_arr(&self) -> Option<&[Json]> {
+        match self {
+            Json::Arr(a) => Some(a),
+            _ => None,
+        }
+    }
+    pub fn as_obj(&self) -> Option<&[(String, Json)]> {
+        match self {
+            Json::Obj(kv) => Some(kv),
+            _ => None,
+        }
+    }
+
+    /// `Json::obj([("a", Json::Num(1.0))])`
+    pub fn obj<const N: usize>(kv: [(&str, Json); N]) -> Json {
+        Json::Obj(kv.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
+    }
+    pub fn str(s: &str) -> Json {
+        Json::Str(s.to_string())
+    }
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => f.write_str("null"),
+            Json::Bool(b) => write!(f, "{b}"),
+            // JSON has no NaN or infinity
+            Json::Num(n) if !n.is_finite() => f.write_str("null"),
+            Json::Num(n) => write!(f, "{n}"),
+            Json::Str(s) => write_str(f, s),
+            Json::Arr(a) => {
+                f.write_char('[')?;
+                for (i, v) in a.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write!(f, "{v}")?;
+                }
+                f.write_char(']')
+            }
+            Json::Obj(kv) => {
+                f.write_char('{')?;
+                for (i, (k, v)) in kv.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write_str(f, k)?;
+                    write!(f, ":{v}")?;
+                }
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+fn write_str(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+struct JsonParser<'a> {
+    cs: &'a [char],
+    pos: usize,
+}
+
+impl JsonParser<'_> {
+    fn error(&self, expected: &str) -> String {
+        format!("json: expected {expected} at {}", self.pos)
+    }
+    fn skip(&mut self) {
+        while self.cs.get(self.pos).is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+    fn eat(&mut self, c: char) -> bool {
+        self.skip();
+        if self.cs.get(self.pos) == Some(&c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+    fn word(&mut self, w: &str, v: Json) -> Result<Json, String> {
+        for c in w.chars() {
+            if self.cs.get(self.pos) != Some(&c) {
+                return Err(self.error(w));
+            }
+            self.pos += 1;
+        }
+        Ok(v)
+    }
+
+    fn value(&mut self) -> Result<Json, String> {
+        self.skip();
+        match self.cs.get(self.pos) {
+            Some('n') => self.word("null", Json::Null),
+            Some('t') => self.word("true", Json::Bool(true)),
+            Some('f') => self.word("false", Json::Bool(false)),
+            Some('"') => Ok(Json::Str(self.string()?)),
+            Some('[') => {
+                self.pos += 1;
+                let mut res = vec![];
+                if self.eat(']') {
+                    return Ok(Json::Arr(res));
+                }
+                loop {
+                    res.push(self.value()?);
+                    if self.eat(']') {
+                        return Ok(Json::Arr(res));
+                    }
+                    if !self.eat(',') {
+                        return Err(self.error("',' or ']'"));
+                    }
+                }
+            }
+            Some('{') => {
+                self.pos += 1;
+                let mut res = vec![];
+                if self.eat('}') {
+                    return Ok(Json::Obj(res));
+                }
+                loop {
+                    self.skip();
+                    let k = self.string()?;
+                    if !self.eat(':') {
+                        return Err(self.error("':'"));
+                    }
+                    res.push((k, self.value()?));
+                    if self.eat('}') {
+                        return Ok(Json::Obj(res));
+                    }
+                    if !self.eat(',') {
+                        return Err(self.error("',' or '}'"));
+                    }
+                }
+            }
+            Some(c) if *c == '-' || c.is_ascii_digit() => {
+                let start = self.pos;
+                self.pos += 1;
+                while self
+                    .cs
+                    .get(self.pos)
+                    .is_some_and(|c| c.is_ascii_digit() || ".eE+-".contains(*c))
+                {
+                    self.pos += 1;
+                }
+                let s: String = self.cs[start..self.pos].iter().collect();
+                s.parse().map(Json::Num).map_err(|_| self.error("number"))
+            }
+            _ => Err(self.error("value")),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        if self.cs.get(self.pos) != Some(&'"') {
+            return Err(self.error("string"));
+        }
+        self.pos += 1;
+        let mut res = String::new();
+        loop {
+            let c = *self.cs.get(self.pos).ok_or_else(|| self.error("'\"'"))?;
+            self.pos += 1;
+            match c {
+                '"' => return Ok(res),
+                '\\' => {
+                    let c = *self.cs.get(self.pos).ok_or_else(|| self.error("escape"))?;
+                    self.pos += 1;
+                    res.push(match c {
+                        'n' => '\n',
+                        'r' => '\r',
+                        't' => '\t',
+                        'b' => '\u{8}',
+                        'f' => '\u{c}',
+                        'u' => {
+                            let hex: String = self
+                                .cs
+                                .get(self.pos..self.pos + 4)
+                                .unwrap_or(&[])
+                                .iter()
+                                .collect();
+                            self.pos += 4;
+                            let n = u32::from_str_radix(&hex, 16).map_err(|_| self.error("hex"))?;
+                            char::from_u32(n).unwrap_or('\u{fffd}')
+                        }
+                        c => c,
+                    });
+                }
+                c => res.push(c),
+            }
+        }
+    }
+}
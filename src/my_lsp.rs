@@ -0,0 +1,739 @@
+use std::collections::HashMap;
+
+use crate::{
+    my_complete::{self, CompletionKind},
+    my_cst::{Child, Cst, SyntaxKind},
+    my_help,
+    my_highlight::{self, HighlightKind},
+    my_json::Json,
+    my_math::{Fun, Val},
+    my_parser::TokenType,
+    Caculator,
+};
+
+/// kinds of semantic tokens, indexed by `token_type`
+const TOKEN_TYPES: [&str; 8] = [
+    "number",
+    "string",
+    "keyword",
+    "variable",
+    "function",
+    "parameter",
+    "operator",
+    "comment",
+];
+
+/// JSON-RPC error codes
+const PARSE_ERROR: f64 = -32700.0;
+const INVALID_REQUEST: f64 = -32600.0;
+const METHOD_NOT_FOUND: f64 = -32601.0;
+
+/// A language server for calculator scripts, one JSON-RPC message at a time:
+///
+/// * diagnostics of malformed sentences, `include` errors and failed calls
+/// * semantic tokens, classified like `highlight`
+/// * hover with the values of variables and what builtins are
+/// * completion, go-to-definition of assigned names, and the outputs
+///   of each sentence as inlay hints
+///
+/// Documents are synchronized whole, positions count UTF-16 units.
+pub struct LanguageServer {
+    docs: HashMap<String, Document>,
+    shutdown: bool,
+    /// set by `exit`: 0 after `shutdown`, else 1
+    exit: Option<i32>,
+}
+
+/// an open file, calculated on each change
+struct Document {
+    calc: Caculator,
+    lines: LineIndex,
+}
+
+impl LanguageServer {
+    pub fn new() -> Self {
+        LanguageServer {
+            docs: HashMap::new(),
+            shutdown: false,
+            exit: None,
+        }
+    }
+
+    /// the exit code once the client sent `exit`
+    pub fn exit_code(&self) -> Option<i32> {
+        self.exit
+    }
+
+    /// the responses and notifications for a message from the client
+    pub fn handle(&mut self, message: &str) -> Vec<String> {
+        let msg = match Json::parse(message) {
+            Ok(msg) => msg,
+            Err(e) => return vec![error(Json::Null, PARSE_ERROR, &e)],
+        };
+        let params = msg.get("params").cloned().unwrap_or(Json::Null);
+        let Some(method) = msg.get("method").and_then(|m| m.as_str()) else {
+            // responses to requests of the server, which sends none
+            return match msg.get("id") {
+                Some(_) if msg.get("result").is_some() || msg.get("error").is_some() => vec![],
+                id => vec![error(
+                    id.cloned().unwrap_or(Json::Null),
+                    INVALID_REQUEST,
+                    "no method",
+                )],
+            };
+        };
+        match msg.get("id") {
+            Some(id) => vec![match self.request(method, &params) {
+                Some(result) => response(id.clone(), result),
+                None => error(
+                    id.clone(),
+                    METHOD_NOT_FOUND,
+                    &format!("unknown method: {method}"),
+                ),
+            }],
+            None => self.notify(method, &params),
+        }
+    }
+
+    /// the result of a request, `None` for unknown methods
+    fn request(&mut self, method: &str, params: &Json) -> Option<Json> {
+        let res = match method {
+            "initialize" => capabilities(),
+            "shutdown" => {
+                self.shutdown = true;
+                Json::Null
+            }
+            "textDocument/semanticTokens/full" => match self.doc(params) {
+                Some(doc) => Json::obj([("data", semantic_tokens(doc))]),
+                None => Json::Null,
+            },
+            "textDocument/hover" => self.at(params, hover).unwrap_or(Json::Null),
+            "textDocument/completion" => self.at(params, completion).unwrap_or(Json::Null),
+            "textDocument/definition" => self.at(params, definition).unwrap_or(Json::Null),
+            "textDocument/inlayHint" => match self.doc(params) {
+                Some(doc) => {
+                    let range = params.get("range");
+                    let line = |key| {
+                        let pos = range.and_then(|r| r.get(key))?;
+                        Some(pos.get("line")?.as_f64()? as usize)
+                    };
+                    let lines = line("start").unwrap_or(0)..=line("end").unwrap_or(usize::MAX);
+                    inlay_hints(doc, lines)
+                }
+                None => Json::Null,
+            },
+            _ => return None,
+        };
+        Some(res)
+    }
+
+    /// the notifications to send for a notification
+    fn notify(&mut self, method: &str, params: &Json) -> Vec<String> {
+        let doc = params.get("textDocument");
+        let Some(uri) = doc.and_then(|d| d.get("uri")).and_then(|u| u.as_str()) else {
+            if method == "exit" {
+                self.exit = Some(if self.shutdown { 0 } else { 1 });
+            }
+            return vec![];
+        };
+        let text = match method {
+            "textDocument/didOpen" => doc.and_then(|d| d.get("text")),
+            // full sync, the last change is the whole text
+            "textDocument/didChange" => params
+                .get("contentChanges")
+                .and_then(|c| c.as_arr())
+                .and_then(|c| c.last())
+                .and_then(|c| c.get("text")),
+            "textDocument/didClose" => {
+                self.docs.remove(uri);
+                return vec![publish(uri, vec![])];
+            }
+            _ => return vec![],
+        };
+        let Some(text) = text.and_then(|t| t.as_str()) else {
+            return vec![];
+        };
+        let doc = self.docs.entry(uri.to_string()).or_insert_with(|| {
+            let mut calc = Caculator::new("");
+            if let Some(path) = uri_path(uri) {
+                calc.set_file(&path);
+            }
+            Document {
+                calc,
+                lines: LineIndex::new(""),
+            }
+        });
+        doc.calc.update(text);
+        doc.lines = LineIndex::new(text);
+        vec![publish(uri, diagnostics(doc))]
+    }
+
+    fn doc(&self, params: &Json) -> Option<&Document> {
+        let uri = params.get("textDocument")?.get("uri")?.as_str()?;
+        self.docs.get(uri)
+    }
+
+    /// `f` of the document at the position of `params`
+    fn at(&self, params: &Json, f: fn(&Document, &str, usize) -> Json) -> Option<Json> {
+        let doc = self.doc(params)?;
+        let uri = params.get("textDocument")?.get("uri")?.as_str()?;
+        let pos = params.get("position")?;
+        let line = pos.get("line")?.as_f64()? as usize;
+        let character = pos.get("character")?.as_f64()? as usize;
+        Some(f(doc, uri, doc.lines.offset(line, character)))
+    }
+}
+
+impl Default for LanguageServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn response(id: Json, result: Json) -> String {
+    Json::obj([
+        ("jsonrpc", Json::str("2.0")),
+        ("id", id),
+        ("result", result),
+    ])
+    .to_string()
+}
+
+fn error(id: Json, code: f64, message: &str) -> String {
+    let error = Json::obj([("code", Json::Num(code)), ("message", Json::str(message))]);
+    Json::obj([("jsonrpc", Json::str("2.0")), ("id", id), ("error", error)]).to_string()
+}
+
+fn publish(uri: &str, diagnostics: Vec<Json>) -> String {
+    let params = Json::obj([
+        ("uri", Json::str(uri)),
+        ("diagnostics", Json::Arr(diagnostics)),
+    ]);
+    Json::obj([
+        ("jsonrpc", Json::str("2.0")),
+        ("method", Json::str("textDocument/publishDiagnostics")),
+        ("params", params),
+    ])
+    .to_string()
+}
+
+fn capabilities() -> Json {
+    let legend = Json::obj([
+        (
+            "tokenTypes",
+            Json::Arr(TOKEN_TYPES.iter().map(|t| Json::str(t)).collect()),
+        ),
+        ("tokenModifiers", Json::Arr(vec![])),
+    ]);
+    Json::obj([
+        (
+            "capabilities",
+            Json::obj([
+                // full text on each change
+                ("textDocumentSync", Json::Num(1.0)),
+                ("hoverProvider", Json::Bool(true)),
+                ("completionProvider", Json::obj([])),
+                ("definitionProvider", Json::Bool(true)),
+                ("inlayHintProvider", Json::Bool(true)),
+                (
+                    "semanticTokensProvider",
+                    Json::obj([("legend", legend), ("full", Json::Bool(true))]),
+                ),
+            ]),
+        ),
+        (
+            "serverInfo",
+            Json::obj([
+                ("name", Json::str("caculator-lsp")),
+                ("version", Json::str(env!("CARGO_PKG_VERSION"))),
+            ]),
+        ),
+    ])
+}
+
+/// the path of a `file://` uri
+fn uri_path(uri: &str) -> Option<String> {
+    let path = uri.strip_prefix("file://")?;
+    let bytes = path.as_bytes();
+    let mut res = vec![];
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok());
+        match hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+            Some(b) if bytes[i] == b'%' => {
+                res.push(b);
+                i += 3;
+            }
+            _ => {
+                res.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    let path = String::from_utf8(res).ok()?;
+    // `/C:/dir` on windows
+    match path.as_bytes() {
+        [b'/', _, b':', ..] => Some(path[1..].to_string()),
+        _ => Some(path),
+    }
+}
+
+/// lines of a text, to convert character offsets to LSP positions
+struct LineIndex {
+    chars: Vec<char>,
+    /// offset of the first character of each line
+    starts: Vec<usize>,
+}
+
+impl LineIndex {
+    fn new(text: &str) -> Self {
+        let chars: Vec<char> = text.chars().collect();
+        let mut starts = vec![0];
+        starts.extend(
+            (0..chars.len())
+                .filter(|&i| chars[i] == '\n')
+                .map(|i| i + 1),
+        );
+        LineIndex { chars, starts }
+    }
+
+    /// line of the character at `pos`, counted from 0
+    fn line(&self, pos: usize) -> usize {
+        self.starts.partition_point(|&s| s <= pos) - 1
+    }
+
+    /// `{ line, character }` of the character offset `pos`
+    fn position(&self, pos: usize) -> Json {
+        let pos = pos.min(self.chars.len());
+        let line = self.line(pos);
+        let character: usize = self.chars[self.starts[line]..pos]
+            .iter()
+            .map(|c| c.len_utf16())
+            .sum();
+        Json::obj([
+            ("line", Json::Num(line as f64)),
+            ("character", Json::Num(character as f64)),
+        ])
+    }
+
+    fn range(&self, [start, end]: [usize; 2]) -> Json {
+        Json::obj([("start", self.position(start)), ("end", self.position(end))])
+    }
+
+    /// the character offset of an LSP position, clamped to its line
+    fn offset(&self, line: usize, character: usize) -> usize {
+        let Some(&start) = self.starts.get(line) else {
+            return self.chars.len();
+        };
+        let mut units = 0;
+        let mut pos = start;
+        while pos < self.chars.len() && self.chars[pos] != '\n' && units < character {
+            units += self.chars[pos].len_utf16();
+            pos += 1;
+        }
+        pos
+    }
+
+    /// `[start, end]` split at line breaks, without them
+    fn split(&self, [start, end]: [usize; 2]) -> Vec<[usize; 2]> {
+        let mut res = vec![];
+        let mut start = start;
+        while start < end {
+            let line_end = self
+                .starts
+                .get(self.line(start) + 1)
+                .map_or(end, |&s| s - 1);
+            let stop = end.min(line_end);
+            if stop > start {
+                res.push([start, stop]);
+            }
+            start = stop + 1;
+        }
+        res
+    }
+
+    /// utf-16 length of `[start, end]`
+    fn len(&self, [start, end]: [usize; 2]) -> usize {
+        self.chars[start..end].iter().map(|c| c.len_utf16()).sum()
+    }
+}
+
+fn diagnostic(range: Json, message: &str) -> Json {
+    Json::obj([
+        ("range", range),
+        // error
+        ("severity", Json::Num(1.0)),
+        ("source", Json::str("caculator")),
+        ("message", Json::str(message)),
+    ])
+}
+
+/// malformed sentences, `include` errors and failed calls of the last calculation
+fn diagnostics(doc: &Document) -> Vec<Json> {
+    let (calc, lines) = (&doc.calc, &doc.lines);
+    let cst = &calc.a.cst;
+    let mut res: Vec<Json> = calc
+        .diagnostics
+        .iter()
+        .map(|d| diagnostic(lines.range(d.span), &d.message))
+        .collect();
+    let main = calc.rt.include.main.as_deref();
+    // errors in included files are shown on the first `include`
+    let include = cst
+        .nodes()
+        .iter()
+        .find(|n| n.kind == SyntaxKind::Include)
+        .map_or([0, 0], |n| n.span);
+    for e in calc.rt.include.errors() {
+        let d = match e.file.as_deref() {
+            None => diagnostic(at_line_colum(lines, e.line_colum), &e.message),
+            Some(file) if Some(file) == main => {
+                diagnostic(at_line_colum(lines, e.line_colum), &e.message)
+            }
+            Some(_) => diagnostic(lines.range(include), &e.to_string()),
+        };
+        res.push(d);
+    }
+    // failed calls and unknown names on every use of the name
+    for e in calc.rt.call_errors() {
+        let uses: Vec<[usize; 2]> = cst
+            .tokens()
+            .iter()
+            .filter(|t| t.token_type() == TokenType::Identifier && t.lexeme() == e.name)
+            .map(|t| t.pos())
+            .collect();
+        let uses = if uses.is_empty() { vec![[0, 0]] } else { uses };
+        for span in uses {
+            res.push(diagnostic(lines.range(span), &e.to_string()));
+        }
+    }
+    res
+}
+
+/// the range of a character at `[line, column]`
+fn at_line_colum(lines: &LineIndex, [line, colum]: [usize; 2]) -> Json {
+    let start = lines
+        .starts
+        .get(line)
+        .map_or(lines.chars.len(), |s| s + colum);
+    lines.range([start, start])
+}
+
+/// `[line, start, length, type, 0]` of each token, relative to the one before
+fn semantic_tokens(doc: &Document) -> Json {
+    let lines = &doc.lines;
+    let mut data = vec![];
+    let (mut last_line, mut last_start) = (0, 0);
+    for h in my_highlight::highlight(&doc.calc.a.cst) {
+        let ty = match h.kind {
+            HighlightKind::Number => 0,
+            HighlightKind::String | HighlightKind::Char => 1,
+            HighlightKind::Bool => 2,
+            HighlightKind::Variable => 3,
+            HighlightKind::Function => 4,
+            HighlightKind::Parameter => 5,
+            HighlightKind::Operator => 6,
+            HighlightKind::Comment => 7,
+            HighlightKind::Punctuation | HighlightKind::Error => continue,
+        };
+        for span in lines.split([h.start, h.end]) {
+            let line = lines.line(span[0]);
+            let start = lines.len([lines.starts[line], span[0]]);
+            let delta = if line == last_line {
+                start - last_start
+            } else {
+                start
+            };
+            data.extend([line - last_line, delta, lines.len(span), ty, 0]);
+            (last_line, last_start) = (line, start);
+        }
+    }
+    Json::Arr(data.into_iter().map(|n| Json::Num(n as f64)).collect())
+}
+
+/// the identifier at `pos`, or just before it
+fn name_at(cst: &Cst, pos: usize) -> Option<usize> {
+    let is_name = |t: &usize| cst.tokens()[*t].token_type() == TokenType::Identifier;
+    let before = pos.checked_sub(1).and_then(|p| cst.token_at(p));
+    cst.token_at(pos).filter(is_name).or(before.filter(is_name))
+}
+
+/// the parameters of the `f(x, y) = ...` around `token` named like it
+fn param_of(cst: &Cst, token: usize) -> Option<usize> {
+    let name = cst.tokens()[token].lexeme();
+    cst.ancestors(token).into_iter().find_map(|id| {
+        let node = cst.node(id);
+        if node.kind != SyntaxKind::FunctionDef {
+            return None;
+        }
+        let params = node
+            .children
+            .iter()
+            .map_while(|&c| match c {
+                Child::Token(t) if cst.tokens()[t].token_type() != TokenType::Equal => Some(t),
+                _ => None,
+            })
+            .filter(|&t| cst.tokens()[t].token_type() == TokenType::Identifier)
+            .skip(1);
+        params
+            .into_iter()
+            .find(|&t| cst.tokens()[t].lexeme() == name)
+    })
+}
+
+/// the value of a name, what a builtin is, or the outputs of the sentence at `pos`
+fn hover(doc: &Document, _: &str, pos: usize) -> Json {
+    let (calc, cst) = (&doc.calc, &doc.calc.a.cst);
+    let sys = &calc.rt.sys;
+    let named = name_at(cst, pos).and_then(|t| {
+        let name = cst.tokens()[t].lexeme();
+        let text = if param_of(cst, t).is_some() {
+            format!("parameter {name}")
+        } else if let Some(val) = calc.rt.lookup(name) {
+            match val {
+                Val::Fun(Fun::User(_)) => val.text(sys),
+                _ => format!("{name} = {}", val.text(sys)),
+            }
+        } else if let Some(f) = sys.function(name) {
+            my_help::function_help(f)
+        } else if let Some(c) = sys.constant_list().into_iter().find(|c| c.0 == name) {
+            my_help::constant_line(sys, c)
+        } else {
+            return None;
+        };
+        Some((text, cst.tokens()[t].pos()))
+    });
+    let (text, span) = match named {
+        Some(named) => named,
+        None => {
+            let outputs: Vec<_> = calc
+                .outputs()
+                .into_iter()
+                .filter(|o| o.span[0] <= pos && pos < o.span[1])
+                .collect();
+            let Some(first) = outputs.first() else {
+                return Json::Null;
+            };
+            let span = first.span;
+            let texts: Vec<_> = outputs
+                .iter()
+                .map(|o| format!("[out {}] {}", o.index, o.text))
+                .collect();
+            (texts.join("\n"), span)
+        }
+    };
+    Json::obj([
+        (
+            "contents",
+            Json::obj([("kind", Json::str("plaintext")), ("value", Json::Str(text))]),
+        ),
+        ("range", doc.lines.range(span)),
+    ])
+}
+
+fn completion(doc: &Document, _: &str, pos: usize) -> Json {
+    let cs = my_complete::complete(&doc.calc.a.cst, pos, &doc.calc.rt);
+    let range = doc.lines.range(cs.span);
+    let items = cs.items.into_iter().map(|c| {
+        // CompletionItemKind
+        let kind = match c.kind {
+            CompletionKind::Variable => 6,
+            CompletionKind::Function | CompletionKind::Builtin => 3,
+            CompletionKind::Constant => 21,
+            CompletionKind::Keyword => 14,
+        };
+        Json::obj([
+            ("label", Json::str(&c.label)),
+            ("kind", Json::Num(kind as f64)),
+            ("detail", Json::Str(c.detail)),
+            (
+                "textEdit",
+                Json::obj([("range", range.clone()), ("newText", Json::Str(c.label))]),
+            ),
+        ])
+    });
+    Json::Arr(items.collect())
+}
+
+/// the parameter, or the last assignment of the name before it, else the first after
+fn definition(doc: &Document, uri: &str, pos: usize) -> Json {
+    let cst = &doc.calc.a.cst;
+    let Some(t) = name_at(cst, pos) else {
+        return Json::Null;
+    };
+    let name = cst.tokens()[t].lexeme();
+    let at = cst.tokens()[t].pos()[0];
+    let target = param_of(cst, t).or_else(|| {
+        let defs: Vec<usize> = cst
+            .nodes()
+            .iter()
+            .filter(|n| matches!(n.kind, SyntaxKind::Assignment | SyntaxKind::FunctionDef))
+            .filter_map(|n| match n.children.first() {
+                Some(&Child::Token(d)) if cst.tokens()[d].lexeme() == name => Some(d),
+                _ => None,
+            })
+            .collect();
+        let before = defs.iter().rev().find(|&&d| cst.tokens()[d].pos()[0] <= at);
+        before.or(defs.first()).copied()
+    });
+    match target {
+        Some(d) => Json::obj([
+            ("uri", Json::str(uri)),
+            ("range", doc.lines.range(cst.tokens()[d].pos())),
+        ]),
+        None => Json::Null,
+    }
+}
+
+/// `= output` after each sentence with outputs, on `lines`
+fn inlay_hints(doc: &Document, lines: std::ops::RangeInclusive<usize>) -> Json {
+    let index = &doc.lines;
+    let mut res = vec![];
+    let outputs = doc.calc.outputs();
+    for group in outputs.chunk_by(|a, b| a.span == b.span) {
+        // after the sentence, before the spaces and `;` ending it
+        let [start, mut end] = group[0].span;
+        while end > start
+            && matches!(index.chars.get(end - 1), Some(c) if c.is_whitespace() || *c == ';')
+        {
+            end -= 1;
+        }
+        if !lines.contains(&index.line(end)) {
+            continue;
+        }
+        let texts: Vec<_> = group.iter().map(|o| o.text.as_str()).collect();
+        res.push(Json::obj([
+            ("position", index.position(end)),
+            ("label", Json::Str(format!("= {}", texts.join(", ")))),
+            ("paddingLeft", Json::Bool(true)),
+        ]));
+    }
+    Json::Arr(res)
+}
+
+#[test]
+fn test_language_server() {
+    let mut ls = LanguageServer::new();
+    let send = |ls: &mut LanguageServer, msg: &str| -> Vec<Json> {
+        let res = ls.handle(msg);
+        res.iter().map(|r| Json::parse(r).unwrap()).collect()
+    };
+    let init = send(
+        &mut ls,
+        r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#,
+    );
+    let caps = init[0].get("result").unwrap().get("capabilities").unwrap();
+    assert_eq!(caps.get("definitionProvider"), Some(&Json::Bool(true)));
+
+    let text = "x = 2 // twö\\nf(t) = t * x\\nf(3); sqtr(4)\\n(";
+    let open = format!(
+        r#"{{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{{"textDocument":{{"uri":"file:///tmp/a%20b.calc","text":"{text}"}}}}}}"#
+    );
+    let published = send(&mut ls, &open);
+    let diags = published[0]
+        .get("params")
+        .unwrap()
+        .get("diagnostics")
+        .unwrap();
+    let messages: Vec<_> = diags
+        .as_arr()
+        .unwrap()
+        .iter()
+        .map(|d| d.get("message").unwrap().as_str().unwrap())
+        .collect();
+    assert_eq!(messages.len(), 2);
+    assert_eq!(
+        messages[1],
+        "sqtr: unknown function, did you mean sqrt or sqr?"
+    );
+    let doc = &ls.docs["file:///tmp/a%20b.calc"];
+    assert_eq!(doc.calc.rt.include.main.as_deref(), Some("/tmp/a b.calc"));
+
+    let request = |method: &str, line: usize, character: usize| {
+        format!(
+            r#"{{"jsonrpc":"2.0","id":2,"method":"{method}","params":{{"textDocument":{{"uri":"file:///tmp/a%20b.calc"}},"position":{{"line":{line},"character":{character}}}}}}}"#
+        )
+    };
+    let result = |res: Vec<Json>| res[0].get("result").unwrap().clone();
+    let hover = result(send(&mut ls, &request("textDocument/hover", 1, 12)));
+    let value = hover.get("contents").unwrap().get("value").unwrap();
+    assert_eq!(value, &Json::str("x = 2"));
+    let hover = result(send(&mut ls, &request("textDocument/hover", 1, 7)));
+    let value = hover.get("contents").unwrap().get("value").unwrap();
+    assert_eq!(value, &Json::str("parameter t"));
+    let hover = result(send(&mut ls, &request("textDocument/hover", 2, 2)));
+    let value = hover.get("contents").unwrap().get("value").unwrap();
+    assert_eq!(value, &Json::str("[out 1] 6"));
+
+    // to `x` of the first line, and to the parameter
+    let def = result(send(&mut ls, &request("textDocument/definition", 1, 12)));
+    assert_eq!(
+        def.get("range").unwrap().to_string(),
+        r#"{"start":{"line":0,"character":0},"end":{"line":0,"character":1}}"#
+    );
+    let def = result(send(&mut ls, &request("textDocument/definition", 1, 7)));
+    assert_eq!(
+        def.get("range").unwrap().get("start"),
+        Some(&Json::obj([
+            ("line", Json::Num(1.0)),
+            ("character", Json::Num(2.0)),
+        ]))
+    );
+
+    let items = result(send(&mut ls, &request("textDocument/completion", 2, 8)));
+    let labels: Vec<_> = items
+        .as_arr()
+        .unwrap()
+        .iter()
+        .map(|i| i.get("label").unwrap().as_str().unwrap())
+        .collect();
+    assert_eq!(labels[..3], ["sqr", "sqrt", "square"]);
+
+    let hints = result(send(
+        &mut ls,
+        r#"{"jsonrpc":"2.0","id":3,"method":"textDocument/inlayHint","params":{"textDocument":{"uri":"file:///tmp/a%20b.calc"},"range":{"start":{"line":0,"character":0},"end":{"line":9,"character":0}}}}"#,
+    ));
+    let hints = hints.as_arr().unwrap();
+    assert_eq!(hints.len(), 1);
+    assert_eq!(hints[0].get("label"), Some(&Json::str("= 6")));
+    assert_eq!(
+        hints[0].get("position").unwrap().to_string(),
+        r#"{"line":2,"character":4}"#
+    );
+
+    // `x`, `=`, `2`, the comment: ö is one unit
+    let tokens = result(send(
+        &mut ls,
+        r#"{"jsonrpc":"2.0","id":4,"method":"textDocument/semanticTokens/full","params":{"textDocument":{"uri":"file:///tmp/a%20b.calc"}}}"#,
+    ));
+    let data: Vec<_> = tokens.get("data").unwrap().as_arr().unwrap()[..20]
+        .iter()
+        .map(|n| n.as_f64().unwrap() as usize)
+        .collect();
+    assert_eq!(
+        data,
+        [0, 0, 1, 3, 0, 0, 2, 1, 6, 0, 0, 2, 1, 0, 0, 0, 2, 6, 7, 0]
+    );
+
+    // a number being typed
+    let change = r#"{"jsonrpc":"2.0","method":"textDocument/didChange","params":{"textDocument":{"uri":"file:///tmp/a%20b.calc","version":2},"contentChanges":[{"text":"x = 1e"}]}}"#;
+    let published = send(&mut ls, change);
+    let diags = published[0].get("params").unwrap().get("diagnostics");
+    let message = diags.unwrap().as_arr().unwrap()[0].get("message");
+    assert_eq!(message, Some(&Json::str("invalid number `1e`")));
+
+    let unknown = send(&mut ls, r#"{"jsonrpc":"2.0","id":5,"method":"nope"}"#);
+    assert_eq!(
+        unknown[0].get("error").unwrap().get("code"),
+        Some(&Json::Num(METHOD_NOT_FOUND))
+    );
+    assert!(
+        send(&mut ls, r#"{"jsonrpc":"2.0","id":6,"method":"shutdown"}"#)[0]
+            .get("result")
+            .is_some()
+    );
+    send(&mut ls, r#"{"jsonrpc":"2.0","method":"exit"}"#);
+    assert_eq!(ls.exit_code(), Some(0));
+}
@@ -1,10 +1,14 @@
-use std::collections::HashMap;
+use std::rc::Rc;
 
 use crate::{
-    my_parser::TokenType,
+    my_highlight::escape_html,
+    my_json::Json,
+    my_parser::{Expression, TokenType},
     my_runtime::{Runtime, System},
 };
 
+pub(crate) mod my_builtins;
+pub(crate) mod my_format;
 pub(crate) mod my_functions;
 
 #[derive(Clone, Debug)]
@@ -13,29 +17,67 @@ pub enum Val {
     Im(Im),
     Fun(Fun),
     Vars(Tuple),
+    /// `"..."`, and what `help` writes
+    Text(String),
+    /// what `plot` draws, trusted as html
+    Svg(String),
 }
 impl Val {
-    pub fn print(&self, index: &mut usize) -> String {
+    /// as html, numbers are written by the format settings of `sys`
+    pub fn print(&self, index: &mut usize, sys: &System) -> String {
         match self {
-            Val::Re(Re(v)) => {
+            Val::Vars(t) => t.print(index, sys),
+            Val::Svg(s) => {
                 *index += 1;
-                format!("[out {index}] {v}\n")
+                format!("[out {index}] {s}\n")
             }
-            Val::Im(Im(u, v)) => {
+            _ => {
                 *index += 1;
-                format!("[out {index}] {u}+{v}i\n")
+                format!("[out {index}] {}\n", escape_html(&self.text(sys)))
             }
-            Val::Fun(Fun::System(name)) => {
-                *index += 1;
-                format!("[out {index}] @fun: {name}\n")
+        }
+    }
+    /// a single value as text, tuples as `(a, b)`
+    pub fn text(&self, sys: &System) -> String {
+        match self {
+            Val::Re(Re(v)) => sys.format_re(*v),
+            Val::Im(Im(u, v)) => sys.format_im(*u, *v),
+            Val::Fun(Fun::System(name)) => format!("@fun: {name}"),
+            Val::Fun(Fun::User(c)) => {
+                format!("@fun: ({}) = {}", c.params.join(", "), c.body.print(0))
+            }
+            Val::Vars(Tuple(v)) => {
+                let v: Vec<String> = v.iter().map(|v| v.text(sys)).collect();
+                format!("({})", v.join(", "))
             }
-            Val::Vars(t) => t.print(index),
+            Val::Text(s) | Val::Svg(s) => s.clone(),
         }
     }
 }
 impl Val {
     pub const NONE: Self = Val::Vars(Tuple::NONE);
 
+    /// the values printed as separate outputs, in order
+    pub fn leaves(&self) -> Vec<Val> {
+        match self {
+            Val::Vars(Tuple(v)) => v.iter().flat_map(|v| v.leaves()).collect(),
+            _ => vec![self.clone()],
+        }
+    }
+
+    /// `x[i]`, counts from 1, negative `i` counts from the end
+    pub fn index(&self, i: &Val) -> Val {
+        let (Val::Vars(Tuple(v)), Val::Re(Re(i))) = (self, i) else {
+            return Val::NONE;
+        };
+        let i = i.round() as i64;
+        let i = if i < 0 { v.len() as i64 + i } else { i - 1 };
+        if i < 0 {
+            return Val::NONE;
+        }
+        v.get(i as usize).cloned().unwrap_or(Val::NONE)
+    }
+
     pub fn reduce(self) -> Self {
         match self {
             Val::Vars(Tuple(mut v)) if v.len() == 1 => v.pop().unwrap().reduce(),
@@ -51,6 +93,13 @@ impl Val {
             TokenType::Star => self.mul(s),
             TokenType::Slash => self.div(s),
             TokenType::Percent => self.rem(s),
+            TokenType::Caret => self.pow(s),
+            TokenType::Less => self.compare(s, |a, b| a < b),
+            TokenType::LessEqual => self.compare(s, |a, b| a <= b),
+            TokenType::Greater => self.compare(s, |a, b| a > b),
+            TokenType::GreaterEqual => self.compare(s, |a, b| a >= b),
+            TokenType::EqualEqual => self.compare(s, |a, b| a == b),
+            TokenType::BangEqual => self.compare(s, |a, b| a != b),
             _ => Self::NONE,
         }
     }
@@ -91,10 +140,27 @@ impl Val {
         }
     }
 
+    /// ^
+    pub fn pow(&self, s: &Self) -> Val {
+        match (self, s) {
+            (Val::Re(r1), Val::Re(r2)) => Val::Re(Re(r1.0.powf(r2.0))),
+            _ => Val::NONE,
+        }
+    }
+
+    /// `<`, `==` and the others, 1 when `f` holds and 0 otherwise
+    pub fn compare(&self, s: &Self, f: impl Fn(f64, f64) -> bool) -> Val {
+        match (self, s) {
+            (Val::Re(r1), Val::Re(r2)) => Val::Re(Re(f64::from(u8::from(f(r1.0, r2.0))))),
+            _ => Val::NONE,
+        }
+    }
+
     /// -x
     pub fn neg(&self) -> Val {
         match self {
             Val::Re(r1) => Val::Re(Re(-r1.0)),
+            Val::Im(i1) => Val::Im(Im(-i1.0, -i1.1)),
             _ => Val::NONE,
         }
     }
@@ -103,11 +169,125 @@ impl Val {
     pub fn calls(&self, rt: &mut Runtime, vars: Val) -> Val {
         match self {
             Val::Fun(Fun::System(name)) => System::calls(rt, name, vars),
+            Val::Fun(Fun::User(c)) => rt.call_closure(c, vars),
             _ => Self::NONE,
         }
     }
 }
 
+impl Val {
+    /// `{"re": 1}`, `{"im": [0, 1]}`, `{"tuple": [...]}`, `{"text": "..."}`, `{"fun": "sin"}` or
+    /// `{"closure": {"name": "f", "params": ["x"], "body": "x ^ a", "captured": {"a": {"re": 2}}}}`.
+    /// NaN and infinities are written as the strings `"NaN"`, `"inf"`, `"-inf"`
+    pub fn to_json(&self) -> Json {
+        match self {
+            Val::Re(Re(v)) => Json::obj([("re", num_to_json(*v))]),
+            Val::Im(Im(u, v)) => {
+                Json::obj([("im", Json::Arr(vec![num_to_json(*u), num_to_json(*v)]))])
+            }
+            Val::Vars(Tuple(v)) => {
+                Json::obj([("tuple", Json::Arr(v.iter().map(|v| v.to_json()).collect()))])
+            }
+            Val::Text(s) => Json::obj([("text", Json::str(s))]),
+            Val::Svg(s) => Json::obj([("svg", Json::str(s))]),
+            Val::Fun(Fun::System(name)) => Json::obj([("fun", Json::str(name))]),
+            Val::Fun(Fun::User(c)) => Json::obj([(
+                "closure",
+                Json::obj([
+                    ("name", Json::str(&c.name)),
+                    (
+                        "params",
+                        Json::Arr(c.params.iter().map(|p| Json::str(p)).collect()),
+                    ),
+                    ("body", Json::Str(c.body.print(0))),
+                    (
+                        "captured",
+                        Json::Obj(
+                            c.captured
+                                .iter()
+                                .map(|(k, v)| (k.clone(), v.to_json()))
+                                .collect(),
+                        ),
+                    ),
+                ]),
+            )]),
+        }
+    }
+
+    pub fn from_json(j: &Json) -> Result<Val, String> {
+        let Some([(kind, v)]) = j.as_obj() else {
+            return Err(format!("not a value: {j}"));
+        };
+        let bad = || format!("bad {kind}: {v}");
+        match kind.as_str() {
+            "re" => Ok(Val::Re(Re(num_from_json(v).ok_or_else(bad)?))),
+            "im" => match v.as_arr() {
+                Some([u, v]) => Ok(Val::Im(Im(
+                    num_from_json(u).ok_or_else(bad)?,
+                    num_from_json(v).ok_or_else(bad)?,
+                ))),
+                _ => Err(bad()),
+            },
+            "tuple" => {
+                let v = v.as_arr().ok_or_else(bad)?;
+                Ok(Val::Vars(Tuple(
+                    v.iter().map(Val::from_json).collect::<Result<_, _>>()?,
+                )))
+            }
+            "text" => Ok(Val::Text(v.as_str().ok_or_else(bad)?.to_string())),
+            // only what `plot` draws is trusted as html, not what is imported
+            "svg" => Ok(Val::Text(v.as_str().ok_or_else(bad)?.to_string())),
+            "fun" => Ok(Val::Fun(Fun::System(
+                v.as_str().ok_or_else(bad)?.to_string(),
+            ))),
+            "closure" => {
+                let params = v.get("params").and_then(|p| p.as_arr()).ok_or_else(bad)?;
+                let params = params
+                    .iter()
+                    .map(|p| p.as_str().map(|p| p.to_string()))
+                    .collect::<Option<_>>()
+                    .ok_or_else(bad)?;
+                let body = v.get("body").and_then(|b| b.as_str()).ok_or_else(bad)?;
+                let body = Expression::parse_str(body).ok_or_else(bad)?;
+                // version 1 wrote neither
+                let name = v.get("name").and_then(|n| n.as_str()).unwrap_or_default();
+                let captured = match v.get("captured") {
+                    Some(c) => c
+                        .as_obj()
+                        .ok_or_else(bad)?
+                        .iter()
+                        .map(|(k, v)| Ok((k.clone(), Val::from_json(v)?)))
+                        .collect::<Result<_, String>>()?,
+                    None => vec![],
+                };
+                Ok(Val::Fun(Fun::User(Rc::new(Closure {
+                    name: name.to_string(),
+                    params,
+                    body,
+                    captured,
+                }))))
+            }
+            _ => Err(format!("unknown value kind: {kind}")),
+        }
+    }
+}
+
+fn num_to_json(v: f64) -> Json {
+    if v.is_finite() {
+        Json::Num(v)
+    } else {
+        Json::Str(v.to_string())
+    }
+}
+
+fn num_from_json(j: &Json) -> Option<f64> {
+    match j {
+        Json::Num(v) => Some(*v),
+        Json::Str(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Re(pub f64);
 
@@ -117,6 +297,19 @@ pub struct Im(pub f64, pub f64);
 #[derive(Clone, Debug)]
 pub enum Fun {
     System(String),
+    /// f(x) = ...
+    User(Rc<Closure>),
+}
+
+/// a user function, with the values of the block it is defined in
+#[derive(Debug)]
+pub struct Closure {
+    /// bound to the function itself in its calls
+    pub name: String,
+    pub params: Vec<String>,
+    pub body: Expression,
+    /// variables of enclosing blocks it reads, as they were when it was defined
+    pub captured: Vec<(String, Val)>,
 }
 
 #[derive(Clone, Debug)]
@@ -126,17 +319,26 @@ impl Tuple {
 }
 
 impl Tuple {
-    fn print(&self, index: &mut usize) -> String {
+    fn print(&self, index: &mut usize, sys: &System) -> String {
         let mut res = String::new();
         for v in &self.0 {
-            res += &v.print(index);
+            res += &v.print(index, sys);
         }
         res
     }
 }
 
-/// create default functions and vars
-pub fn intial_system() -> HashMap<String, Val> {
-    let mut res = HashMap::new();
-    res
+/// default constants, with what they are
+pub fn intial_system() -> Vec<(&'static str, Val, &'static str)> {
+    use std::f64::consts::{E, PI};
+    vec![
+        (
+            "pi",
+            Val::Re(Re(PI)),
+            "ratio of a circle's circumference to its diameter",
+        ),
+        ("π", Val::Re(Re(PI)), "pi"),
+        ("e", Val::Re(Re(E)), "base of the natural logarithm"),
+        ("i", Val::Im(Im(0.0, 1.0)), "imaginary unit, i^2 = -1"),
+    ]
 }
@@ -0,0 +1,361 @@
+use std::{ops::RangeInclusive, rc::Rc};
+
+use crate::{
+    my_help,
+    my_math::{my_functions::SysFunctionReal, Re, Val},
+    my_plot,
+    my_runtime::{Runtime, System},
+};
+
+/// the arguments to the result, or why it fails
+pub type NativeCall = Rc<dyn Fn(&System, &[Val]) -> Result<Val, String>>;
+/// like `NativeCall`, for functions calling the functions they are given
+pub type RuntimeCall = Rc<dyn Fn(&mut Runtime, &[Val]) -> Result<Val, String>>;
+
+/// a function the sentences call by name, builtin or given by the embedder
+#[derive(Clone)]
+pub struct NativeFunction {
+    pub name: String,
+    /// other names calling the same function
+    pub aliases: Vec<String>,
+    /// counts of arguments it takes
+    pub arity: RangeInclusive<usize>,
+    pub call: NativeCall,
+    /// called instead of `call` if any
+    pub runtime: Option<RuntimeCall>,
+    /// how it is called, like `log(b, x)`
+    pub signature: String,
+    /// one line about what it does
+    pub doc: String,
+    /// arguments it is defined for, empty for any
+    pub domain: String,
+    /// a sentence and its output, like `sin(pi / 2) = 1`
+    pub example: String,
+}
+
+impl NativeFunction {
+    pub fn new(
+        name: &str,
+        arity: RangeInclusive<usize>,
+        call: impl Fn(&System, &[Val]) -> Result<Val, String> + 'static,
+    ) -> Self {
+        NativeFunction {
+            name: name.to_string(),
+            aliases: vec![],
+            signature: signature(name, &arity),
+            arity,
+            call: Rc::new(call),
+            runtime: None,
+            doc: String::new(),
+            domain: String::new(),
+            example: String::new(),
+        }
+    }
+    pub fn alias(mut self, name: &str) -> Self {
+        self.aliases.push(name.to_string());
+        self
+    }
+    pub fn doc(mut self, doc: &str) -> Self {
+        self.doc = doc.to_string();
+        self
+    }
+    /// `name(x, y)` by default, `name(x, [y])` for optional arguments
+    pub fn signature(mut self, signature: &str) -> Self {
+        self.signature = signature.to_string();
+        self
+    }
+    pub fn domain(mut self, domain: &str) -> Self {
+        self.domain = domain.to_string();
+        self
+    }
+    pub fn example(mut self, example: &str) -> Self {
+        self.example = example.to_string();
+        self
+    }
+
+    /// a function given the runtime, to call the functions among its arguments
+    pub fn with_runtime(
+        name: &str,
+        arity: RangeInclusive<usize>,
+        call: impl Fn(&mut Runtime, &[Val]) -> Result<Val, String> + 'static,
+    ) -> Self {
+        let mut f = Self::new(name, arity, |_, _| Err("needs the runtime".to_string()));
+        f.runtime = Some(Rc::new(call));
+        f
+    }
+
+    /// a function of real numbers
+    pub fn real(name: &str, arity: usize, f: impl Fn(&[f64]) -> f64 + 'static) -> Self {
+        Self::new(name, arity..=arity, move |_, args| {
+            Ok(Val::Re(Re(f(&reals(args)?))))
+        })
+    }
+
+    /// the name and the aliases
+    pub fn names(&self) -> impl Iterator<Item = &String> {
+        std::iter::once(&self.name).chain(&self.aliases)
+    }
+}
+
+/// `name(x, y, [z])`
+fn signature(name: &str, arity: &RangeInclusive<usize>) -> String {
+    let params: Vec<String> = (0..*arity.end())
+        .map(|i| {
+            let p = match i {
+                0..=3 => ["x", "y", "z", "w"][i].to_string(),
+                _ => format!("x{}", i + 1),
+            };
+            if i < *arity.start() {
+                p
+            } else {
+                format!("[{p}]")
+            }
+        })
+        .collect();
+    format!("{name}({})", params.join(", "))
+}
+
+/// the arguments as real numbers
+pub fn reals(args: &[Val]) -> Result<Vec<f64>, String> {
+    args.iter()
+        .map(|v| match v {
+            Val::Re(Re(x)) => Ok(*x),
+            _ => Err("expects real numbers".to_string()),
+        })
+        .collect()
+}
+
+type S = System;
+
+/// the functions every runtime starts with
+pub fn builtins() -> Vec<NativeFunction> {
+    let re1 = |name: &str, f: fn(f64) -> f64| NativeFunction::real(name, 1, move |x| f(x[0]));
+    let re2 =
+        |name: &str, f: fn(f64, f64) -> f64| NativeFunction::real(name, 2, move |x| f(x[0], x[1]));
+    // one or two arguments
+    let re12 = |name: &str, f: fn(f64) -> f64, g: fn(f64, f64) -> f64| {
+        NativeFunction::new(name, 1..=2, move |_, args| {
+            let res = match reals(args)?[..] {
+                [x] => f(x),
+                [x, y] => g(x, y),
+                _ => f64::NAN,
+            };
+            Ok(Val::Re(Re(res)))
+        })
+    };
+    vec![
+        re1("abs", S::_abs)
+            .alias("absolute")
+            .doc("absolute value")
+            .example("abs(-3) = 3"),
+        re1("neg", S::_neg)
+            .alias("negative")
+            .doc("-x")
+            .example("neg(3) = -3"),
+        re1("round", S::_round)
+            .doc("nearest integer, halves away from zero")
+            .example("round(2.5) = 3"),
+        re1("ceil", S::_ceil)
+            .doc("smallest integer not below x")
+            .example("ceil(2.1) = 3"),
+        re1("floor", S::_floor)
+            .alias("int")
+            .doc("largest integer not above x")
+            .example("floor(-2.1) = -3"),
+        re1("sin", S::_sin)
+            .doc("sine, x in radians")
+            .example("sin(pi / 2) = 1"),
+        re1("cos", S::_cos)
+            .doc("cosine, x in radians")
+            .example("cos(pi) = -1"),
+        re1("tan", S::_tan)
+            .doc("tangent, x in radians")
+            .domain("x ≠ pi / 2 + k pi")
+            .example("tan(pi / 4) = 1"),
+        re1("cot", S::_cot)
+            .doc("cotangent, x in radians")
+            .domain("x ≠ k pi")
+            .example("cot(pi / 4) = 1"),
+        re1("sec", S::_sec)
+            .doc("secant, x in radians")
+            .domain("x ≠ pi / 2 + k pi")
+            .example("sec(0) = 1"),
+        re1("csc", S::_csc)
+            .doc("cosecant, x in radians")
+            .domain("x ≠ k pi")
+            .example("csc(pi / 2) = 1"),
+        re1("asin", S::_arcsin)
+            .alias("arcsin")
+            .doc("inverse sine, in radians")
+            .domain("-1 ≤ x ≤ 1")
+            .example("asin(1) = 1.5707963267948966"),
+        re1("acos", S::_arccos)
+            .alias("arccos")
+            .doc("inverse cosine, in radians")
+            .domain("-1 ≤ x ≤ 1")
+            .example("acos(1) = 0"),
+        re12("atan", S::_arctan, S::_arctan2)
+            .alias("arctan")
+            .signature("atan(x), atan(y, x)")
+            .doc("inverse tangent, in radians; atan(y, x) is atan2(y, x)")
+            .example("atan(1) = 0.7853981633974483"),
+        re1("acot", S::_arccot)
+            .alias("arccot")
+            .doc("inverse cotangent, in radians")
+            .example("acot(1) = 0.7853981633974483"),
+        re1("asec", S::_arcsec)
+            .alias("arcsec")
+            .doc("inverse secant, in radians")
+            .domain("|x| ≥ 1")
+            .example("asec(1) = 0"),
+        re1("acsc", S::_arccsc)
+            .alias("arccsc")
+            .doc("inverse cosecant, in radians")
+            .domain("|x| ≥ 1")
+            .example("acsc(1) = 1.5707963267948966"),
+        re1("sinh", S::_sinh)
+            .doc("hyperbolic sine")
+            .example("sinh(0) = 0"),
+        re1("cosh", S::_cosh)
+            .doc("hyperbolic cosine")
+            .example("cosh(0) = 1"),
+        re1("tanh", S::_tanh)
+            .doc("hyperbolic tangent")
+            .example("tanh(0) = 0"),
+        re1("coth", S::_coth)
+            .doc("hyperbolic cotangent")
+            .domain("x ≠ 0")
+            .example("coth(1) = 1.3130352854993315"),
+        re1("sech", S::_sech)
+            .doc("hyperbolic secant")
+            .example("sech(0) = 1"),
+        re1("csch", S::_csch)
+            .doc("hyperbolic cosecant")
+            .domain("x ≠ 0")
+            .example("csch(1) = 0.8509181282393216"),
+        re1("asinh", S::_arcsinh)
+            .alias("arcsinh")
+            .doc("inverse hyperbolic sine")
+            .example("asinh(0) = 0"),
+        re1("acosh", S::_arccosh)
+            .alias("arccosh")
+            .doc("inverse hyperbolic cosine")
+            .domain("x ≥ 1")
+            .example("acosh(1) = 0"),
+        re1("atanh", S::_arctanh)
+            .alias("arctanh")
+            .doc("inverse hyperbolic tangent")
+            .domain("-1 < x < 1")
+            .example("atanh(0) = 0"),
+        re1("acoth", S::_arccoth)
+            .alias("arccoth")
+            .doc("inverse hyperbolic cotangent")
+            .domain("|x| > 1")
+            .example("acoth(2) = 0.5493061443340548"),
+        re1("asech", S::_arcsech)
+            .alias("arcsech")
+            .doc("inverse hyperbolic secant")
+            .domain("0 < x ≤ 1")
+            .example("asech(1) = 0"),
+        re1("acsch", S::_arccsch)
+            .alias("arccsch")
+            .doc("inverse hyperbolic cosecant")
+            .domain("x ≠ 0")
+            .example("acsch(1) = 0.881373587019543"),
+        re1("todegree", S::_rad_to_deg)
+            .alias("raddegree")
+            .doc("radians to degrees")
+            .example("todegree(pi) = 180"),
+        re1("torad", S::_deg_to_rad)
+            .alias("degreerad")
+            .doc("degrees to radians")
+            .example("torad(180) = 3.141592653589793"),
+        re1("square", S::_square)
+            .doc("x^2")
+            .example("square(3) = 9"),
+        re1("cube", S::_cube).doc("x^3").example("cube(2) = 8"),
+        re1("sqrt", S::_sqrt)
+            .alias("sqr")
+            .doc("square root, also written √x")
+            .domain("x ≥ 0")
+            .example("sqrt(16) = 4"),
+        re1("cbrt", S::_cbrt)
+            .alias("cbr")
+            .doc("cube root")
+            .example("cbrt(-8) = -2"),
+        re1("exp", S::_exp).doc("e^x").example("exp(0) = 1"),
+        re1("log10", S::_log10)
+            .doc("logarithm to base 10")
+            .domain("x > 0")
+            .example("log10(1000) = 3"),
+        re1("ln", S::_ln)
+            .alias("loge")
+            .doc("natural logarithm")
+            .domain("x > 0")
+            .example("ln(e) = 1"),
+        re1("log2", S::_log2)
+            .doc("logarithm to base 2")
+            .domain("x > 0")
+            .example("log2(8) = 3"),
+        NativeFunction::new("zero", 1..=1, |sys, args| {
+            let x = reals(args)?[0];
+            Ok(Val::Re(Re(sys._iszero(x) as i32 as f64)))
+        })
+        .doc("1 if x is within epsilon of 0, else 0")
+        .example("zero(1e-12) = 1"),
+        re2("add", S::_add)
+            .alias("plus")
+            .doc("x + y")
+            .example("add(1, 2) = 3"),
+        re2("substract", S::_sub)
+            .alias("minus")
+            .doc("x - y")
+            .example("substract(5, 2) = 3"),
+        re2("multiply", S::_mulitply)
+            .alias("dot")
+            .doc("x * y")
+            .example("multiply(2, 3) = 6"),
+        re2("devide", S::_devide)
+            .alias("frac")
+            .doc("x / y")
+            .example("devide(6, 3) = 2"),
+        re2("atan2", S::_arctan2)
+            .alias("arctan2")
+            .signature("atan2(y, x)")
+            .doc("angle of the point (x, y), in radians")
+            .example("atan2(1, 0) = 1.5707963267948966"),
+        re2("pow", S::_pow)
+            .alias("power")
+            .doc("x^y")
+            .example("pow(2, 10) = 1024"),
+        re12("log", S::_ln, S::_log)
+            .alias("logarithm")
+            .signature("log(x), log(b, x)")
+            .doc("natural logarithm; log(b, x) is the logarithm of x to base b")
+            .domain("x > 0, b > 0, b ≠ 1")
+            .example("log(2, 8) = 3"),
+        NativeFunction::with_runtime("plot", 3..=3, my_plot::plot)
+            .signature("plot(f, a, b), plot((f, g), a, b)")
+            .doc("svg of y = f(x) for a ≤ x ≤ b; f may be a function or an expression of x in quotes")
+            .domain("a < b"),
+        NativeFunction::with_runtime("parametric", 3..=3, my_plot::parametric)
+            .signature("parametric(c, a, b), parametric((f, g), a, b)")
+            .doc("svg of the points c(t) = (x, y), or (f(t), g(t)), for a ≤ t ≤ b; expressions in quotes are of t")
+            .domain("a < b"),
+        NativeFunction::with_runtime("polar", 3..=3, my_plot::polar)
+            .signature("polar(r, a, b), polar((r, s), a, b)")
+            .doc("svg of the points at distance r(θ) and angle θ for a ≤ θ ≤ b; expressions in quotes are of θ")
+            .domain("a < b"),
+        NativeFunction::with_runtime("implicit", 5..=5, my_plot::implicit)
+            .signature("implicit(F, x0, x1, y0, y1)")
+            .doc("svg of F(x, y) = 0 for x0 ≤ x ≤ x1, y0 ≤ y ≤ y1; F may be an expression of x and y in quotes")
+            .domain("x0 < x1, y0 < y1"),
+        NativeFunction::new("help", 0..=1, my_help::help)
+            .signature("help(), help(f), help(\"name\")")
+            .doc("what a function or a constant is"),
+        NativeFunction::new("functions", 0..=0, my_help::functions)
+            .doc("every function with what it does"),
+        NativeFunction::new("constants", 0..=0, my_help::constants)
+            .doc("every constant with its value"),
+    ]
+}
@@ -0,0 +1,274 @@
+use std::{f64::consts::PI, num::NonZeroUsize};
+
+/// the way a number is written out
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Notation {
+    /// shortest exact form, switches to `1e20` for huge or tiny values
+    Auto,
+    /// fixed count of decimals, `Fixed(3)`: `3.142`
+    Fixed(usize),
+    /// significant figures, `Significant(3)`: `3.14`, at least one
+    Significant(NonZeroUsize),
+    /// decimals of the mantissa, `Scientific(3)`: `3.142e0`
+    Scientific(usize),
+    /// exponent is a multiple of 3, `Engineering(2)`: `31.42e3`
+    Engineering(usize),
+}
+
+/// output settings of numbers
+#[derive(Clone, Debug)]
+pub struct NumFormat {
+    pub notation: Notation,
+    /// `Some(',')`: `1,234,567.5`
+    pub separator: Option<char>,
+    /// snap `0.30000000000000004` to `0.3`, and values within epsilon to non-zero integers
+    pub rounding: bool,
+    /// write `π/2` instead of `1.5707963267948966`
+    pub pi: bool,
+}
+
+impl Default for NumFormat {
+    fn default() -> Self {
+        Self {
+            notation: Notation::Auto,
+            separator: None,
+            rounding: true,
+            pi: false,
+        }
+    }
+}
+
+/// significant digits of the shortest form tried by automatic rounding
+const ROUND_DIGITS: usize = 15;
+/// most decimals or significant figures, all an `f64` has
+pub const MAX_DIGITS: usize = 17;
+/// largest denominator tried by `π` multiples
+const PI_DENOMINATOR: i64 = 12;
+
+impl Notation {
+    /// `auto`, `fixed`, `sig`, `sci`, `eng` and their long names,
+    /// `None` for more than `MAX_DIGITS` digits
+    pub fn from_name(name: &str, digits: usize) -> Option<Self> {
+        if digits > MAX_DIGITS {
+            return None;
+        }
+        match name.to_lowercase().as_str() {
+            "auto" | "default" => Some(Notation::Auto),
+            "fixed" | "fix" => Some(Notation::Fixed(digits)),
+            "significant" | "sig" => Some(Notation::Significant(
+                NonZeroUsize::new(digits).unwrap_or(NonZeroUsize::MIN),
+            )),
+            "scientific" | "sci" => Some(Notation::Scientific(digits)),
+            "engineering" | "eng" => Some(Notation::Engineering(digits)),
+            _ => None,
+        }
+    }
+}
+
+impl Notation {
+    /// name and digits, read back by `from_name`
+    pub fn name(&self) -> (&'static str, usize) {
+        match *self {
+            Notation::Auto => ("auto", 0),
+            Notation::Fixed(d) => ("fixed", d),
+            Notation::Significant(d) => ("sig", d.get()),
+            Notation::Scientific(d) => ("sci", d),
+            Notation::Engineering(d) => ("eng", d),
+        }
+    }
+}
+
+impl NumFormat {
+    /// write a real number
+    pub fn real(&self, x: f64, epsilon: f64) -> String {
+        if x.is_nan() {
+            return "NaN".to_string();
+        }
+        if x.is_infinite() {
+            return if x > 0.0 { "inf" } else { "-inf" }.to_string();
+        }
+        let x = if self.rounding { snap(x, epsilon) } else { x };
+        if self.pi {
+            if let Some(s) = pi_multiple(x, epsilon) {
+                return s;
+            }
+        }
+        match self.notation {
+            Notation::Auto => {
+                let a = x.abs();
+                if a != 0.0 && !(1e-6..1e16).contains(&a) {
+                    format!("{x:e}")
+                } else {
+                    self.group(format!("{x}"))
+                }
+            }
+            Notation::Fixed(d) => self.group(format!("{x:.d$}")),
+            Notation::Significant(n) => {
+                let n = n.get();
+                if x == 0.0 {
+                    return "0".to_string();
+                }
+                let x: f64 = format!("{:.*e}", n - 1, x).parse().unwrap_or(x);
+                let exp = x.abs().log10().floor() as i32;
+                if !(-6..16).contains(&exp) {
+                    format!("{:.*e}", n - 1, x)
+                } else {
+                    let d = (n as i32 - 1 - exp).max(0) as usize;
+                    self.group(format!("{x:.d$}"))
+                }
+            }
+            Notation::Scientific(d) => format!("{x:.d$e}"),
+            Notation::Engineering(d) => {
+                if x == 0.0 {
+                    return format!("{:.d$}e0", 0.0);
+                }
+                let mut exp = (x.abs().log10().floor() as i32).div_euclid(3) * 3;
+                let mut mant = x / 10f64.powi(exp);
+                // 999.96 may round up to 1000.0
+                if format!("{:.d$}", mant.abs()).starts_with("1000") {
+                    exp += 3;
+                    mant = x / 10f64.powi(exp);
+                }
+                format!("{mant:.d$}e{exp}")
+            }
+        }
+    }
+
+    /// write a complex number `re + im i`
+    pub fn complex(&self, re: f64, im: f64, epsilon: f64) -> String {
+        let (re, im) = if self.rounding {
+            // a part lost in the noise of the other
+            let size = re.hypot(im);
+            let part = |v: f64| {
+                if v.abs() < epsilon * size {
+                    0.0
+                } else {
+                    snap(v, epsilon)
+                }
+            };
+            (part(re), part(im))
+        } else {
+            (re, im)
+        };
+        if im == 0.0 {
+            return self.real(re, epsilon);
+        }
+        let mut i = self.real(im.abs(), epsilon);
+        if i == "1" {
+            i.clear();
+        }
+        match (re == 0.0, im < 0.0) {
+            (true, false) => format!("{i}i"),
+            (true, true) => format!("-{i}i"),
+            (false, false) => format!("{}+{i}i", self.real(re, epsilon)),
+            (false, true) => format!("{}-{i}i", self.real(re, epsilon)),
+        }
+    }
+
+    /// insert the thousands separator into the integer digits
+    fn group(&self, s: String) -> String {
+        let Some(sep) = self.separator else {
+            return s;
+        };
+        let (sign, body) = match s.strip_prefix('-') {
+            Some(b) => ("-", b),
+            None => ("", s.as_str()),
+        };
+        let end = body.find(['.', 'e']).unwrap_or(body.len());
+        let (int, rest) = body.split_at(end);
+        let mut res = String::from(sign);
+        for (i, c) in int.chars().enumerate() {
+            if i > 0 && (int.len() - i) % 3 == 0 {
+                res.push(sep);
+            }
+            res.push(c);
+        }
+        res + rest
+    }
+}
+
+/// drop float noise: snap to a non-zero integer within epsilon, or to the
+/// `ROUND_DIGITS` digits form when it is the float next to `x`
+fn snap(x: f64, epsilon: f64) -> f64 {
+    let r = x.round();
+    if r != 0.0 && (x - r).abs() < epsilon {
+        return r;
+    }
+    let short: f64 = format!("{:.*e}", ROUND_DIGITS - 1, x).parse().unwrap_or(x);
+    // the last bit of `x`
+    let ulp = f64::from_bits(x.abs().to_bits() + 1) - x.abs();
+    if (short - x).abs() <= ulp {
+        short
+    } else {
+        x
+    }
+}
+
+/// `p π / q` with a small `q`
+fn pi_multiple(x: f64, epsilon: f64) -> Option<String> {
+    if x == 0.0 {
+        return None;
+    }
+    let r = x / PI;
+    for q in 1..=PI_DENOMINATOR {
+        let p = (r * q as f64).round();
+        if p == 0.0 || p.abs() > 1000.0 {
+            continue;
+        }
+        if (x - p * PI / q as f64).abs() < epsilon * x.abs().max(1.0) {
+            let p = p as i64;
+            let g = gcd(p.abs(), q);
+            let (p, q) = (p / g, q / g);
+            let num = match p {
+                1 => "π".to_string(),
+                -1 => "-π".to_string(),
+                _ => format!("{p}π"),
+            };
+            return Some(if q == 1 { num } else { format!("{num}/{q}") });
+        }
+    }
+    None
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+#[test]
+fn test_format() {
+    let eps = 1.0e-9;
+    let mut f = NumFormat::default();
+    assert_eq!(f.real(0.1 + 0.2, eps), "0.3");
+    assert_eq!(f.real(PI.sin(), eps), "1.2246467991473532e-16");
+    assert_eq!(f.real(1e-12, eps), "1e-12");
+    assert_eq!(f.real(1e-7, eps), "1e-7");
+    assert_eq!(f.real(std::f64::consts::E, eps), "2.718281828459045");
+    assert_eq!(f.real(PI, eps), "3.141592653589793");
+    assert_eq!(f.real(1.0f64.atan(), eps), "0.7853981633974483");
+    assert_eq!(f.real(0.7 + 0.1, eps), "0.8");
+    assert_eq!(f.real(2.0 - 1e-12, eps), "2");
+    assert_eq!(f.complex(-1.0, PI.sin(), eps), "-1");
+    assert_eq!(f.complex(1.0, -2.0, eps), "1-2i");
+    assert_eq!(f.complex(0.0, 1.0, eps), "i");
+    f.separator = Some(',');
+    f.notation = Notation::Fixed(2);
+    assert_eq!(f.real(-1234567.891, eps), "-1,234,567.89");
+    f.notation = Notation::from_name("sig", 3).unwrap();
+    assert_eq!(f.real(PI, eps), "3.14");
+    f.notation = Notation::from_name("sig", 0).unwrap();
+    assert_eq!(f.real(PI, eps), "3");
+    f.notation = Notation::from_name("fixed", MAX_DIGITS).unwrap();
+    assert_eq!(f.real(0.5, eps), "0.50000000000000000");
+    assert!(Notation::from_name("fixed", MAX_DIGITS + 1).is_none());
+    f.notation = Notation::Engineering(2);
+    assert_eq!(f.real(31415.9, eps), "31.42e3");
+    f.notation = Notation::Scientific(3);
+    assert_eq!(f.real(31415.9, eps), "3.142e4");
+    f.pi = true;
+    assert_eq!(f.real(PI / 2.0, eps), "π/2");
+    assert_eq!(f.real(-3.0 * PI / 4.0, eps), "-3π/4");
+}
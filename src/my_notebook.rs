@@ -0,0 +1,373 @@
+use std::{
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
+
+use crate::{
+    my_cst::Cst,
+    my_graph::{is_history, DepError, DepGraph},
+    my_math::{Fun, Tuple, Val},
+    my_parser::{Article, Diagnostic, Sentence, SentenceRanges, Token},
+    my_runtime::{CallError, Runtime},
+};
+
+/// Caches every top-level sentence of the input, so that an edit only
+/// recomputes the sentences it touches.
+///
+/// Each update restarts the runtime: unchanged sentences replay their
+/// cached assignments and outputs, the others are calculated again,
+/// so the result always equals a fresh run over the whole input.
+#[derive(Default)]
+pub struct Notebook {
+    cells: Vec<Cell>,
+    /// sentences calculated by the last update
+    recomputed: usize,
+    /// malformed sentences of the last update
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// what `Article::split` gives
+type Split = (SentenceRanges, Vec<Diagnostic>, Cst);
+
+struct Cell {
+    /// lexemes it is parsed from, each ended by `\n`, without whitespaces
+    /// and comments, and another `\n` for each line break, as it may end a sentence
+    key: String,
+    /// it has an `include`, whose file may have changed
+    includes: bool,
+    /// variables it depends on
+    reads: Rc<HashSet<String>>,
+    /// variables it may assign
+    writes: Rc<HashSet<String>>,
+    /// values of `writes` in the root env after it
+    effects: Rc<[(String, Option<Val>)]>,
+    /// calls that failed while it was calculated
+    errors: Rc<[CallError]>,
+    result: Val,
+    /// place in the order the sentences were calculated
+    rank: usize,
+}
+
+impl Notebook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// forget all cached sentences, the next update calculates everything
+    pub fn clear(&mut self) {
+        self.cells.clear();
+    }
+
+    pub fn recomputed(&self) -> usize {
+        self.recomputed
+    }
+
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// calculate the sentences of `tks` in a restarted `rt`, reusing the cache,
+    /// and the article they are parsed into
+    pub fn update(&mut self, tks: &[Token], rt: &mut Runtime) -> (Val, Article) {
+        let (ss, diagnostics, cst) = Article::split(tks);
+        let order = (0..ss.len()).collect();
+        self.run(tks, (ss, diagnostics, cst), order, false, rt)
+    }
+
+    /// like `update`, but each sentence is calculated after the sentences
+    /// assigning what it reads, see `DepGraph`
+    pub fn update_reactive(
+        &mut self,
+        tks: &[Token],
+        rt: &mut Runtime,
+    ) -> Result<(Val, Article), DepError> {
+        let (ss, diagnostics, cst) = Article::split(tks);
+        let order = DepGraph::from(ss.iter().map(|s| &s.0)).order()?;
+        Ok(self.run(tks, (ss, diagnostics, cst), order, true, rt))
+    }
+
+    fn run(
+        &mut self,
+        tks: &[Token],
+        (ss, diagnostics, cst): Split,
+        order: Vec<usize>,
+        reactive: bool,
+        rt: &mut Runtime,
+    ) -> (Val, Article) {
+        rt.restart();
+        let old = std::mem::take(&mut self.cells);
+        self.recomputed = 0;
+        self.diagnostics = diagnostics;
+        let spans = ss.iter().map(|(_, _, span)| *span).collect();
+
+        // old cells matched in the order written, the others are deleted or moved away;
+        // the cells of each key are only looked up after an edit
+        let mut by_key: Option<HashMap<&str, Vec<usize>>> = None;
+        let mut matched = vec![];
+        let mut kept = vec![false; old.len()];
+        let mut next = 0;
+        let mut sentences = vec![];
+        for (sentence, range, _) in ss {
+            let mut key = String::new();
+            for t in &tks[range] {
+                if !t.is_skipped() {
+                    key += t.lexeme();
+                    key.push('\n');
+                } else if t.lexeme().contains('\n') {
+                    key.push('\n');
+                }
+            }
+            let found = if old.get(next).is_some_and(|c| c.key == key) {
+                Some(next)
+            } else {
+                let by_key = by_key.get_or_insert_with(|| {
+                    let mut res: HashMap<&str, Vec<usize>> = HashMap::new();
+                    for (k, c) in old.iter().enumerate() {
+                        res.entry(&c.key).or_default().push(k);
+                    }
+                    res
+                });
+                by_key
+                    .get(key.as_str())
+                    .and_then(|ks| ks.get(ks.partition_point(|&k| k < next)).copied())
+            };
+            if let Some(k) = found {
+                next = k + 1;
+                kept[k] = true;
+            }
+            matched.push(found);
+            sentences.push(Some((key, sentence)));
+        }
+        // old cells with output before each one that are gone
+        let mut lost = vec![0; old.len() + 1];
+        for (k, c) in old.iter().enumerate() {
+            lost[k + 1] = lost[k] + (!kept[k] && c.has_output()) as usize;
+        }
+        // for each variable, the ranks of the old cells assigning it in order,
+        // from the first not calculated yet, and the last rank of those calculated
+        let mut waiting: HashMap<&str, (Vec<usize>, usize)> = HashMap::new();
+        let mut last_done: HashMap<&str, usize> = HashMap::new();
+        // old cells calculated so far, by rank
+        let mut done = vec![false; old.len()];
+        for c in &old {
+            for n in c.writes.iter() {
+                waiting.entry(n).or_default().0.push(c.rank);
+            }
+        }
+        waiting.values_mut().for_each(|(r, _)| r.sort_unstable());
+        // variables that may differ from the cached run
+        let mut dirty = HashSet::new();
+        // cells written before each one whose output may differ from the cached run
+        let mut changed = Counts::new(sentences.len());
+        for (i, m) in matched.iter().enumerate() {
+            if m.is_some_and(|m| old[m].has_output()) {
+                changed.add(i, 1);
+            }
+        }
+        // an `include` before may assign anything
+        let mut included = false;
+        let mut cells: Vec<Option<Cell>> = (0..sentences.len()).map(|_| None).collect();
+        let mut parsed: Vec<Option<Sentence>> = (0..sentences.len()).map(|_| None).collect();
+        // in reactive mode the history holds the outputs of the cells before `upto`,
+        // and `lens[j]` its length with those before `j`
+        let mut upto = 0;
+        let mut lens = vec![rt.history_len()];
+        for (rank, i) in order.into_iter().enumerate() {
+            let (key, sentence) = sentences[i].take().unwrap();
+            if reactive && reads_history(&sentence, rt) {
+                // the outputs written before the sentence, which are all calculated
+                upto = upto.min(i);
+                rt.truncate_history(lens[upto]);
+                lens.truncate(upto + 1);
+                for c in &cells[upto..i] {
+                    if let Some(c) = c {
+                        rt.record(&c.result);
+                    }
+                    lens.push(rt.history_len());
+                }
+                upto = i;
+            }
+            let recorded = rt.history_len();
+            let cached = matched[i].filter(|&k| {
+                let c = &old[k];
+                if included || c.includes {
+                    return false;
+                }
+                // assigned by old cells calculated before it then but not now, or now but not then
+                let moved = |n: &str| {
+                    waiting
+                        .get(n)
+                        .and_then(|(r, first)| r.get(*first))
+                        .is_some_and(|&r| r < c.rank)
+                        || last_done.get(n).is_some_and(|&r| r > c.rank)
+                };
+                if any_read(&c.reads, rt, |n| dirty.contains(n) || moved(n)) {
+                    return false;
+                }
+                // the outputs written before it are those of the cached run
+                !c.reads.iter().any(|n| is_history(n)) || lost[k] == 0 && changed.before(i) == 0
+            });
+            let cell = match cached.map(|k| &old[k]) {
+                Some(c) => {
+                    for (name, val) in c.effects.iter() {
+                        if let Some(val) = val {
+                            rt.set_val(name.clone(), val.clone());
+                        }
+                    }
+                    for e in c.errors.iter() {
+                        rt.add_call_error(e.clone());
+                    }
+                    rt.record(&c.result);
+                    Cell {
+                        key,
+                        includes: c.includes,
+                        reads: Rc::clone(&c.reads),
+                        writes: Rc::clone(&c.writes),
+                        effects: Rc::clone(&c.effects),
+                        errors: Rc::clone(&c.errors),
+                        result: c.result.clone(),
+                        rank,
+                    }
+                }
+                None => {
+                    let cell = Cell::calc(key, &sentence, rank, rt);
+                    self.recomputed += 1;
+                    dirty.extend(cell.writes.iter().cloned());
+                    cell
+                }
+            };
+            let was = matched[i].is_some_and(|m| old[m].has_output());
+            let now = cached.is_none() && (was || cell.has_output());
+            changed.add(i, now as isize - was as isize);
+            if let Some(k) = matched[i] {
+                let o = &old[k];
+                done[o.rank] = true;
+                for n in o.writes.iter() {
+                    if let Some((r, first)) = waiting.get_mut(n.as_str()) {
+                        while r.get(*first).is_some_and(|&r| done[r]) {
+                            *first += 1;
+                        }
+                    }
+                    let last = last_done.entry(n).or_default();
+                    *last = o.rank.max(*last);
+                }
+            }
+            if reactive {
+                rt.truncate_history(recorded);
+                if i < upto && cell.has_output() {
+                    upto = i;
+                    rt.truncate_history(lens[i]);
+                    lens.truncate(i + 1);
+                }
+            }
+            included |= cell.includes;
+            cells[i] = Some(cell);
+            parsed[i] = Some(sentence);
+        }
+        self.cells = cells.into_iter().flatten().collect();
+        if reactive {
+            rt.restart_history();
+            self.cells.iter().for_each(|c| rt.record(&c.result));
+        }
+        let res = Val::Vars(Tuple(self.cells.iter().map(|c| c.result.clone()).collect()));
+        let article = Article {
+            sentences: parsed.into_iter().flatten().collect(),
+            spans,
+            cst,
+        };
+        (res, article)
+    }
+}
+
+impl Cell {
+    fn has_output(&self) -> bool {
+        !self.result.leaves().is_empty()
+    }
+
+    fn calc(key: String, sentence: &Sentence, rank: usize, rt: &mut Runtime) -> Self {
+        let mut reads = HashSet::new();
+        let mut writes = HashSet::new();
+        sentence.reads(&mut reads);
+        sentence.writes(&mut writes);
+        if let Sentence::Block(_) = *sentence {
+            // assignments in a block reach outside only when the variable exists there
+            reads.extend(writes.iter().cloned());
+        }
+        let before = rt.take_call_errors();
+        let result = sentence.calc(rt);
+        let errors: Rc<[CallError]> = rt.take_call_errors().into();
+        for e in before.into_iter().chain(errors.iter().cloned()) {
+            rt.add_call_error(e);
+        }
+        let effects = writes.iter().map(|n| (n.clone(), rt.lookup(n))).collect();
+        Cell {
+            key,
+            includes: sentence.includes(),
+            reads: Rc::new(reads),
+            writes: Rc::new(writes),
+            effects,
+            errors,
+            result,
+            rank,
+        }
+    }
+}
+
+/// the sentence reads `ans` or `out`, itself or in the functions it calls
+fn reads_history(sentence: &Sentence, rt: &Runtime) -> bool {
+    let mut reads = HashSet::new();
+    sentence.reads(&mut reads);
+    any_read(&reads, rt, is_history)
+}
+
+/// counts at indices, summed over the indices before one
+struct Counts(Vec<isize>);
+
+impl Counts {
+    fn new(len: usize) -> Self {
+        Counts(vec![0; len + 1])
+    }
+    fn add(&mut self, i: usize, n: isize) {
+        let mut i = i + 1;
+        while i < self.0.len() {
+            self.0[i] += n;
+            i += i & i.wrapping_neg();
+        }
+    }
+    /// the sum of the counts before `i`
+    fn before(&self, mut i: usize) -> isize {
+        let mut res = 0;
+        while i > 0 {
+            res += self.0[i];
+            i -= i & i.wrapping_neg();
+        }
+        res
+    }
+}
+
+/// `f` holds for one of `reads`, or of the variables read by the user functions among them
+fn any_read(reads: &HashSet<String>, rt: &Runtime, mut f: impl FnMut(&str) -> bool) -> bool {
+    if reads.iter().any(|n| f(n)) {
+        return true;
+    }
+    let user = |n: &str| match rt.lookup(n) {
+        Some(Val::Fun(Fun::User(c))) => Some(c),
+        _ => None,
+    };
+    let mut stack: Vec<_> = reads.iter().filter_map(|n| user(n)).collect();
+    let mut seen = HashSet::new();
+    while let Some(c) = stack.pop() {
+        let mut body = HashSet::new();
+        c.body.reads(&mut body);
+        for n in body {
+            if c.params.contains(&n) || reads.contains(&n) || !seen.insert(n.clone()) {
+                continue;
+            }
+            if f(&n) {
+                return true;
+            }
+            stack.extend(user(&n));
+        }
+    }
+    false
+}
@@ -0,0 +1,247 @@
+use js_sys::{Array, Object};
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    my_include::IncludeError,
+    my_js::{set, span_to_js},
+    my_math::{Im, Re, Tuple, Val},
+    my_parser::Diagnostic,
+    my_runtime::{CallError, Runtime, System},
+};
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_RESULTS: &'static str = r#"
+export type OutputKind = "real" | "complex" | "function" | "text" | "svg";
+
+/** a value written as `[out n]` */
+export interface Output {
+    /** `n` of `[out n]` */
+    index: number;
+    kind: OutputKind;
+    /** `[re]`, `[re, im]`, or empty for functions and texts */
+    value: number[];
+    /** by the format settings, not escaped as in `get_html` */
+    text: string;
+    /** `[start, end)` of the sentence it comes from, in characters of the input */
+    span: [number, number];
+}
+
+export interface OutputError {
+    kind: "syntax" | "include" | "call";
+    /** the included file, missing for the input */
+    file?: string;
+    /** counted from 1, missing for call errors */
+    line?: number;
+    /** counted from 1, missing for call errors */
+    column?: number;
+    /** `[start, end)` in characters of the input, missing for include errors */
+    span?: [number, number];
+    message: string;
+    /** for an unknown name, defined names like it, closest first */
+    suggestions: string[];
+}
+
+export interface Results {
+    outputs: Output[];
+    errors: OutputError[];
+}
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    /// `{ outputs, errors }` as plain objects
+    #[wasm_bindgen(typescript_type = "Results")]
+    pub type Results;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputKind {
+    Real,
+    Complex,
+    Function,
+    Text,
+    Svg,
+}
+
+impl OutputKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            OutputKind::Real => "real",
+            OutputKind::Complex => "complex",
+            OutputKind::Function => "function",
+            OutputKind::Text => "text",
+            OutputKind::Svg => "svg",
+        }
+    }
+}
+
+/// a value written as `[out n]`, with the sentence it comes from
+#[derive(Debug, Clone, PartialEq)]
+pub struct Output {
+    /// `n` of `[out n]`
+    pub index: usize,
+    pub kind: OutputKind,
+    /// `[re]`, `[re, im]`, or empty for functions and texts
+    pub value: Vec<f64>,
+    /// by the format settings, not escaped as in `get_html`
+    pub text: String,
+    /// [start, end] of the sentence in the input
+    pub span: [usize; 2],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// a malformed sentence or token
+    Syntax,
+    /// a file that cannot be found or has errors
+    Include,
+    /// a function that failed or got wrong arguments
+    Call,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputError {
+    pub kind: ErrorKind,
+    /// the included file, `None` for the input
+    pub file: Option<String>,
+    /// [line, column], both counted from 0, `None` for call errors
+    pub line_colum: Option<[usize; 2]>,
+    /// [start, end] in the input, `None` for include and call errors
+    pub span: Option<[usize; 2]>,
+    pub message: String,
+    /// for an unknown name, defined names like it, closest first
+    pub suggestions: Vec<String>,
+}
+
+/// the outputs of `res`, one value per sentence of `spans`,
+/// numbered after the `first` outputs before them
+pub fn outputs(res: &Val, spans: &[[usize; 2]], first: usize, sys: &System) -> Vec<Output> {
+    let Val::Vars(Tuple(vals)) = res else {
+        return vec![];
+    };
+    let mut res = vec![];
+    for (v, &span) in vals.iter().zip(spans) {
+        for v in v.leaves() {
+            let (kind, value) = match &v {
+                Val::Re(Re(x)) => (OutputKind::Real, vec![*x]),
+                Val::Im(Im(u, v)) => (OutputKind::Complex, vec![*u, *v]),
+                Val::Text(_) => (OutputKind::Text, vec![]),
+                Val::Svg(_) => (OutputKind::Svg, vec![]),
+                // leaves are never tuples
+                _ => (OutputKind::Function, vec![]),
+            };
+            res.push(Output {
+                index: first + res.len() + 1,
+                kind,
+                value,
+                text: v.text(sys),
+                span,
+            });
+        }
+    }
+    res
+}
+
+/// malformed sentences, `include` errors and failed functions, in this order
+pub fn errors(diagnostics: &[Diagnostic], rt: &Runtime) -> Vec<OutputError> {
+    let include = rt.include.errors().iter().map(OutputError::from);
+    let calls = rt.call_errors().iter().map(OutputError::from);
+    diagnostics
+        .iter()
+        .map(OutputError::from)
+        .chain(include)
+        .chain(calls)
+        .collect()
+}
+
+impl From<&Diagnostic> for OutputError {
+    fn from(d: &Diagnostic) -> Self {
+        OutputError {
+            kind: ErrorKind::Syntax,
+            file: None,
+            line_colum: Some(d.line_colum),
+            span: Some(d.span),
+            message: d.message.clone(),
+            suggestions: vec![],
+        }
+    }
+}
+
+impl From<&IncludeError> for OutputError {
+    fn from(e: &IncludeError) -> Self {
+        OutputError {
+            kind: ErrorKind::Include,
+            file: e.file.clone(),
+            line_colum: Some(e.line_colum),
+            span: None,
+            message: e.message.clone(),
+            suggestions: vec![],
+        }
+    }
+}
+
+impl From<&CallError> for OutputError {
+    fn from(e: &CallError) -> Self {
+        OutputError {
+            kind: ErrorKind::Call,
+            file: None,
+            line_colum: None,
+            span: None,
+            message: e.to_string(),
+            suggestions: e.suggestions.clone(),
+        }
+    }
+}
+
+/// `{ outputs, errors }` as the `Results` of the typescript definitions
+pub fn to_js(outputs: &[Output], errors: &[OutputError]) -> Results {
+    let res = Object::new();
+    let outputs: Array = outputs.iter().map(Output::to_js).collect();
+    let errors: Array = errors.iter().map(OutputError::to_js).collect();
+    set(&res, "outputs", outputs.into());
+    set(&res, "errors", errors.into());
+    res.unchecked_into()
+}
+
+impl Output {
+    fn to_js(&self) -> Object {
+        let res = Object::new();
+        set(&res, "index", (self.index as u32).into());
+        set(&res, "kind", self.kind.name().into());
+        let value: Array = self.value.iter().map(|&x| JsValue::from(x)).collect();
+        set(&res, "value", value.into());
+        set(&res, "text", self.text.as_str().into());
+        set(&res, "span", span_to_js(self.span));
+        res
+    }
+}
+
+impl OutputError {
+    fn to_js(&self) -> Object {
+        let res = Object::new();
+        let kind = match self.kind {
+            ErrorKind::Syntax => "syntax",
+            ErrorKind::Include => "include",
+            ErrorKind::Call => "call",
+        };
+        set(&res, "kind", kind.into());
+        if let Some(file) = &self.file {
+            set(&res, "file", file.as_str().into());
+        }
+        if let Some([line, colum]) = self.line_colum {
+            set(&res, "line", (line as u32 + 1).into());
+            set(&res, "column", (colum as u32 + 1).into());
+        }
+        if let Some(span) = self.span {
+            set(&res, "span", span_to_js(span));
+        }
+        set(&res, "message", self.message.as_str().into());
+        let suggestions: Array = self
+            .suggestions
+            .iter()
+            .map(|s| JsValue::from_str(s))
+            .collect();
+        set(&res, "suggestions", suggestions.into());
+        res
+    }
+}
@@ -1,6 +1,10 @@
-use std::f64;
+use std::{collections::HashSet, f64, fmt, ops::Range};
+
+use unicode_xid::UnicodeXID;
 
 use crate::{
+    my_cst::{Cst, SyntaxKind},
+    my_highlight, my_include,
     my_math::{self, Val},
     my_runtime::Runtime,
 };
@@ -20,6 +24,8 @@ pub struct Token {
     line_colum: [usize; 2],
     // [start, end]
     pos: [usize; 2],
+    /// why the token is malformed
+    error: Option<String>,
 }
 
 #[repr(u8)]
@@ -86,7 +92,7 @@ pub enum TokenType {
     PlusPlus,
 
     // -------- Literals --------
-    /// [a-zA-Z_][a-zA-Z_0-9]*, not reserved words
+    /// XID_Start XID_Continue*, or `_`, not reserved words
     Identifier,
     /// [']([^'\\]|(\\.))[']
     Char,
@@ -100,6 +106,8 @@ pub enum TokenType {
     Number,
     /// true or false
     Bool,
+    /// malformed, the reason is in `Token::error`
+    Error,
 
     // -------- Keywords --------
 
@@ -130,6 +138,7 @@ pub enum TokenType {
 
     // End of file
     #[default]
+    #[allow(clippy::upper_case_acronyms)]
     EOF,
 }
 
@@ -169,12 +178,17 @@ impl LexicalParser {
             if t.is_eof() {
                 break;
             }
-            colum = t.line_colum[1] + t.pos[1] - t.pos[0];
-            offset = t.pos[1];
-            if t.is_newline() {
-                colum = 0;
-                line += 1;
+            // tokens like comments may span lines, columns count characters
+            [line, colum] = t.line_colum;
+            for &c in &utf8_slice[t.pos[0]..t.pos[1]] {
+                if c == '\n' {
+                    line += 1;
+                    colum = 0;
+                } else {
+                    colum += 1;
+                }
             }
+            offset = t.pos[1];
             self.tokens.push(t);
         }
     }
@@ -186,15 +200,56 @@ impl LexicalParser {
     pub fn parse(&self) -> Article {
         Article::from(&self.tokens)
     }
+
+    /// the article, skipping malformed sentences, and why they are malformed
+    pub fn parse_with_diagnostics(&self) -> (Article, Vec<Diagnostic>) {
+        Article::parse(&self.tokens)
+    }
+
+    pub fn tokens(&self) -> &[Token] {
+        &self.tokens
+    }
+
+    /// the text ends inside a `{ }` block or a `/* */` comment
+    pub fn unclosed(&self) -> bool {
+        let mut depth = 0i32;
+        for t in &self.tokens {
+            match t.token_type {
+                TokenType::LeftBrace => depth += 1,
+                TokenType::RightBrace => depth -= 1,
+                _ => {}
+            }
+        }
+        let comment = self
+            .tokens
+            .last()
+            .is_some_and(|t| t.token_type == TokenType::MuitiLineComment && t.error.is_some());
+        depth > 0 || comment
+    }
+
+    /// malformed tokens: unknown characters, bad numbers,
+    /// unterminated strings and comments
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        self.tokens.iter().filter_map(|t| t.diagnostic()).collect()
+    }
 }
 
 impl Token {
-    fn from(text: &[char], mut line_colum: [usize; 2], mut offset: usize) -> Self {
+    fn from(text: &[char], line_colum: [usize; 2], offset: usize) -> Self {
         let mut len = 0;
-        // skip whitespaces
-        while offset < text.len() && TokenType::Whitespace.is_char(text[offset]) {
-            line_colum[1] += 1;
-            offset += 1;
+        // whitespaces are kept, so the tokens spell the whole text
+        while offset + len < text.len() && TokenType::Whitespace.is_char(text[offset + len]) {
+            len += 1;
+        }
+        if len > 0 {
+            return Token {
+                token_type: TokenType::Whitespace,
+                lexeme: text[offset..offset + len].iter().collect(),
+                literal: None,
+                line_colum,
+                pos: [offset, offset + len],
+                error: None,
+            };
         }
         // judge EOF
         if offset >= text.len() {
@@ -204,17 +259,18 @@ impl Token {
                 literal: None,
                 line_colum,
                 pos: [offset, offset + len],
+                error: None,
             };
         }
         // judge NewLine
         if TokenType::NewLine.is_char(text[offset]) {
-            line_colum[0] += 1;
             return Token {
                 token_type: TokenType::NewLine,
                 lexeme: "\n".to_string(),
                 literal: None,
                 line_colum,
                 pos: [offset, offset + 1],
+                error: None,
             };
         }
         // judge Comments
@@ -231,6 +287,7 @@ impl Token {
                     literal: None,
                     line_colum,
                     pos: [offset, offset + len],
+                    error: None,
                 };
             } else if TokenType::Star.is_char(text[offset + 1]) {
                 // multi line comment
@@ -242,6 +299,7 @@ impl Token {
                         literal: None,
                         line_colum,
                         pos: [offset, text.len()],
+                        error: Some("unterminated comment".to_string()),
                     };
                 }
                 len += 3;
@@ -250,6 +308,8 @@ impl Token {
                 {
                     len += 1;
                 }
+                let error =
+                    (offset + len >= text.len()).then(|| "unterminated comment".to_string());
                 return Token {
                     token_type: TokenType::MuitiLineComment,
                     lexeme: text[offset..text.len().min(offset + len + 1)]
@@ -258,76 +318,10 @@ impl Token {
                     literal: None,
                     line_colum,
                     pos: [offset, text.len().min(offset + len + 1)],
+                    error,
                 };
             }
         }
-        // judge Keywords
-        match text[offset] {
-            't' if char_starts_with(text, offset, "true") => {
-                // text starts with true and text[4] is not alphanumeric
-                // true
-                return Token {
-                    token_type: TokenType::Bool,
-                    lexeme: text[offset..offset + 4].iter().collect(),
-                    literal: Literal::from_bool(true),
-                    line_colum,
-                    pos: [offset, offset + 4],
-                };
-            }
-            'f' if char_starts_with(text, offset, "false") => {
-                // text starts with true and text[4] is not alphanumeric
-                // false
-                return Token {
-                    token_type: TokenType::Bool,
-                    lexeme: text[offset..offset + 5].iter().collect(),
-                    literal: Literal::from_bool(false),
-                    line_colum,
-                    pos: [offset, offset + 5],
-                };
-            }
-            'i' if char_starts_with(text, offset, "i") => {
-                // number starts with true and text[1] is not alphanumeric
-                // I
-                return Token {
-                    token_type: TokenType::Number,
-                    lexeme: text[offset..offset + 1].iter().collect(),
-                    literal: Some(Literal::Number(1.0, true)),
-                    line_colum,
-                    pos: [offset, offset + 1],
-                };
-            }
-            'e' if char_starts_with(text, offset, "e") => {
-                // number starts with true and text[1] is not alphanumeric
-                // E
-                return Token {
-                    token_type: TokenType::Number,
-                    lexeme: text[offset..offset + 1].iter().collect(),
-                    literal: Some(Literal::Number(std::f64::consts::E, false)),
-                    line_colum,
-                    pos: [offset, offset + 1],};
-            }
-            'p' if char_starts_with(text, offset, "pi") => {
-                // number starts with true and text[2] is not alphanumeric
-                // Pi
-                return Token {
-                    token_type: TokenType::Number,
-                    lexeme: text[offset..offset + 2].iter().collect(),
-                    literal: Some(Literal::Number(std::f64::consts::PI, false)),
-                    line_colum,
-                    pos: [offset, offset + 2],};
-            }
-            'π' => {
-                // number starts with true and text[2] is not alphanumeric
-                // Pi
-                return Token {
-                    token_type: TokenType::Number,
-                    lexeme: text[offset..offset + 1].iter().collect(),
-                    literal: Some(Literal::Number(std::f64::consts::PI, false)),
-                    line_colum,
-                    pos: [offset, offset + 1],};
-            }
-            _ => {}
-        }
         // others mainly literals
         match text[offset] {
             '\'' if Some(&'\\') != text.get(offset + 1) && Some(&'\'') == text.get(offset + 2) => {
@@ -338,6 +332,7 @@ impl Token {
                     literal: Literal::from_char(text[offset + 1]),
                     line_colum,
                     pos: [offset, offset + 3],
+                    error: None,
                 }
             }
             '\'' if Some(&'\\') == text.get(offset + 1) && Some(&'\'') == text.get(offset + 3) => {
@@ -348,6 +343,7 @@ impl Token {
                     literal: Literal::from_slash_char(text[offset + 2]),
                     line_colum,
                     pos: [offset, offset + 4],
+                    error: None,
                 }
             }
             '"' => {
@@ -369,18 +365,50 @@ impl Token {
                     }
                     len += 1;
                 }
-                let lexeme = text[offset..text.len().min(offset + len + 1)]
-                    .iter()
-                    .collect();
+                if text.get(offset + len) != Some(&'"') {
+                    return Token {
+                        token_type: TokenType::Error,
+                        lexeme: text[offset..offset + len].iter().collect(),
+                        literal: None,
+                        line_colum,
+                        pos: [offset, offset + len],
+                        error: Some("unterminated string".to_string()),
+                    };
+                }
+                let lexeme = text[offset..offset + len + 1].iter().collect();
                 Token {
                     token_type: TokenType::String,
                     lexeme,
                     literal: Literal::from_char_vec(literal),
                     line_colum,
-                    pos: [offset, text.len().min(offset + len + 1)],
+                    pos: [offset, offset + len + 1],
+                    error: None,
+                }
+            }
+            '√' => {
+                // a prefix square root, parsed as `sqrt`
+                Token {
+                    token_type: TokenType::Identifier,
+                    lexeme: "√".to_string(),
+                    literal: Some(Literal::Identifier(Identifier::new("sqrt".to_string()))),
+                    line_colum,
+                    pos: [offset, offset + 1],
+                    error: None,
+                }
+            }
+            '²' | '³' => {
+                // `x²` is `x ^ 2`
+                let d = if text[offset] == '²' { 2.0 } else { 3.0 };
+                Token {
+                    token_type: TokenType::Caret,
+                    lexeme: text[offset].to_string(),
+                    literal: Some(Literal::Number(d, false)),
+                    line_colum,
+                    pos: [offset, offset + 1],
+                    error: None,
                 }
             }
-            'a'..='z' | 'A'..='Z' | '_' => {
+            c if c == '_' || c.is_xid_start() => {
                 // Identifier
                 len = 1;
                 while offset + len < text.len() && TokenType::Identifier.is_char(text[offset + len])
@@ -388,6 +416,16 @@ impl Token {
                     len += 1;
                 }
                 let lexeme: String = text[offset..offset + len].iter().collect();
+                if let Some(token_type) = keyword(&lexeme) {
+                    return Token {
+                        token_type,
+                        literal: Literal::from_bool(lexeme == "true"),
+                        lexeme,
+                        line_colum,
+                        pos: [offset, offset + len],
+                        error: None,
+                    };
+                }
                 let literal = Some(Literal::Identifier(Identifier::new(lexeme.clone())));
                 Token {
                     token_type: TokenType::Identifier,
@@ -395,6 +433,7 @@ impl Token {
                     literal,
                     line_colum,
                     pos: [offset, offset + len],
+                    error: None,
                 }
             }
             '0'..='9' => {
@@ -414,8 +453,8 @@ impl Token {
                         len += 1;
                     }
                 }
-                if (text[offset + len - 1] == 'E' || text[offset + len - 1] == 'e')
-                    && (text[offset + len] == '+' || text[offset + len] == '-')
+                if matches!(text[offset + len - 1], 'E' | 'e')
+                    && matches!(text.get(offset + len), Some('+' | '-'))
                 {
                     // with expontional part
                     len += 1;
@@ -425,20 +464,32 @@ impl Token {
                     }
                 }
                 let lexeme: String = text[offset..offset + len].iter().collect();
-                let literal = Literal::from_number(&lexeme);
-
-                return Token {
-                    token_type: TokenType::Number,
-                    lexeme,
-                    literal,
-                    line_colum,
-                    pos: [offset, offset + len],
-                };
+                match Literal::from_number(&lexeme) {
+                    Ok(literal) => Token {
+                        token_type: TokenType::Number,
+                        lexeme,
+                        literal: Some(literal),
+                        line_colum,
+                        pos: [offset, offset + len],
+                        error: None,
+                    },
+                    Err(e) => Token {
+                        token_type: TokenType::Error,
+                        lexeme,
+                        literal: None,
+                        line_colum,
+                        pos: [offset, offset + len],
+                        error: Some(e),
+                    },
+                }
             }
             _ => {
                 if let Some(t) = TokenType::from_char(text[offset]) {
                     len = 1;
+                    let ascii = text[offset].is_ascii();
                     let token_type = match t {
+                        // `−-` is not `--`
+                        _ if !ascii => t,
                         TokenType::Plus if Some(&'+') == text.get(offset + 1) => {
                             len = 2;
                             TokenType::PlusPlus
@@ -471,54 +522,61 @@ impl Token {
                         literal: None,
                         line_colum,
                         pos: [offset, offset + len],
+                        error: None,
                     }
                 } else {
-                    Token::default()
+                    Token {
+                        token_type: TokenType::Error,
+                        lexeme: text[offset].to_string(),
+                        literal: None,
+                        line_colum,
+                        pos: [offset, offset + 1],
+                        error: Some(format!("unexpected character `{}`", text[offset])),
+                    }
                 }
             }
         }
     }
 
-    fn is_literal(&self) -> bool {
-        match self.token_type {
-            TokenType::Char | TokenType::String | TokenType::Number | TokenType::Bool => true,
-            _ => false,
-        }
-    }
-    fn is_identifier(&self) -> bool {
-        self.token_type == TokenType::Identifier
-    }
-    fn is_assign(&self) -> bool {
-        self.token_type == TokenType::Equal
-    }
-    fn is_pos_neg(&self) -> bool {
-        self.token_type == TokenType::Plus || self.token_type == TokenType::Minus
-    }
     fn is_eof(&self) -> bool {
         self.token_type == TokenType::EOF
     }
-    fn is_calc_op(&self) -> bool {
-        match self.token_type {
-            TokenType::Plus
-            | TokenType::Minus
-            | TokenType::Star
-            | TokenType::Slash
-            | TokenType::Percent
-            | TokenType::Caret => true,
-            _ => false,
-        }
-    }
     fn is_newline(&self) -> bool {
         self.token_type == TokenType::NewLine
     }
-    fn is_skipped(&self) -> bool {
-        match self.token_type {
+    pub(crate) fn is_skipped(&self) -> bool {
+        matches!(
+            self.token_type,
             TokenType::Whitespace
-            | TokenType::NewLine
-            | TokenType::MuitiLineComment
-            | TokenType::SingleLineComment => true,
-            _ => false,
-        }
+                | TokenType::NewLine
+                | TokenType::MuitiLineComment
+                | TokenType::SingleLineComment
+        )
+    }
+    pub fn lexeme(&self) -> &str {
+        &self.lexeme
+    }
+    /// malformed, like unknown characters and unterminated strings
+    pub fn has_error(&self) -> bool {
+        self.error.is_some()
+    }
+    pub fn token_type(&self) -> TokenType {
+        self.token_type
+    }
+    /// [start, end] in characters of the input
+    pub fn pos(&self) -> [usize; 2] {
+        self.pos
+    }
+    /// [line, column] of the start, both counted from 0
+    pub fn line_colum(&self) -> [usize; 2] {
+        self.line_colum
+    }
+    fn diagnostic(&self) -> Option<Diagnostic> {
+        Some(Diagnostic {
+            span: self.pos,
+            line_colum: self.line_colum,
+            message: self.error.clone()?,
+        })
     }
     fn print(&self, level: usize) -> String {
         match level {
@@ -571,10 +629,17 @@ impl TokenType {
             '=' => Some(TokenType::Equal),
             '>' => Some(TokenType::Greater),
             '<' => Some(TokenType::Less),
+            // math glyphs
+            '×' | '·' | '⋅' => Some(TokenType::Star),
+            '÷' => Some(TokenType::Slash),
+            '−' => Some(TokenType::Minus),
+            '≤' => Some(TokenType::LessEqual),
+            '≥' => Some(TokenType::GreaterEqual),
+            '≠' => Some(TokenType::BangEqual),
             _ => None,
         }
     }
-    pub fn to_char(&self) -> char {
+    pub fn to_char(self) -> char {
         match self {
             TokenType::LeftParen => '(',
             TokenType::RightParen => ')',
@@ -599,23 +664,25 @@ impl TokenType {
             _ => '\0',
         }
     }
+    /// how an operator is printed, `<=` for `≤`
+    pub fn symbol(self) -> String {
+        match self {
+            TokenType::BangEqual => "!=".to_string(),
+            TokenType::EqualEqual => "==".to_string(),
+            TokenType::GreaterEqual => ">=".to_string(),
+            TokenType::LessEqual => "<=".to_string(),
+            _ => self.to_char().to_string(),
+        }
+    }
     pub fn is_char(&self, c: char) -> bool {
         match self {
             TokenType::Whitespace => c == ' ' || c == '\t' || c == '\r',
             TokenType::NewLine => c == '\n',
-            TokenType::Identifier => c.is_ascii_alphanumeric() || c == '_',
+            TokenType::Identifier => c.is_xid_continue(),
             TokenType::Number => c.is_ascii_alphanumeric() || c == '_',
             _ => c == self.to_char(),
         }
     }
-
-    pub fn get_priority(&self) -> usize {
-        match self {
-            Self::Plus | Self::Minus => 1,
-            Self::Star | Self::Slash | Self::Percent => 2,
-            _ => 0,
-        }
-    }
 }
 
 impl Literal {
@@ -625,9 +692,9 @@ impl Literal {
     pub fn from_slash_char(c: char) -> Option<Self> {
         Some(Literal::Char(slash_char(c)))
     }
-    pub fn from_number(n: &String) -> Option<Self> {
-        println!("num: {n}");
-        let mut n = n.replace('_', "").to_lowercase();
+    /// the reason is returned for malformed numbers
+    pub fn from_number(text: &str) -> Result<Self, String> {
+        let mut n = text.replace('_', "").to_lowercase();
         let mut radix = 10;
         let mut integer = false;
         let mut error = false;
@@ -705,9 +772,9 @@ impl Literal {
             }
         };
         if error {
-            println!("Number parse Error!");
+            return Err(format!("invalid number `{text}`"));
         }
-        Some(Literal::Number(d))
+        Ok(Literal::Number(d, false))
     }
     pub fn from_char_vec(cs: Vec<char>) -> Option<Self> {
         let s: String = cs.into_iter().collect();
@@ -742,30 +809,18 @@ impl Literal {
                 Literal::Identifier(i) => i.name.clone(),
                 Literal::Char(c) => c.to_string(),
                 Literal::String(s) => s.to_owned(),
-                Literal::Number(d) => d.to_string(),
+                Literal::Number(d, false) => d.to_string(),
+                Literal::Number(d, true) => format!("{d}i"),
                 Literal::Bool(b) => b.to_string(),
             }
-        } else if level < 10 {
+        } else {
             match self {
                 Literal::Identifier(i) => format!("<{}>", i.name.clone()),
-                Literal::Char(c) => format!("'{}'", c.to_string()),
+                Literal::Char(c) => format!("'{}'", c),
                 Literal::String(s) => format!("\"{}\"", s.to_owned()),
-                Literal::Number(d) => format!("{}", d.to_string()),
-                Literal::Bool(b) => format!("{}", b.to_string()),
-            }
-        } else {
-            match self {
-                Literal::Identifier(i) => {
-                    format!("<span class='syntax_identifier'>{}</span>", i.name.clone())
-                }
-                Literal::Char(c) => format!("<span class='syntax_char'>'{}'</span>", c.to_string()),
-                Literal::String(s) => {
-                    format!("<span class='syntax_string'>\"{}\"</span>", s.to_owned())
-                }
-                Literal::Number(d) => {
-                    format!("<span class='syntax_number'>{}</span>", d.to_string())
-                }
-                Literal::Bool(b) => format!("<span class='syntax_bool'>{}</span>", b.to_string()),
+                Literal::Number(d, false) => format!("{}", d),
+                Literal::Number(d, true) => format!("{}i", d),
+                Literal::Bool(b) => format!("{}", b),
             }
         }
     }
@@ -794,194 +849,180 @@ fn slash_char(c: char) -> char {
     }
 }
 
-fn char_starts_with(c: &[char], offset: usize, s: &str) -> bool {
-    if c.len() < s.len() + offset
-        || c.get(offset + s.len())
-            .is_some_and(|c| c.is_ascii_alphanumeric())
-    {
-        return false;
-    }
-    let mut i = 0;
-    for c0 in s.chars() {
-        if c0 != c[offset + i] {
-            return false;
-        }
-        i += 1;
-    }
-    true
+/// words that are never identifiers, constants like `pi` live in `System`
+const KEYWORDS: [(&str, TokenType); 2] = [("true", TokenType::Bool), ("false", TokenType::Bool)];
+
+fn keyword(word: &str) -> Option<TokenType> {
+    KEYWORDS.iter().find(|(k, _)| *k == word).map(|(_, t)| *t)
 }
 
 // ----------- syntax parser ------------------- //
 
-#[derive(Clone)]
-pub enum Article {
-    Sentences(Vec<Sentence>),
+/// brackets, blocks and unary operators nested deeper than this are not parsed
+const MAX_NESTING: usize = 256;
+/// binding power of unary `+` and `-`, between `*` and `^`
+const PREFIX_BP: u8 = 7;
+
+/// a problem found while parsing
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    /// [start, end] in the input, like `Token::pos`
+    pub span: [usize; 2],
+    /// [line, column] of the start, both counted from 0
+    pub line_colum: [usize; 2],
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}",
+            self.line_colum[0] + 1,
+            self.line_colum[1] + 1,
+            self.message
+        )
+    }
+}
+
+/// sentences with the range of tokens each one is parsed from,
+/// and its [start, end] in the input without the tokens skipped before it
+pub type SentenceRanges = Vec<(Sentence, Range<usize>, [usize; 2])>;
+
+#[derive(Clone, Debug)]
+pub struct Article {
+    pub sentences: Vec<Sentence>,
+    /// [start, end] of each sentence in the input
+    pub spans: Vec<[usize; 2]>,
+    /// the text it is parsed from, with whitespaces and comments
+    pub cst: Cst,
 }
 
 impl Article {
     pub fn from(tks: &[Token]) -> Self {
+        Self::parse(tks).0
+    }
+
+    /// the well-formed sentences and a diagnostic for each malformed one
+    pub fn parse(tks: &[Token]) -> (Self, Vec<Diagnostic>) {
+        let (ss, diagnostics, cst) = Self::split(tks);
+        let (sentences, spans) = ss.into_iter().map(|(s, _, span)| (s, span)).unzip();
+        (
+            Article {
+                sentences,
+                spans,
+                cst,
+            },
+            diagnostics,
+        )
+    }
+
+    /// sentences with the range of tokens each one is parsed from.
+    /// malformed sentences are skipped up to the next `;`, new line or `}`
+    pub fn split(tks: &[Token]) -> (SentenceRanges, Vec<Diagnostic>, Cst) {
+        let mut p = Parser::new(tks);
         let mut res = vec![];
-        let mut offset = 0;
-        while let Some((s, len)) = Sentence::from(&tks[offset..]) {
-            offset += len;
-            res.push(s);
+        let mut start = 0;
+        while let Some(s) = p.next(false) {
+            let end = p.end();
+            res.push((s, start..end, p.span_from(p.begun)));
+            start = end;
+        }
+        let mut spans = std::mem::take(&mut p.nodes);
+        for (s, ..) in &res {
+            s.nodes(&mut spans);
+        }
+        let cst = Cst::new(tks, spans);
+        // malformed tokens the parser did not stop at, like those in comments
+        let mut diagnostics = p.diagnostics;
+        let mut reported: HashSet<[usize; 2]> = diagnostics.iter().map(|d| d.span).collect();
+        for d in tks.iter().filter_map(|t| t.diagnostic()) {
+            if reported.insert(d.span) {
+                diagnostics.push(d);
+            }
         }
-        Article::Sentences(res)
+        diagnostics.sort_by_key(|d| d.span[0]);
+        (res, diagnostics, cst)
     }
 
-    // level 11: with html
+    /// the text as written, level 11: with html
     pub fn print(&self, level: usize) -> String {
-        match self {
-            Article::Sentences(ss) => {
-                let mut res = String::new();
-                for s in ss {
-                    res += &s.print(level);
-                }
-                if level == 11 {
-                    format!("<span class='syntax_article_stntences'>{}</span>", res)
-                } else {
-                    res
-                }
-            }
-        }
+        self.cst.print(level)
     }
 
     pub fn tree(&self, level: usize, html: bool) -> String {
-        match self {
-            Article::Sentences(ss) => {
-                let mut res = format!("+Article Sentences {}", ss.len());
-                for s in ss {
-                    res += "\n";
-                    res += &(INDENT.repeat(level) + "+---" + &s.tree(level + 1, html));
-                }
-                res
-            }
+        let ss = &self.sentences;
+        let mut res = format!("+Article Sentences {}", ss.len());
+        for s in ss {
+            res += "\n";
+            res += &(INDENT.repeat(level) + "+---" + &s.tree(level + 1, html));
         }
+        res
     }
 
     pub fn calc(&self, rt: &mut Runtime) -> Val {
-        match self {
-            Self::Sentences(ss) => {
-                let mut res = vec![];
-                for i in ss {
-                    res.push(i.calc(rt));
-                }
-                Val::Vars(my_math::Tuple(res))
-            }
+        let mut res = vec![];
+        for i in &self.sentences {
+            res.push(i.calc(rt));
         }
+        Val::Vars(my_math::Tuple(res))
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub enum Sentence {
     AssignmentExp(AssignmentExp),
+    /// f(x, y) = ...
+    FunctionDef(FunctionDef),
     Expression(Expression),
     /// ;
     Seperator,
     // {Stentence}
     Block(Vec<Sentence>),
+    /// include("file")
+    Include(Include),
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct AssignmentExp(Identifier, Expression);
 
-impl Sentence {
-    pub fn from(tks: &[Token]) -> Option<(Self, usize)> {
-        let mut offset = 0;
-        // 0 -> Ok: Seperator
-        // 0 -> 1 : {
-        // 1 -> 1 : Stentence
-        // 1 -> 2 : !Stentence
-        // 2 -> Ok: }
-        // 0 -> Ok: AssignmentExp
-        // 0 -> Ok: Expression
-        let mut state = 0;
-        let mut res = None;
-        let mut blocks = vec![];
-        while offset < tks.len() && !tks[offset].is_eof() {
-            if tks[offset].is_skipped() {
-                offset += 1;
-                continue;
-            }
-            match state {
-                0 if tks[offset].token_type.is_char(';') => {
-                    offset += 1;
-                    res = Some((Sentence::Seperator, offset));
-                    return res;
-                }
-                0 if tks[offset].token_type.is_char('{') => {
-                    offset += 1;
-                    state = 1;
-                    continue;
-                }
-                0 => {
-                    if let Some((asexp, len)) = AssignmentExp::from(&tks[offset..]) {
-                        offset += len;
-                        res = Some((Sentence::AssignmentExp(asexp), offset));
-                        return res;
-                    } else if let Some((exp, len)) = Expression::from(&tks[offset..]) {
-                        offset += len;
-                        res = Some((Sentence::Expression(exp), offset));
-                        return res;
-                    }
-                }
-                1 => {
-                    while let Some((st, len)) = Sentence::from(&tks[offset..]) {
-                        blocks.push(st);
-                        offset += len;
-                    }
-                    state = 2;
-                    continue;
-                }
-                2 if tks[offset].token_type.is_char('}') => {
-                    offset += 1;
-                    res = Some((Sentence::Block(blocks), offset));
-                    return res;
-                }
-                _ => {}
-            }
-            return res;
-        }
-        res
-    }
+#[derive(Clone, Debug)]
+pub struct FunctionDef(Identifier, Vec<Identifier>, Expression);
+
+#[derive(Clone, Debug)]
+pub struct Include {
+    path: String,
+    /// [line, column] of `include`
+    line_colum: [usize; 2],
+}
 
-    // level 11: with html
+impl Sentence {
+    /// the sentence as parsed, like `2 * x` for `2 x`
+    #[allow(dead_code)]
     pub fn print(&self, level: usize) -> String {
-        if level == 11 {
-            match self {
-                Sentence::AssignmentExp(asexp) => asexp.print(level),
-                Sentence::Expression(exp) => exp.print(level) + "\n",
-                Sentence::Seperator => "<span class='syntax_seperator'>;</span>\n".to_string(),
-                Sentence::Block(ss) => {
-                    let mut res = "{\n".to_string();
-                    for s in ss.iter() {
-                        res += &s.print(level);
-                    }
-                    res += "}\n";
-                    format!("<span class='syntax_codeblock'>{res}</span>\n")
-                }
-            }
-        } else {
-            match self {
-                Sentence::AssignmentExp(asexp) => asexp.print(level),
-                Sentence::Expression(exp) => exp.print(level) + "\n",
-                Sentence::Seperator => ";\n".to_string(),
-                Sentence::Block(ss) => {
-                    let mut res = "{\n".to_string();
-                    for s in ss.iter() {
-                        res += &s.print(level);
-                    }
-                    res + "}\n"
+        match self {
+            Sentence::AssignmentExp(asexp) => asexp.print(level),
+            Sentence::FunctionDef(def) => def.print(level),
+            Sentence::Expression(exp) => exp.print(level) + "\n",
+            Sentence::Seperator => ";\n".to_string(),
+            Sentence::Block(ss) => {
+                let mut res = "{\n".to_string();
+                for s in ss.iter() {
+                    res += &s.print(level);
                 }
+                res + "}\n"
             }
+            Sentence::Include(inc) => inc.print(level),
         }
     }
 
     pub fn tree(&self, level: usize, html: bool) -> String {
         match self {
             Sentence::AssignmentExp(asexp) => asexp.tree(level, html),
+            Sentence::FunctionDef(def) => def.tree(level, html),
             Sentence::Expression(exp) => exp.tree(level, html),
             Sentence::Seperator => tree_node(html, " ;"),
+            Sentence::Include(inc) => inc.tree(html),
             Sentence::Block(ss) => {
                 let mut res = "+CodeBlock".to_string();
                 for s in ss.iter() {
@@ -994,10 +1035,65 @@ impl Sentence {
         }
     }
 
+    /// names of every variable or function this sentence may read
+    pub fn reads(&self, res: &mut HashSet<String>) {
+        match self {
+            Sentence::AssignmentExp(AssignmentExp(_, ex)) | Sentence::Expression(ex) => {
+                ex.reads(res)
+            }
+            Sentence::FunctionDef(FunctionDef(_, params, ex)) => {
+                let mut body = HashSet::new();
+                ex.reads(&mut body);
+                for p in params {
+                    body.remove(&p.name);
+                }
+                res.extend(body);
+            }
+            // unknown until the file is read
+            Sentence::Seperator | Sentence::Include(_) => {}
+            Sentence::Block(ss) => ss.iter().for_each(|s| s.reads(res)),
+        }
+    }
+    /// names of every variable this sentence may assign
+    pub fn writes(&self, res: &mut HashSet<String>) {
+        match self {
+            Sentence::AssignmentExp(AssignmentExp(id, _))
+            | Sentence::FunctionDef(FunctionDef(id, _, _)) => {
+                res.insert(id.name.clone());
+            }
+            Sentence::Expression(_) | Sentence::Seperator | Sentence::Include(_) => {}
+            Sentence::Block(ss) => ss.iter().for_each(|s| s.writes(res)),
+        }
+    }
+    /// kinds and spans of the expressions in it, for the `Cst`.
+    /// the spans of sentences are kept by the parser
+    fn nodes(&self, res: &mut Vec<(SyntaxKind, [usize; 2])>) {
+        match self {
+            Sentence::AssignmentExp(AssignmentExp(_, ex))
+            | Sentence::FunctionDef(FunctionDef(_, _, ex))
+            | Sentence::Expression(ex) => ex.nodes(res),
+            Sentence::Block(ss) => ss.iter().for_each(|s| s.nodes(res)),
+            Sentence::Seperator | Sentence::Include(_) => {}
+        }
+    }
+    /// contains `include`, whose reads and writes are unknown
+    pub fn includes(&self) -> bool {
+        match self {
+            Sentence::Include(_) => true,
+            Sentence::Block(ss) => ss.iter().any(|s| s.includes()),
+            _ => false,
+        }
+    }
+
     pub fn calc(&self, rt: &mut Runtime) -> Val {
         match self {
             Self::AssignmentExp(ass) => ass.calc(rt),
-            Sentence::Expression(ex) => ex.calc(rt),
+            Sentence::FunctionDef(def) => def.calc(rt),
+            Sentence::Expression(ex) => {
+                let res = ex.calc(rt);
+                rt.record(&res);
+                res
+            }
             Sentence::Seperator => Val::NONE,
             Sentence::Block(b) => {
                 let mut res = vec![];
@@ -1008,63 +1104,14 @@ impl Sentence {
                 rt.pop();
                 Val::Vars(my_math::Tuple(res))
             }
+            Sentence::Include(inc) => my_include::include(rt, &inc.path, inc.line_colum),
         }
     }
 }
 
 impl AssignmentExp {
-    pub fn from(tks: &[Token]) -> Option<(Self, usize)> {
-        let mut offset = 0;
-        // 0 -> 1 : Identifer
-        // 1 -> 2 : '='
-        // 2 -> Ok: Expression
-        let mut state = 0;
-        let mut id = None;
-        let mut res = None;
-        while offset < tks.len() && !tks[offset].is_eof() {
-            if tks[offset].is_skipped() {
-                offset += 1;
-                continue;
-            }
-            match state {
-                0 if tks[offset].is_identifier() => {
-                    if let Some(Literal::Identifier(i)) = tks[offset].literal.clone() {
-                        offset += 1;
-                        id = Some(i);
-                        state = 1;
-                        continue;
-                    }
-                }
-                1 if tks[offset].is_assign() => {
-                    offset += 1;
-                    state = 2;
-                    continue;
-                }
-                2 => {
-                    if let Some((ex, len)) = Expression::from(&tks[offset..]) {
-                        offset += len;
-                        res = Some((AssignmentExp(id?, ex), offset));
-                        return res;
-                    }
-                }
-                _ => {}
-            }
-            return res;
-        }
-        res
-    }
-
-    // level 11: with html
     pub fn print(&self, level: usize) -> String {
-        if level == 11 {
-            format!(
-                "<span class='syntax_assign'>{} = {}</span>\n",
-                self.0.name,
-                self.1.print(level)
-            )
-        } else {
-            format!("{} = {}\n", self.0.name, self.1.print(level))
-        }
+        format!("{} = {}\n", self.0.name, self.1.print(level))
     }
 
     pub fn tree(&self, level: usize, html: bool) -> String {
@@ -1077,397 +1124,306 @@ impl AssignmentExp {
 
     pub fn calc(&self, rt: &mut Runtime) -> Val {
         let val = self.1.calc(rt);
-        let res = rt.set_val(self.0.name.clone(), val);
+        rt.set_val(self.0.name.clone(), val);
+        Val::NONE
+    }
+}
+
+impl Include {
+    pub fn print(&self, _level: usize) -> String {
+        format!("include({:?})\n", self.path)
+    }
+
+    pub fn tree(&self, html: bool) -> String {
+        format!("+Include {}", tree_node(html, &format!("{:?}", self.path)))
+    }
+}
+
+impl FunctionDef {
+    fn head(&self) -> String {
+        let params: Vec<&str> = self.1.iter().map(|p| p.name.as_str()).collect();
+        format!("{}({})", self.0.name, params.join(", "))
+    }
+
+    pub fn print(&self, level: usize) -> String {
+        format!("{} = {}\n", self.head(), self.2.print(level))
+    }
+
+    pub fn tree(&self, level: usize, html: bool) -> String {
+        format!(
+            "+Define {} {}",
+            &tree_node(html, &self.head()),
+            self.2.tree(level + 1, html)
+        )
+    }
+
+    pub fn calc(&self, rt: &mut Runtime) -> Val {
+        let params = self.1.iter().map(|p| p.name.clone()).collect();
+        let f = rt.closure(self.0.name.clone(), params, self.2.clone());
+        rt.set_val(self.0.name.clone(), f);
         Val::NONE
     }
 }
 
 // basic expression
 
-#[derive(Clone)]
-pub enum Expression {
-    /// exp ([+-*/] exp)*
-    Operation(CalcUnit, Vec<(TokenType, CalcUnit)>),
+#[derive(Debug)]
+pub struct Expression {
+    pub kind: ExpKind,
+    /// [start, end] in the input, like `Token::pos`
+    pub span: [usize; 2],
 }
 
-#[derive(Clone)]
-pub enum CalcUnit {
+#[derive(Clone, Debug)]
+pub enum ExpKind {
     /// 123
     Literal(Literal),
-    /// - 123
-    NegVal(Literal),
     /// x
     Identifier(Identifier),
-    /// - 123
-    NegVar(Identifier),
+    /// -x, +x
+    Unary(TokenType, Box<Expression>),
+    /// a + b, `2 x` is `2 * x`
+    Binary(TokenType, Box<Expression>, Box<Expression>),
     /// f(...)
     Function(Identifier, Tuple),
-    /// - f(...)
-    NegFun(Identifier, Tuple),
+    /// x[...]
+    Index(Identifier, Box<Expression>),
     /// (...)
     Tuple(Tuple),
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct Tuple {
     val: Vec<Expression>,
 }
 
 impl Expression {
-    pub fn from(tks: &[Token]) -> Option<(Self, usize)> {
-        let mut offset = 0;
-        // 0    -> 1 : CalcUnit
-        // 1, 3 -> 2 : Token.is_calc_op()
-        // 1, 3 -> 3 : Token.NewLine
-        // 1    -> 1 : CalcUnit // default multiply
-        // 1    -> Ok: Else
-        // 2    -> 1 : CalcUnit
-        // 3    -> Ok: else
-        let mut state = 0;
-        let mut unit = None;
-        let mut units = vec![];
-        let mut op = TokenType::Star;
-        while offset < tks.len() && !tks[offset].is_eof() {
-            if tks[offset].is_skipped() {
-                if state == 1 && tks[offset].is_newline() {
-                    state = 3;
-                }
-                offset += 1;
-                continue;
-            }
-            match state {
-                1 | 3 if tks[offset].is_calc_op() => {
-                    op = tks[offset].token_type;
-                    offset += 1;
-                    state = 2;
-                    continue;
-                }
-                3 => {
-                    return Some((Expression::Operation(unit?, units), offset));
-                }
-                0 | 1 | 2 => {
-                    if let Some((cu, len)) = CalcUnit::from(&tks[offset..]) {
-                        offset += len;
-                        if state == 0 {
-                            unit = Some(cu);
-                        } else {
-                            units.push((op, cu));
-                            op = TokenType::Star;
-                        }
-                        state = 1;
-                        continue;
-                    } else if state == 1 {
-                        return Some((Expression::Operation(unit?, units), offset));
-                    }
-                }
-                _ => {}
-            }
-            return None;
+    /// an empty tuple, left behind by `drop`
+    const EMPTY: Self = Expression {
+        kind: ExpKind::Tuple(Tuple { val: Vec::new() }),
+        span: [0, 0],
+    };
+
+    /// parse a whole text as one expression
+    pub fn parse_str(text: &str) -> Option<Self> {
+        let p = LexicalParser::new_inline(text.to_string());
+        let mut parser = Parser::new(p.tokens());
+        let ex = parser.expression(0)?;
+        if parser.peek().is_none() {
+            Some(ex)
+        } else {
+            None
+        }
+    }
+
+    /// the leftmost operand and the operations after it, `a + b * c - d`
+    /// is `a` with `+ b * c` and `- d`.
+    /// walks long chains like `1 + 1 + ...` without recursion
+    fn chain(&self) -> (&Expression, Vec<(TokenType, &Expression)>) {
+        let mut ops = vec![];
+        let mut ex = self;
+        while let ExpKind::Binary(op, a, b) = &ex.kind {
+            ops.push((*op, &**b));
+            ex = a;
         }
-        Some((Expression::Operation(unit?, units), offset))
+        ops.reverse();
+        (ex, ops)
     }
 
-    // level 11: with html
     pub fn print(&self, level: usize) -> String {
-        if level == 11 {
-            match self {
-                Expression::Operation(cu, us) => {
-                    let mut res = cu.print(level);
-                    for (tt, u) in us {
-                        res += &format!(
-                            " <span class='syntax_operator'>{}</span> {}",
-                            tt.to_char(),
-                            u.print(level)
-                        );
-                    }
-                    format!("<span class='syntax_expression'>{}</span>", res)
-                }
-            }
-        } else {
-            match self {
-                Expression::Operation(cu, us) => {
-                    let mut res = cu.print(level);
-                    for (tt, u) in us {
-                        res += &format!(" {} {}", tt.to_char(), u.print(level));
-                    }
-                    format!("{}", res)
-                }
-            }
+        let (first, ops) = self.chain();
+        let mut res = first.print_unit(level);
+        for (op, ex) in ops {
+            res += &format!(" {} {}", op.symbol(), ex.print(level));
         }
+        res
     }
 
-    pub fn tree(&self, level: usize, html: bool) -> String {
-        match self {
-            Expression::Operation(cu, us) => {
-                let mut res = format!("+Expression: {}\n", us.len());
-                res += &(INDENT.repeat(level) + "+---" + &cu.tree(level + 1, html));
-                for (tt, u) in us {
-                    res += "\n";
-                    res += &(INDENT.repeat(level)
-                        + "| Operator "
-                        + &tree_node(html, &tt.to_char().to_string()));
-                    res += "\n";
-                    res += &(INDENT.repeat(level) + "+---" + &u.tree(level + 1, html));
-                }
-                res
-            }
+    fn print_unit(&self, level: usize) -> String {
+        match &self.kind {
+            ExpKind::Literal(l) => l.print(level),
+            ExpKind::Identifier(i) => i.name.to_string(),
+            ExpKind::Unary(op, ex) => format!("{}{}", op.to_char(), ex.print(level)),
+            ExpKind::Function(f, vars) => format!("{}{}", f.name, vars.print(level)),
+            ExpKind::Index(x, ex) => format!("{}[{}]", x.name, ex.print(level)),
+            ExpKind::Tuple(t) => t.print(level),
+            ExpKind::Binary(..) => self.print(level),
         }
     }
 
-    pub fn calc(&self, rt: &mut Runtime) -> Val {
-        match self {
-            Self::Operation(cu, t_cu_vec) => {
-                let mut values: Vec<Val> = vec![cu.calc(rt).reduce()];
-                let mut operators: Vec<TokenType> = vec![];
-                for (op, val) in t_cu_vec {
-                    while !operators.is_empty()
-                        && operators.last().unwrap().get_priority() >= op.get_priority()
-                    {
-                        let b = values.pop().unwrap();
-                        let a = values.pop().unwrap();
-                        let oper = operators.pop().unwrap();
-                        values.push(a.calc(&b, oper));
-                    }
-                    values.push(val.calc(rt).reduce());
-                    operators.push(*op);
-                }
-                while !operators.is_empty() {
-                    let b = values.pop().unwrap();
-                    let a = values.pop().unwrap();
-                    let oper = operators.pop().unwrap();
-                    values.push(a.calc(&b, oper));
-                }
-                values.pop().unwrap().reduce()
-            }
+    pub fn tree(&self, level: usize, html: bool) -> String {
+        let (first, ops) = self.chain();
+        let mut res = format!("+Expression: {}\n", ops.len());
+        res += &(INDENT.repeat(level) + "+---" + &first.tree_unit(level + 1, html));
+        for (op, ex) in ops {
+            res += "\n";
+            res += &(INDENT.repeat(level) + "| Operator " + &tree_node(html, &op.symbol()));
+            res += "\n";
+            res += &(INDENT.repeat(level) + "+---" + &ex.tree_unit(level + 1, html));
         }
+        res
     }
-}
 
-impl CalcUnit {
-    fn from(tks: &[Token]) -> Option<(Self, usize)> {
-        let mut offset = 0;
-        // 0 -> Literal : Literal
-        // 0 -> 1       : +/-
-        // 0 -> 2       : Identifier
-        // 0 -> Tuple   : Tuple
-        // 1 -> NegVal  : Literal
-        // 1 -> 3       : Identifier
-        // 2 -> Function: ~Tuple
-        // 2 -> Identifier: Else
-        // 3 -> NegFun  : ~Tuple
-        // 3 -> NegVar  : Else
-        let mut state = 0;
-        let mut id = None;
-        let mut is_neg = false;
-        while offset < tks.len() && !tks[offset].is_eof() {
-            if tks[offset].is_skipped() {
-                if state != 2 {
-                    offset += 1;
-                    continue;
-                } else {
-                    return Some((CalcUnit::Identifier(id?), offset));
-                }
+    fn tree_unit(&self, level: usize, html: bool) -> String {
+        match &self.kind {
+            ExpKind::Literal(l) => " Literal ".to_string() + &tree_node(html, &l.print(3)),
+            ExpKind::Identifier(i) => " Identifier ".to_string() + &tree_node(html, &i.name),
+            ExpKind::Unary(op, ex) => {
+                let mut res =
+                    "+Unary ".to_string() + &tree_node(html, &op.to_char().to_string()) + "\n";
+                res += &(INDENT.repeat(level) + "+---" + &ex.tree_unit(level + 1, html));
+                res
             }
-            match state {
-                0 if tks[offset].is_literal() => {
-                    let it = tks[offset].literal.clone()?;
-                    offset += 1;
-                    return Some((CalcUnit::Literal(it), offset));
-                }
-                0 if tks[offset].is_identifier() => {
-                    if let Some(Literal::Identifier(i)) = &tks[offset].literal {
-                        offset += 1;
-                        id = Some(i.clone());
-                        state = 2;
-                        continue;
-                    }
-                }
-                0 if tks[offset].is_pos_neg() => {
-                    if tks[offset].token_type == TokenType::Minus {
-                        is_neg = true;
-                    }
-                    offset += 1;
-                    state = 1;
-                    continue;
-                }
-                0 => {
-                    if let Some((tp, len)) = Tuple::from(&tks[offset..]) {
-                        offset += len;
-                        return Some((CalcUnit::Tuple(tp), offset));
-                    }
-                }
-                1 if tks[offset].is_literal() => {
-                    let it = tks[offset].literal.clone()?;
-                    offset += 1;
-                    if is_neg {
-                        return Some((CalcUnit::NegVal(it), offset));
-                    } else {
-                        return Some((CalcUnit::Literal(it), offset));
-                    }
-                }
-                1 if tks[offset].is_identifier() => {
-                    if let Literal::Identifier(id0) = tks[offset].literal.clone()? {
-                        offset += 1;
-                        id = Some(id0);
-                        state = 3;
-                        continue;
-                    }
-                }
-                2 | 3 => {
-                    if let Some((tp, len)) = Tuple::from(&tks[offset..]) {
-                        offset += len;
-                        if is_neg {
-                            return Some((CalcUnit::NegFun(id?, tp), offset));
-                        } else {
-                            return Some((CalcUnit::Function(id?, tp), offset));
-                        }
-                    }
-                    if is_neg {
-                        return Some((CalcUnit::NegVar(id?), offset));
-                    } else {
-                        return Some((CalcUnit::Identifier(id?), offset));
-                    }
-                }
-                _ => {}
+            ExpKind::Function(f, vars) => {
+                let mut res = "+Function ".to_string() + &tree_node(html, &f.name) + "\n";
+                res += &(INDENT.repeat(level) + "+---" + &vars.tree(level + 1, html));
+                res
             }
-            return None;
-        }
-        if state == 2 {
-            return Some((CalcUnit::Identifier(id?), offset));
+            ExpKind::Index(x, ex) => {
+                let mut res = "+Index ".to_string() + &tree_node(html, &x.name) + "\n";
+                res += &(INDENT.repeat(level) + "+---" + &ex.tree(level + 1, html));
+                res
+            }
+            ExpKind::Tuple(t) => t.tree(level, html),
+            ExpKind::Binary(..) => self.tree(level, html),
         }
-        None
     }
 
-    fn print(&self, level: usize) -> String {
-        if level == 11 {
-            match self {
-                CalcUnit::Literal(l) => l.print(level),
-                CalcUnit::Identifier(i) => {
-                    format!("<span class='syntax_identifier'>{}</span>", i.name)
-                }
-                CalcUnit::NegVal(l) => {
-                    format!("<span class='syntax_neg'>-{}</span>", l.print(level))
-                }
-                CalcUnit::NegVar(i) => format!(
-                    "<span class='syntax_neg'>-<span class='syntax_identifier'>{}</span></span>",
-                    i.name
-                ),
-                CalcUnit::Function(f, vars) => format!(
-                    "<span class='syntax_fun'>{}{}</span>",
-                    f.name,
-                    vars.print(level)
-                ),
-                CalcUnit::NegFun(f, vars) => format!(
-                    "<span class='syntax_neg'>-<span class='syntax_fun'>{}{}</span></span>",
-                    f.name,
-                    vars.print(level)
-                ),
-                CalcUnit::Tuple(t) => t.print(level),
-            }
-        } else {
-            match self {
-                CalcUnit::Literal(l) => l.print(level),
-                CalcUnit::Identifier(i) => format!("{}", i.name),
-                CalcUnit::NegVal(l) => {
-                    format!("-{}", l.print(level))
-                }
-                CalcUnit::NegVar(i) => format!("-{}", i.name),
-                CalcUnit::Function(f, vars) => format!("{}{}", f.name, vars.print(level)),
-                CalcUnit::NegFun(f, vars) => format!("-{}{}", f.name, vars.print(level)),
-                CalcUnit::Tuple(t) => t.print(level),
+    /// kinds and spans of it and the expressions in it,
+    /// a chain is one node and names or literals are none
+    fn nodes(&self, res: &mut Vec<(SyntaxKind, [usize; 2])>) {
+        let (first, ops) = self.chain();
+        if !ops.is_empty() {
+            res.push((SyntaxKind::Binary, self.span));
+        }
+        let kind = match &first.kind {
+            ExpKind::Unary(..) => SyntaxKind::Unary,
+            ExpKind::Function(..) => SyntaxKind::Call,
+            ExpKind::Index(..) => SyntaxKind::Index,
+            ExpKind::Tuple(_) => SyntaxKind::Tuple,
+            ExpKind::Literal(_) | ExpKind::Identifier(_) | ExpKind::Binary(..) => {
+                ops.iter().for_each(|(_, ex)| ex.nodes(res));
+                return;
             }
+        };
+        res.push((kind, first.span));
+        match &first.kind {
+            ExpKind::Unary(_, ex) | ExpKind::Index(_, ex) => ex.nodes(res),
+            ExpKind::Function(_, t) | ExpKind::Tuple(t) => t.val.iter().for_each(|e| e.nodes(res)),
+            _ => {}
         }
+        ops.iter().for_each(|(_, ex)| ex.nodes(res));
     }
 
-    fn tree(&self, level: usize, html: bool) -> String {
-        match self {
-            CalcUnit::Literal(l) => " Literal ".to_string() + &tree_node(html, &l.print(3)),
-            CalcUnit::Identifier(i) => " Identifier ".to_string() + &tree_node(html, &i.name),
-            CalcUnit::NegVal(l) => " Literal Minus ".to_string() + &tree_node(html, &l.print(3)),
-            CalcUnit::NegVar(i) => " Identifier Minus ".to_string() + &tree_node(html, &i.name),
-            CalcUnit::Function(f, vars) => {
-                let mut res = "+Function ".to_string() + &tree_node(html, &f.name) + "\n";
-                res += &(INDENT.repeat(level) + "+---" + &vars.tree(level + 1, html));
-                res
+    pub fn reads(&self, res: &mut HashSet<String>) {
+        let (first, ops) = self.chain();
+        match &first.kind {
+            ExpKind::Literal(Literal::Identifier(x)) | ExpKind::Identifier(x) => {
+                res.insert(x.name.clone());
             }
-            CalcUnit::NegFun(f, vars) => {
-                let mut res = "+Function Minus ".to_string() + &tree_node(html, &f.name) + "\n";
-                res += &(INDENT.repeat(level) + "+---" + &vars.tree(level + 1, html));
-                res
+            ExpKind::Literal(_) | ExpKind::Binary(..) => {}
+            ExpKind::Unary(_, ex) => ex.reads(res),
+            ExpKind::Function(x, t) => {
+                res.insert(x.name.clone());
+                t.val.iter().for_each(|e| e.reads(res));
             }
-            CalcUnit::Tuple(t) => t.tree(level, html),
+            ExpKind::Index(x, e) => {
+                res.insert(x.name.clone());
+                e.reads(res);
+            }
+            ExpKind::Tuple(t) => t.val.iter().for_each(|e| e.reads(res)),
         }
+        ops.iter().for_each(|(_, ex)| ex.reads(res));
     }
 
     pub fn calc(&self, rt: &mut Runtime) -> Val {
-        match self {
-            CalcUnit::Literal(Literal::Number(d)) => Val::Re(my_math::Re(*d)),
-            CalcUnit::Literal(Literal::Identifier(x)) => rt.get_val(&x.name).reduce(),
-            CalcUnit::NegVal(Literal::Number(d)) => Val::Re(my_math::Re(*d)).reduce().neg(),
-            CalcUnit::NegVal(Literal::Identifier(x)) => rt.get_val(&x.name).neg(),
-            CalcUnit::Identifier(x) => rt.get_val(&x.name).reduce(),
-            CalcUnit::NegVar(x) => rt.get_val(&x.name).reduce().neg(),
-            CalcUnit::Function(f, vars) => {
+        rt.nested(|rt| {
+            let (first, ops) = self.chain();
+            let mut res = first.calc_unit(rt);
+            for (op, ex) in ops {
+                let b = ex.calc(rt);
+                res = res.calc(&b, op);
+            }
+            res.reduce()
+        })
+    }
+
+    fn calc_unit(&self, rt: &mut Runtime) -> Val {
+        match &self.kind {
+            ExpKind::Literal(Literal::Number(d, false)) => Val::Re(my_math::Re(*d)),
+            ExpKind::Literal(Literal::Number(d, true)) => Val::Im(my_math::Im(0.0, *d)),
+            ExpKind::Literal(Literal::Identifier(x)) | ExpKind::Identifier(x) => {
+                rt.get_val(&x.name).reduce()
+            }
+            ExpKind::Literal(Literal::String(s)) => Val::Text(s.clone()),
+            ExpKind::Literal(Literal::Bool(b)) => Val::Re(my_math::Re(f64::from(u8::from(*b)))),
+            ExpKind::Literal(_) => Val::NONE,
+            ExpKind::Unary(TokenType::Minus, ex) => ex.calc(rt).neg(),
+            ExpKind::Unary(_, ex) => ex.calc(rt),
+            ExpKind::Function(f, vars) => {
                 let vars = vars.calc(rt).reduce();
                 rt.calls(&f.name, vars).reduce()
             }
-            CalcUnit::NegFun(f, vars) => {
-                let vars = vars.calc(rt).reduce();
-                rt.calls(&f.name, vars).reduce().neg()
+            ExpKind::Index(x, ex) => {
+                let i = ex.calc(rt);
+                rt.get_index(&x.name, i).reduce()
             }
-            CalcUnit::Tuple(vars) => vars.calc(rt).reduce(),
-            _ => Val::NONE,
+            ExpKind::Tuple(vars) => vars.calc(rt).reduce(),
+            ExpKind::Binary(..) => self.calc(rt),
         }
     }
 }
 
-impl Tuple {
-    /// Warning: this func do not remove leading space before it, so it could return None
-    fn from(tks: &[Token]) -> Option<(Self, usize)> {
-        let mut offset = 0;
-        // 0 -> 1 : (
-        // 1 -> 2 : Expression
-        // 3 -> 2 : Expression
-        // 1 -> Ok: )
-        // 2 -> Ok: )
-        // 2 -> 3 : ,
-        let mut state = 0;
-        let mut exps = vec![];
-        while offset < tks.len() && !tks[offset].is_eof() {
-            if tks[offset].is_skipped() {
-                offset += 1;
-                continue;
+impl Drop for Expression {
+    /// long chains like `1 + 1 + ...` are dropped without deep recursion
+    fn drop(&mut self) {
+        let mut stack = vec![];
+        self.kind.take_children(&mut stack);
+        while let Some(mut ex) = stack.pop() {
+            ex.kind.take_children(&mut stack);
+        }
+    }
+}
+
+impl Clone for Expression {
+    /// long chains like `1 + 1 + ...` are cloned without deep recursion
+    fn clone(&self) -> Self {
+        let mut spine = vec![];
+        let mut ex = self;
+        while let ExpKind::Binary(op, a, b) = &ex.kind {
+            spine.push((*op, &**b, ex.span));
+            ex = a;
+        }
+        let mut res = Expression {
+            kind: ex.kind.clone(),
+            span: ex.span,
+        };
+        for (op, b, span) in spine.into_iter().rev() {
+            res = Expression {
+                kind: ExpKind::Binary(op, Box::new(res), Box::new(b.clone())),
+                span,
+            };
+        }
+        res
+    }
+}
+
+impl ExpKind {
+    fn take_children(&mut self, res: &mut Vec<Expression>) {
+        match self {
+            ExpKind::Unary(_, a) | ExpKind::Index(_, a) => {
+                res.push(std::mem::replace(&mut **a, Expression::EMPTY));
             }
-            match state {
-                0 if tks[offset].token_type == TokenType::LeftParen => {
-                    offset += 1;
-                    state = 1;
-                    continue;
-                }
-                1 | 2 if tks[offset].token_type == TokenType::RightParen => {
-                    offset += 1;
-                    return Some((Tuple { val: exps }, offset));
-                }
-                2 if tks[offset].token_type == TokenType::Comma => {
-                    offset += 1;
-                    state = 3;
-                    continue;
-                }
-                1 | 3 => {
-                    if let Some((exp, len)) = Expression::from(&tks[offset..]) {
-                        offset += len;
-                        state = 2;
-                        exps.push(exp);
-                        continue;
-                    }
-                }
-                _ => {}
+            ExpKind::Binary(_, a, b) => {
+                res.push(std::mem::replace(&mut **a, Expression::EMPTY));
+                res.push(std::mem::replace(&mut **b, Expression::EMPTY));
             }
-            return None;
+            _ => {}
         }
-        None
     }
+}
 
+impl Tuple {
     fn print(&self, level: usize) -> String {
         let mut res = "(".to_string();
         for i in 0..self.val.len() {
@@ -1476,11 +1432,7 @@ impl Tuple {
                 res += ", ";
             }
         }
-        if level == 11 {
-            format!("<span class='syntax_tuple'>{}</span>", res + ")")
-        } else {
-            res + ")"
-        }
+        res + ")"
     }
 
     fn tree(&self, level: usize, html: bool) -> String {
@@ -1501,11 +1453,558 @@ impl Tuple {
     }
 }
 
+/// a token seen by the parser: whitespaces and comments are dropped,
+/// `--` and `++` are split into two operators
+#[derive(Clone, Copy, Debug)]
+struct Tok {
+    ty: TokenType,
+    /// index in the tokens
+    index: usize,
+    pos: [usize; 2],
+    /// whitespaces or comments before it
+    spaced: bool,
+    /// a new line before it
+    newline: bool,
+}
+
+/// Precedence climbing parser over the tokens of an article.
+///
+/// Each token is looked at a bounded number of times, so parsing is linear.
+/// * a new line ends an expression, unless an operator follows
+/// * `2 x` and `2(x + 1)` multiply, `f(x)` and `x[i]` need no space before the bracket
+struct Parser<'a> {
+    tks: &'a [Token],
+    toks: Vec<Tok>,
+    at: usize,
+    depth: usize,
+    /// the furthest place a sentence failed at, and why
+    failure: Option<(usize, String)>,
+    diagnostics: Vec<Diagnostic>,
+    /// spans of the sentences parsed and the tokens skipped, for the `Cst`
+    nodes: Vec<(SyntaxKind, [usize; 2])>,
+    /// where the last sentence returned by `next` starts
+    begun: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(tks: &'a [Token]) -> Self {
+        let mut toks = vec![];
+        let mut spaced = false;
+        let mut newline = false;
+        for (index, t) in tks.iter().enumerate() {
+            if t.is_eof() {
+                break;
+            }
+            if t.is_skipped() {
+                spaced = true;
+                newline |= t.is_newline() || t.lexeme().contains('\n');
+                continue;
+            }
+            let half = match t.token_type {
+                TokenType::MinusMinus => Some(TokenType::Minus),
+                TokenType::PlusPlus => Some(TokenType::Plus),
+                _ => None,
+            };
+            match half {
+                Some(ty) => {
+                    let mid = t.pos[0] + 1;
+                    toks.push(Tok {
+                        ty,
+                        index,
+                        pos: [t.pos[0], mid],
+                        spaced,
+                        newline,
+                    });
+                    toks.push(Tok {
+                        ty,
+                        index,
+                        pos: [mid, t.pos[1]],
+                        spaced: false,
+                        newline: false,
+                    });
+                }
+                None => toks.push(Tok {
+                    ty: t.token_type,
+                    index,
+                    pos: t.pos,
+                    spaced,
+                    newline,
+                }),
+            }
+            spaced = false;
+            newline = false;
+        }
+        Parser {
+            tks,
+            toks,
+            at: 0,
+            depth: 0,
+            failure: None,
+            diagnostics: vec![],
+            nodes: vec![],
+            begun: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<Tok> {
+        self.toks.get(self.at).copied()
+    }
+    fn peek_ty(&self, n: usize) -> Option<TokenType> {
+        self.toks.get(self.at + n).map(|t| t.ty)
+    }
+    fn eat(&mut self, ty: TokenType) -> Option<Tok> {
+        let t = self.peek().filter(|t| t.ty == ty)?;
+        self.at += 1;
+        Some(t)
+    }
+    fn token(&self, t: Tok) -> &'a Token {
+        &self.tks[t.index]
+    }
+    fn identifier(&self, n: usize) -> Option<Identifier> {
+        match &self.token(*self.toks.get(self.at + n)?).literal {
+            Some(Literal::Identifier(i)) => Some(i.clone()),
+            _ => None,
+        }
+    }
+    /// index in the tokens after the last one parsed
+    fn end(&self) -> usize {
+        match self.at {
+            0 => 0,
+            at => self.toks[at - 1].index + 1,
+        }
+    }
+
+    /// [start, end] in the input from the token at `start` to the last one parsed
+    fn span_from(&self, start: usize) -> [usize; 2] {
+        [self.toks[start].pos[0], self.toks[self.at - 1].pos[1]]
+    }
+
+    /// `expected ..., found ...` at the current token
+    fn fail<T>(&mut self, expected: &str) -> Option<T> {
+        if let Some(e) = self.peek().and_then(|t| self.token(t).error.clone()) {
+            return self.fail_with(e);
+        }
+        let found = match self.peek() {
+            Some(t) => format!("`{}`", self.text(t)),
+            None => "the end".to_string(),
+        };
+        self.fail_with(format!("expected {expected}, found {found}"))
+    }
+    /// keeps the furthest failure, which explains the others
+    fn fail_with<T>(&mut self, message: String) -> Option<T> {
+        if self.failure.as_ref().is_none_or(|(at, _)| self.at > *at) {
+            self.failure = Some((self.at, message));
+        }
+        None
+    }
+    fn text(&self, t: Tok) -> String {
+        match t.pos[1] - t.pos[0] {
+            // half of `--` or `++`
+            1 if self.token(t).lexeme.chars().count() == 2 => t.ty.to_char().to_string(),
+            _ => self.token(t).lexeme.clone(),
+        }
+    }
+    fn diagnose(&mut self, at: usize, message: String) {
+        let (span, line_colum) = match self.toks.get(at) {
+            Some(&t) => {
+                let lc = self.token(t).line_colum;
+                let lexeme_start = self.token(t).pos[0];
+                (t.pos, [lc[0], lc[1] + t.pos[0] - lexeme_start])
+            }
+            None => self.end_of_input(),
+        };
+        // a failure right before a `;` or new line is found again from there
+        if self.diagnostics.last().is_some_and(|d| d.span == span) {
+            return;
+        }
+        self.diagnostics.push(Diagnostic {
+            span,
+            line_colum,
+            message,
+        });
+    }
+    fn end_of_input(&self) -> ([usize; 2], [usize; 2]) {
+        match self.tks.last() {
+            Some(t) if t.is_newline() => ([t.pos[1]; 2], [t.line_colum[0] + 1, 0]),
+            Some(t) => {
+                let len = t.lexeme.chars().filter(|&c| c != '\n').count();
+                ([t.pos[1]; 2], [t.line_colum[0], t.line_colum[1] + len])
+            }
+            None => ([0; 2], [0; 2]),
+        }
+    }
+
+    /// the next sentence, malformed ones are reported and skipped
+    fn next(&mut self, in_block: bool) -> Option<Sentence> {
+        loop {
+            let t = self.peek()?;
+            if t.ty == TokenType::RightBrace {
+                if in_block {
+                    return None;
+                }
+                self.diagnose(self.at, "unmatched `}`".to_string());
+                self.nodes.push((SyntaxKind::Error, t.pos));
+                self.at += 1;
+                continue;
+            }
+            self.failure = None;
+            let start = self.at;
+            if let Some(s) = self.sentence() {
+                self.begun = start;
+                return Some(s);
+            }
+            self.recover();
+        }
+    }
+
+    /// report the failure and skip to the next `;`, new line or `}`
+    fn recover(&mut self) {
+        let start = self.at;
+        let (at, message) = match self.failure.take() {
+            Some(f) => f,
+            None => (self.at, "expected a sentence".to_string()),
+        };
+        self.diagnose(at, message);
+        self.at = at.max(start + 1);
+        while let Some(t) = self.peek() {
+            if t.newline || matches!(t.ty, TokenType::Semicolon | TokenType::RightBrace) {
+                break;
+            }
+            self.at += 1;
+        }
+        self.nodes.push((SyntaxKind::Error, self.span_from(start)));
+    }
+
+    /// a sentence, or nothing parsed when it is malformed
+    fn sentence(&mut self) -> Option<Sentence> {
+        let start = self.at;
+        let nodes = self.nodes.len();
+        self.depth += 1;
+        let res = if self.depth > MAX_NESTING {
+            self.fail_with("blocks nested too deep".to_string())
+        } else {
+            self.sentence_inner()
+        };
+        self.depth -= 1;
+        let kind = match res.as_ref() {
+            None => {
+                self.at = start;
+                self.nodes.truncate(nodes);
+                return None;
+            }
+            Some(Sentence::Block(_)) => SyntaxKind::Block,
+            Some(Sentence::AssignmentExp(_)) => SyntaxKind::Assignment,
+            Some(Sentence::FunctionDef(_)) => SyntaxKind::FunctionDef,
+            Some(Sentence::Include(_)) => SyntaxKind::Include,
+            Some(Sentence::Expression(_) | Sentence::Seperator) => return res,
+        };
+        self.nodes.push((kind, self.span_from(start)));
+        res
+    }
+
+    fn sentence_inner(&mut self) -> Option<Sentence> {
+        match self.peek()?.ty {
+            TokenType::Semicolon => {
+                self.at += 1;
+                Some(Sentence::Seperator)
+            }
+            TokenType::LeftBrace => {
+                self.at += 1;
+                let mut ss = vec![];
+                while let Some(s) = self.next(true) {
+                    ss.push(s);
+                }
+                if self.eat(TokenType::RightBrace).is_none() {
+                    self.diagnose(self.at, "expected `}`, found the end".to_string());
+                }
+                Some(Sentence::Block(ss))
+            }
+            TokenType::Identifier => {
+                if let Some(inc) = self.include() {
+                    Some(Sentence::Include(inc))
+                } else if self.peek_ty(1) == Some(TokenType::Equal) {
+                    let id = self.identifier(0)?;
+                    self.at += 2;
+                    Some(Sentence::AssignmentExp(AssignmentExp(
+                        id,
+                        self.expression(0)?,
+                    )))
+                } else if let Some((id, params)) = self.function_head() {
+                    let ex = self.expression(0)?;
+                    Some(Sentence::FunctionDef(FunctionDef(id, params, ex)))
+                } else {
+                    Some(Sentence::Expression(self.expression(0)?))
+                }
+            }
+            _ => Some(Sentence::Expression(self.expression(0)?)),
+        }
+    }
+
+    /// include("file")
+    fn include(&mut self) -> Option<Include> {
+        let t = self.peek()?;
+        let lparen = self.toks.get(self.at + 1)?;
+        let path = self.toks.get(self.at + 2)?;
+        if self.token(t).lexeme != "include"
+            || lparen.ty != TokenType::LeftParen
+            || lparen.spaced
+            || path.ty != TokenType::String
+            || self.peek_ty(3) != Some(TokenType::RightParen)
+        {
+            return None;
+        }
+        let Some(Literal::String(path)) = &self.token(*path).literal else {
+            return None;
+        };
+        let res = Include {
+            path: path.clone(),
+            line_colum: self.token(t).line_colum,
+        };
+        self.at += 4;
+        Some(res)
+    }
+
+    /// `f(x, y) =`, only looks ahead unless it matches
+    fn function_head(&mut self) -> Option<(Identifier, Vec<Identifier>)> {
+        let id = self.identifier(0)?;
+        if self.peek_ty(1)? != TokenType::LeftParen {
+            return None;
+        }
+        let mut n = 2;
+        let mut params = vec![];
+        if self.peek_ty(n)? != TokenType::RightParen {
+            loop {
+                params.push(self.identifier(n)?);
+                n += 1;
+                match self.peek_ty(n)? {
+                    TokenType::Comma => n += 1,
+                    TokenType::RightParen => break,
+                    _ => return None,
+                }
+            }
+        }
+        if self.peek_ty(n + 1)? != TokenType::Equal {
+            return None;
+        }
+        self.at += n + 2;
+        Some((id, params))
+    }
+
+    /// operations binding tighter than `min_bp`
+    fn expression(&mut self, min_bp: u8) -> Option<Expression> {
+        self.depth += 1;
+        let res = if self.depth > MAX_NESTING {
+            self.fail_with("expression nested too deep".to_string())
+        } else {
+            self.climb(min_bp)
+        };
+        self.depth -= 1;
+        res
+    }
+
+    fn climb(&mut self, min_bp: u8) -> Option<Expression> {
+        let mut lhs = self.prefix()?;
+        while let Some(t) = self.peek() {
+            let (op, implicit) = if infix_bp(t.ty).is_some() {
+                (t.ty, false)
+            } else if !t.newline && starts_operand(t.ty) {
+                (TokenType::Star, true)
+            } else if !t.newline && t.ty == TokenType::Error {
+                // `3 $ 4` is malformed as a whole
+                return self.fail("an operator");
+            } else {
+                break;
+            };
+            let (l, r) = infix_bp(op)?;
+            if l < min_bp {
+                break;
+            }
+            if !implicit {
+                self.at += 1;
+            }
+            let rhs = match &self.token(t).literal {
+                // `²` carries its exponent
+                Some(lit) if op == TokenType::Caret => Expression {
+                    kind: ExpKind::Literal(lit.clone()),
+                    span: t.pos,
+                },
+                _ => self.expression(r)?,
+            };
+            lhs = Expression {
+                span: [lhs.span[0], rhs.span[1]],
+                kind: ExpKind::Binary(op, Box::new(lhs), Box::new(rhs)),
+            };
+        }
+        Some(lhs)
+    }
+
+    fn prefix(&mut self) -> Option<Expression> {
+        let Some(t) = self.peek() else {
+            return self.fail("an operand");
+        };
+        let (kind, end) = match t.ty {
+            TokenType::Plus | TokenType::Minus => {
+                self.at += 1;
+                let ex = self.expression(PREFIX_BP)?;
+                let end = ex.span[1];
+                (ExpKind::Unary(t.ty, Box::new(ex)), end)
+            }
+            TokenType::Number | TokenType::Char | TokenType::String | TokenType::Bool => {
+                self.at += 1;
+                (ExpKind::Literal(self.token(t).literal.clone()?), t.pos[1])
+            }
+            TokenType::Identifier if self.token(t).lexeme == "√" => {
+                let id = self.identifier(0)?;
+                self.at += 1;
+                let ex = self.expression(PREFIX_BP)?;
+                let end = ex.span[1];
+                (ExpKind::Function(id, Tuple { val: vec![ex] }), end)
+            }
+            TokenType::Identifier => {
+                let id = self.identifier(0)?;
+                self.at += 1;
+                match self.peek() {
+                    Some(n) if n.ty == TokenType::LeftParen && !n.spaced => {
+                        let (vars, end) = self.tuple()?;
+                        (ExpKind::Function(id, vars), end)
+                    }
+                    Some(n) if n.ty == TokenType::LeftSquare && !n.spaced => {
+                        self.at += 1;
+                        let ex = self.expression(0)?;
+                        let Some(close) = self.eat(TokenType::RightSquare) else {
+                            return self.fail("`]`");
+                        };
+                        let end = close.pos[1];
+                        (ExpKind::Index(id, Box::new(ex)), end)
+                    }
+                    _ => (ExpKind::Identifier(id), t.pos[1]),
+                }
+            }
+            TokenType::LeftParen => {
+                let (vars, end) = self.tuple()?;
+                (ExpKind::Tuple(vars), end)
+            }
+            _ => return self.fail("an operand"),
+        };
+        Some(Expression {
+            kind,
+            span: [t.pos[0], end],
+        })
+    }
+
+    /// `(a, b, ...)` and where it ends
+    fn tuple(&mut self) -> Option<(Tuple, usize)> {
+        self.eat(TokenType::LeftParen)?;
+        let mut val = vec![];
+        if let Some(t) = self.eat(TokenType::RightParen) {
+            return Some((Tuple { val }, t.pos[1]));
+        }
+        loop {
+            val.push(self.expression(0)?);
+            match self.peek().map(|t| t.ty) {
+                Some(TokenType::Comma) => self.at += 1,
+                Some(TokenType::RightParen) => {
+                    let t = self.toks[self.at];
+                    self.at += 1;
+                    return Some((Tuple { val }, t.pos[1]));
+                }
+                _ => return self.fail("`,` or `)`"),
+            }
+        }
+    }
+}
+
+/// binding powers of binary operators on the left and the right
+fn infix_bp(op: TokenType) -> Option<(u8, u8)> {
+    match op {
+        TokenType::Less
+        | TokenType::LessEqual
+        | TokenType::Greater
+        | TokenType::GreaterEqual
+        | TokenType::EqualEqual
+        | TokenType::BangEqual => Some((1, 2)),
+        TokenType::Plus | TokenType::Minus => Some((3, 4)),
+        TokenType::Star | TokenType::Slash | TokenType::Percent => Some((5, 6)),
+        // right associative, tighter than unary minus: -x^2 is -(x^2)
+        TokenType::Caret => Some((10, 9)),
+        _ => None,
+    }
+}
+
+/// tokens multiplying the operand before them, as in `2 x`
+fn starts_operand(ty: TokenType) -> bool {
+    matches!(
+        ty,
+        TokenType::Number
+            | TokenType::Char
+            | TokenType::String
+            | TokenType::Bool
+            | TokenType::Identifier
+            | TokenType::LeftParen
+    )
+}
+
 const INDENT: &str = "|   ";
 fn tree_node(html: bool, name: &str) -> String {
     if html {
+        let name = my_highlight::escape_html(name);
         format!("<span class='tree_syntax_node'>{name}</span>")
     } else {
         name.to_string()
     }
 }
+
+#[test]
+fn test_parser() {
+    let parse = |text: &str| {
+        let p = LexicalParser::new_inline(text.to_string());
+        p.parse()
+            .sentences
+            .iter()
+            .map(|s| s.print(0))
+            .collect::<String>()
+    };
+    assert_eq!(parse("1 + 2 * 3 - 4"), "1 + 2 * 3 - 4\n");
+    assert_eq!(parse("a ≤ b+1 ≠ c"), "a <= b + 1 != c\n");
+    assert_eq!(parse("--x; -(a+b)"), "--x\n;\n-(a + b)\n");
+    assert_eq!(
+        parse("f(x, y) = 2 x + y\ny = f(1,\n 2)"),
+        "f(x, y) = 2 * x + y\ny = f(1, 2)\n"
+    );
+    // an operator continues the expression on the next line
+    assert_eq!(parse("a\n+ b\nc"), "a + b\nc\n");
+    assert_eq!(parse("{x = out[1]} 3"), "{\nx = out[1]\n}\n3\n");
+    // so does a comment across lines
+    assert_eq!(parse("x = 1 /* a\nb */ y = 2"), "x = 1\ny = 2\n");
+
+    let ex = Expression::parse_str("a - -b * c").unwrap();
+    let ExpKind::Binary(TokenType::Minus, a, b) = &ex.kind else {
+        panic!("{ex:?}")
+    };
+    assert!(matches!(a.kind, ExpKind::Identifier(_)));
+    assert!(matches!(
+        &b.kind,
+        ExpKind::Binary(TokenType::Star, neg, _) if matches!(neg.kind, ExpKind::Unary(..))
+    ));
+    assert_eq!(ex.span, [0, 10]);
+    assert_eq!(b.span, [4, 10]);
+
+    let mut rt = Runtime::new();
+    let calc = |rt: &mut Runtime, text: &str| match Expression::parse_str(text).unwrap().calc(rt) {
+        Val::Re(my_math::Re(v)) => v,
+        v => panic!("{v:?}"),
+    };
+    assert_eq!(calc(&mut rt, "2 + 3 * 4 - 6 / 2"), 11.0);
+    assert_eq!(calc(&mut rt, "---2 - -(1 + 2)"), 1.0);
+    assert_eq!(calc(&mut rt, "2(3 + 1) 5"), 40.0);
+
+    // long inputs parse in linear time, and clone and drop without deep recursion
+    let long = vec!["1"; 100_000].join(" + ");
+    assert_eq!(calc(&mut rt, &long), 100_000.0);
+    let mut c = crate::Caculator::new("");
+    c.update(&long);
+    assert_eq!(c.get_html(), "[out 1] 100000\n");
+    c.update(&format!("f(x) = {}\nf(1)", vec!["x"; 100_000].join(" + ")));
+    assert_eq!(c.get_html(), "[out 1] 100000\n");
+    assert!(Expression::parse_str(&"(".repeat(1000)).is_none());
+}
@@ -0,0 +1,726 @@
+use std::{collections::HashMap, fmt::Write};
+
+use crate::{
+    check_name,
+    my_highlight::escape_html,
+    my_math::{Fun, Re, Tuple, Val},
+    my_parser::Expression,
+    my_runtime::Runtime,
+};
+
+/// size of the svg
+const WIDTH: f64 = 640.0;
+const HEIGHT: f64 = 400.0;
+/// space for tick labels, left and below the plot
+const LEFT: f64 = 56.0;
+const BOTTOM: f64 = 28.0;
+const MARGIN: f64 = 12.0;
+/// points sampled evenly before refining
+const SAMPLES: usize = 128;
+/// halvings of an interval between even samples
+const MAX_DEPTH: usize = 10;
+/// cells of the grid of `implicit`, on each axis
+const CELLS: usize = 120;
+/// colours of the series, repeated
+const COLORS: [&str; 6] = [
+    "#1f77b4", "#d62728", "#2ca02c", "#ff7f0e", "#9467bd", "#8c564b",
+];
+
+/// a curve, as lines broken where it is undefined or jumps
+#[derive(Debug, Clone, PartialEq)]
+pub struct Series {
+    pub label: String,
+    pub lines: Vec<Vec<[f64; 2]>>,
+}
+
+/// what the axes of `svg` show
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Frame {
+    /// `[min, max]` of x, that of the points if `None`
+    pub x: Option<[f64; 2]>,
+    pub y: Option<[f64; 2]>,
+    /// a unit as long on both axes, for curves keeping their shape
+    pub equal: bool,
+}
+
+/// `plot(f, a, b)`: the svg of each function on `[a, b]`
+pub fn plot(rt: &mut Runtime, args: &[Val]) -> Result<Val, String> {
+    let [fs, a, b] = args else {
+        return Err("expects functions and two real numbers".to_string());
+    };
+    let [a, b] = bounds(a, b)?;
+    let mut series = vec![];
+    for f in items(fs) {
+        let (label, mut f) = function(rt, f, &["x"])?;
+        let lines = sample(&mut |x| real(f(rt, &[x])), a, b);
+        series.push(Series { label, lines });
+    }
+    let frame = Frame {
+        x: Some([a, b]),
+        ..Frame::default()
+    };
+    Ok(Val::Svg(svg(&series, frame)))
+}
+
+/// `parametric(c, a, b)`: the svg of the points `c(t)` for `a ≤ t ≤ b`,
+/// where `c` gives a pair, or is a pair of functions
+pub fn parametric(rt: &mut Runtime, args: &[Val]) -> Result<Val, String> {
+    let [c, a, b] = args else {
+        return Err("expects a curve and two real numbers".to_string());
+    };
+    let [a, b] = bounds(a, b)?;
+    let (label, lines) = match c {
+        Val::Vars(Tuple(v)) if v.len() == 2 => {
+            let (fx, mut x) = function(rt, v[0].clone(), &["t"])?;
+            let (fy, mut y) = function(rt, v[1].clone(), &["t"])?;
+            let lines = sample_path(&mut |t| [real(x(rt, &[t])), real(y(rt, &[t]))], a, b);
+            (format!("({fx}, {fy})"), lines)
+        }
+        c => {
+            let (label, mut c) = function(rt, c.clone(), &["t"])?;
+            (label, sample_path(&mut |t| pair(c(rt, &[t])), a, b))
+        }
+    };
+    Ok(Val::Svg(svg(&[Series { label, lines }], curve_frame())))
+}
+
+/// `polar(r, a, b)`: the svg of each function `r(θ)` for `a ≤ θ ≤ b`
+pub fn polar(rt: &mut Runtime, args: &[Val]) -> Result<Val, String> {
+    let [rs, a, b] = args else {
+        return Err("expects functions and two real numbers".to_string());
+    };
+    let [a, b] = bounds(a, b)?;
+    let mut series = vec![];
+    for r in items(rs) {
+        let (label, mut r) = function(rt, r, &["θ"])?;
+        let mut point = |t: f64| {
+            let r = real(r(rt, &[t]));
+            [r * t.cos(), r * t.sin()]
+        };
+        let lines = sample_path(&mut point, a, b);
+        series.push(Series { label, lines });
+    }
+    Ok(Val::Svg(svg(&series, curve_frame())))
+}
+
+/// `implicit(F, x0, x1, y0, y1)`: the svg of `F(x, y) = 0` for each function
+pub fn implicit(rt: &mut Runtime, args: &[Val]) -> Result<Val, String> {
+    let [fs, x0, x1, y0, y1] = args else {
+        return Err("expects functions and four real numbers".to_string());
+    };
+    let (x, y) = (bounds(x0, x1)?, bounds(y0, y1)?);
+    let mut series = vec![];
+    for f in items(fs) {
+        let (label, mut f) = function(rt, f, &["x", "y"])?;
+        let lines = contour(&mut |x, y| real(f(rt, &[x, y])), x, y, [CELLS, CELLS]);
+        series.push(Series { label, lines });
+    }
+    let frame = Frame {
+        x: Some(x),
+        y: Some(y),
+        equal: true,
+    };
+    Ok(Val::Svg(svg(&series, frame)))
+}
+
+fn curve_frame() -> Frame {
+    Frame {
+        equal: true,
+        ..Frame::default()
+    }
+}
+
+/// `[a, b]` of finite reals, `a < b`
+fn bounds(a: &Val, b: &Val) -> Result<[f64; 2], String> {
+    match (a, b) {
+        (Val::Re(Re(a)), Val::Re(Re(b))) if a.is_finite() && b.is_finite() && a < b => Ok([*a, *b]),
+        (Val::Re(_), Val::Re(_)) => Err("expects a < b".to_string()),
+        _ => Err("expects real numbers for the range".to_string()),
+    }
+}
+
+/// the functions of `(f, g)`, or `f` alone
+fn items(fs: &Val) -> Vec<Val> {
+    match fs {
+        Val::Vars(Tuple(v)) => v.clone(),
+        f => vec![f.clone()],
+    }
+}
+
+/// a function of real numbers, giving what it calculates
+pub type Function = Box<dyn FnMut(&mut Runtime, &[f64]) -> Val>;
+
+/// the function of `f` and its label: `f` is a function, the name of one,
+/// or an expression of `vars` in quotes, calculated in an env binding them
+pub fn function(rt: &Runtime, f: Val, vars: &[&str]) -> Result<(String, Function), String> {
+    let label = match &f {
+        Val::Fun(Fun::System(name)) => name.clone(),
+        Val::Fun(Fun::User(c)) => c.body.print(0),
+        Val::Text(s) => s.clone(),
+        _ => return Err(format!("expects functions, found {}", f.text(&rt.sys))),
+    };
+    // a name in quotes is the function of that name
+    let f = match f {
+        Val::Text(s) if check_name(&s).is_ok() && rt.sys.function(&s).is_some() => {
+            Val::Fun(Fun::System(s))
+        }
+        Val::Text(s) if check_name(&s).is_ok() && rt.lookup(&s).is_some_and(|v| is_fun(&v)) => {
+            rt.lookup(&s).unwrap_or(Val::NONE)
+        }
+        f => f,
+    };
+    let call: Function = match f {
+        Val::Text(s) => {
+            let ex = Expression::parse_str(&s).ok_or(format!("not an expression: {s}"))?;
+            let vars: Vec<String> = vars.iter().map(|v| v.to_string()).collect();
+            Box::new(move |rt, xs| {
+                rt.scoped(|rt| {
+                    for (var, x) in vars.iter().zip(xs) {
+                        rt.bind(var, Val::Re(Re(*x)));
+                    }
+                    ex.calc(rt)
+                })
+            })
+        }
+        f => Box::new(move |rt, xs| {
+            let args = match xs {
+                [x] => Val::Re(Re(*x)),
+                xs => Val::Vars(Tuple(xs.iter().map(|x| Val::Re(Re(*x))).collect())),
+            };
+            f.calls(rt, args)
+        }),
+    };
+    Ok((label, call))
+}
+
+fn is_fun(v: &Val) -> bool {
+    matches!(v, Val::Fun(_))
+}
+
+/// a real result, NaN for anything else
+fn real(v: Val) -> f64 {
+    match v.reduce() {
+        Val::Re(Re(y)) => y,
+        _ => f64::NAN,
+    }
+}
+
+/// a pair of reals, NaN for anything else
+fn pair(v: Val) -> [f64; 2] {
+    match v.reduce() {
+        Val::Vars(Tuple(v)) if v.len() == 2 => [real(v[0].clone()), real(v[1].clone())],
+        _ => [f64::NAN; 2],
+    }
+}
+
+/// `y = f(x)` on `[a, b]`, sampled more where it bends, broken where it is not finite or jumps
+pub fn sample(f: &mut dyn FnMut(f64) -> f64, a: f64, b: f64) -> Vec<Vec<[f64; 2]>> {
+    sample_path(&mut |x| [x, f(x)], a, b)
+}
+
+/// the points `f(t)` for `a ≤ t ≤ b`, like `sample`
+pub fn sample_path(f: &mut dyn FnMut(f64) -> [f64; 2], a: f64, b: f64) -> Vec<Vec<[f64; 2]>> {
+    let ts = (0..=SAMPLES).map(|i| a + (b - a) * i as f64 / SAMPLES as f64);
+    let even: Vec<(f64, [f64; 2])> = ts.map(|t| (t, f(t))).collect();
+    // steps are measured against the size of the curve
+    let scale = [0, 1].map(|k| {
+        let [lo, hi] = range(even.iter().map(|p| (p.1[k], 1.0)));
+        hi - lo
+    });
+    let mut points = vec![even[0]];
+    for w in even.windows(2) {
+        refine(f, w[0], w[1], 0, scale, &mut points);
+    }
+    let mut res = vec![];
+    let mut line: Vec<(f64, [f64; 2])> = vec![];
+    for p in points {
+        let broken = match line.last() {
+            _ if !finite(p.1) => true,
+            Some(&last) => step(last.1, p.1, scale) > 0.05 && jumps(f, last, p, scale),
+            None => false,
+        };
+        if broken {
+            if line.len() > 1 {
+                res.push(line.iter().map(|p| p.1).collect());
+            }
+            line.clear();
+        }
+        if finite(p.1) {
+            line.push(p);
+        }
+    }
+    if line.len() > 1 {
+        res.push(line.iter().map(|p| p.1).collect());
+    }
+    res
+}
+
+fn finite(p: [f64; 2]) -> bool {
+    p[0].is_finite() && p[1].is_finite()
+}
+
+/// the larger of the steps on each axis, against the size of the curve
+fn step(p: [f64; 2], q: [f64; 2], scale: [f64; 2]) -> f64 {
+    ((p[0] - q[0]) / scale[0])
+        .abs()
+        .max(((p[1] - q[1]) / scale[1]).abs())
+}
+
+/// the points after `p0` up to `p1`, halving the interval where
+/// the middle is off the chord or only some ends are finite
+fn refine(
+    f: &mut dyn FnMut(f64) -> [f64; 2],
+    p0: (f64, [f64; 2]),
+    p1: (f64, [f64; 2]),
+    depth: usize,
+    scale: [f64; 2],
+    res: &mut Vec<(f64, [f64; 2])>,
+) {
+    let t = (p0.0 + p1.0) / 2.0;
+    let pm = (t, f(t));
+    let bends = match [p0.1, pm.1, p1.1].map(finite) {
+        [true, true, true] => {
+            let chord = [0, 1].map(|k| (p0.1[k] + p1.1[k]) / 2.0);
+            step(pm.1, chord, scale) > 1e-3
+        }
+        [a, b, c] => !(a == b && b == c),
+    };
+    if bends && depth < MAX_DEPTH {
+        refine(f, p0, pm, depth + 1, scale, res);
+        refine(f, pm, p1, depth + 1, scale, res);
+    } else {
+        res.extend([pm, p1]);
+    }
+}
+
+/// the step between `p0` and `p1` stays when closing in on it, like at a pole or a floor
+fn jumps(
+    f: &mut dyn FnMut(f64) -> [f64; 2],
+    mut p0: (f64, [f64; 2]),
+    mut p1: (f64, [f64; 2]),
+    scale: [f64; 2],
+) -> bool {
+    for _ in 0..40 {
+        let t = (p0.0 + p1.0) / 2.0;
+        let pm = (t, f(t));
+        if !finite(pm.1) {
+            return true;
+        }
+        // the half with the larger step
+        if step(p0.1, pm.1, scale) > step(pm.1, p1.1, scale) {
+            p1 = pm;
+        } else {
+            p0 = pm;
+        }
+    }
+    step(p0.1, p1.1, scale) > 0.05
+}
+
+/// an edge of the grid: `(i, j, false)` from corner `(i, j)` to `(i + 1, j)`,
+/// `(i, j, true)` to `(i, j + 1)`
+type Edge = (usize, usize, bool);
+
+/// lines where `f(x, y) = 0` by marching squares on `n` cells,
+/// without sign changes that are poles rather than zeros
+pub fn contour(
+    f: &mut dyn FnMut(f64, f64) -> f64,
+    x: [f64; 2],
+    y: [f64; 2],
+    n: [usize; 2],
+) -> Vec<Vec<[f64; 2]>> {
+    let at = |i: usize, j: usize| {
+        [
+            x[0] + (x[1] - x[0]) * i as f64 / n[0] as f64,
+            y[0] + (y[1] - y[0]) * j as f64 / n[1] as f64,
+        ]
+    };
+    let v: Vec<Vec<f64>> = (0..=n[0])
+        .map(|i| (0..=n[1]).map(|j| f(at(i, j)[0], at(i, j)[1])).collect())
+        .collect();
+    // where each crossed edge crosses 0, `None` for a pole
+    let mut crossings: HashMap<Edge, Option<[f64; 2]>> = HashMap::new();
+    let mut cross = |e: Edge| {
+        *crossings.entry(e).or_insert_with(|| {
+            let (i, j, up) = e;
+            let (i1, j1) = if up { (i, j + 1) } else { (i + 1, j) };
+            let (v0, v1) = (v[i][j], v[i1][j1]);
+            let s = v0 / (v0 - v1);
+            let (p0, p1) = (at(i, j), at(i1, j1));
+            let p = [p0[0] + (p1[0] - p0[0]) * s, p0[1] + (p1[1] - p0[1]) * s];
+            (f(p[0], p[1]).abs() <= v0.abs().max(v1.abs()) * 0.5).then_some(p)
+        })
+    };
+    let mut segments: Vec<[Edge; 2]> = vec![];
+    for i in 0..n[0] {
+        for j in 0..n[1] {
+            let corners = [v[i][j], v[i + 1][j], v[i + 1][j + 1], v[i][j + 1]];
+            if !corners.iter().all(|c| c.is_finite()) {
+                continue;
+            }
+            let [a, b, c, d] = corners.map(|c| c > 0.0);
+            // bottom, right, top and left
+            let edges = [
+                (i, j, false),
+                (i + 1, j, true),
+                (i, j + 1, false),
+                (i, j, true),
+            ];
+            let crossed = [a != b, b != c, d != c, a != d];
+            let mut pairs = vec![];
+            match crossed {
+                [true, true, true, true] => {
+                    // a saddle: the middle decides which corners are cut off
+                    let middle = corners.iter().sum::<f64>() > 0.0;
+                    if middle == a {
+                        pairs.extend([[0, 1], [2, 3]]);
+                    } else {
+                        pairs.extend([[3, 0], [1, 2]]);
+                    }
+                }
+                _ => {
+                    let ks: Vec<usize> = (0..4).filter(|&k| crossed[k]).collect();
+                    if let [k0, k1] = ks[..] {
+                        pairs.push([k0, k1]);
+                    }
+                }
+            }
+            for [k0, k1] in pairs {
+                let (e0, e1) = (edges[k0], edges[k1]);
+                if cross(e0).is_some() && cross(e1).is_some() {
+                    segments.push([e0, e1]);
+                }
+            }
+        }
+    }
+    let point = |e: &Edge| crossings[e].unwrap_or([f64::NAN; 2]);
+    chain(&segments)
+        .iter()
+        .map(|l| l.iter().map(point).collect())
+        .collect()
+}
+
+/// segments sharing ends joined into lines
+fn chain(segments: &[[Edge; 2]]) -> Vec<Vec<Edge>> {
+    let mut ends: HashMap<Edge, Vec<usize>> = HashMap::new();
+    for (k, s) in segments.iter().enumerate() {
+        ends.entry(s[0]).or_default().push(k);
+        ends.entry(s[1]).or_default().push(k);
+    }
+    let mut used = vec![false; segments.len()];
+    let mut res = vec![];
+    for k in 0..segments.len() {
+        if used[k] {
+            continue;
+        }
+        used[k] = true;
+        let mut line = std::collections::VecDeque::from(segments[k]);
+        // on from the back, then from the front
+        for back in [true, false] {
+            loop {
+                let end = if back { line[line.len() - 1] } else { line[0] };
+                let Some(&next) = ends[&end].iter().find(|&&n| !used[n]) else {
+                    break;
+                };
+                used[next] = true;
+                let s = segments[next];
+                let other = if s[0] == end { s[1] } else { s[0] };
+                if back {
+                    line.push_back(other);
+                } else {
+                    line.push_front(other);
+                }
+            }
+        }
+        res.push(line.into());
+    }
+    res
+}
+
+/// the range of finite values, without far outliers like those near a pole,
+/// each value counted by its weight
+fn range(vs: impl Iterator<Item = (f64, f64)>) -> [f64; 2] {
+    let mut vs: Vec<(f64, f64)> = vs.filter(|v| v.0.is_finite()).collect();
+    if vs.is_empty() {
+        return [-1.0, 1.0];
+    }
+    vs.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let total: f64 = vs.iter().map(|v| v.1).sum();
+    let at = |q: f64| {
+        let mut sum = 0.0;
+        let i = vs.iter().position(|v| {
+            sum += v.1;
+            sum >= q * total
+        });
+        vs[i.unwrap_or(vs.len() - 1)].0
+    };
+    let (min, max) = (vs[0].0, vs[vs.len() - 1].0);
+    let (lo, hi) = (at(0.05), at(0.95));
+    let [lo, hi] = if max - min > 4.0 * (hi - lo) && hi > lo {
+        let pad = (hi - lo) * 0.5;
+        [(lo - pad).max(min), (hi + pad).min(max)]
+    } else {
+        [min, max]
+    };
+    if hi - lo < 1e-12 * lo.abs().max(1.0) {
+        [lo - 1.0, hi + 1.0]
+    } else {
+        [lo, hi]
+    }
+}
+
+/// about `n` round steps covering `[lo, hi]`: the step and the ticks
+fn ticks(lo: f64, hi: f64, n: usize) -> (f64, Vec<f64>) {
+    let raw = (hi - lo) / n as f64;
+    let mag = 10f64.powf(raw.log10().floor());
+    let step = mag
+        * match raw / mag {
+            r if r <= 1.0 => 1.0,
+            r if r <= 2.0 => 2.0,
+            r if r <= 5.0 => 5.0,
+            _ => 10.0,
+        };
+    (step, multiples(lo, hi, step))
+}
+
+/// the multiples of `step` in `[lo, hi]`
+fn multiples(lo: f64, hi: f64, step: f64) -> Vec<f64> {
+    let first = (lo / step - 1e-9).ceil() as i64;
+    let last = (hi / step + 1e-9).floor() as i64;
+    (first..=last).map(|i| i as f64 * step).collect()
+}
+
+/// a tick with the digits of its step
+fn tick_label(v: f64, step: f64) -> String {
+    if v == 0.0 {
+        return "0".to_string();
+    }
+    if v.abs() >= 1e6 || v.abs() < 1e-4 {
+        return format!("{v:e}");
+    }
+    let digits = (-step.log10().floor()).max(0.0) as usize;
+    format!("{v:.digits$}")
+}
+
+/// the series on axes with ticks and a legend
+pub fn svg(series: &[Series], frame: Frame) -> String {
+    let (left, top) = (LEFT, MARGIN);
+    let (right, bottom) = (WIDTH - MARGIN, HEIGHT - BOTTOM);
+    let lines = || series.iter().flat_map(|s| &s.lines);
+    let mut x = frame
+        .x
+        .unwrap_or_else(|| range(lines().flatten().map(|p| (p[0], 1.0))));
+    // points by the width they stand for, as refining adds many near poles
+    let mut y = frame.y.unwrap_or_else(|| {
+        range(lines().flat_map(|l| {
+            (0..l.len()).map(|i| {
+                let (a, b) = (l[i.saturating_sub(1)][0], l[(i + 1).min(l.len() - 1)][0]);
+                let width = if frame.x.is_some() {
+                    (b - a).abs() / 2.0
+                } else {
+                    1.0
+                };
+                (l[i][1], width)
+            })
+        }))
+    });
+    if frame.equal {
+        // the larger of the units per pixel, both ranges widened around their middles
+        let unit = ((x[1] - x[0]) / (right - left)).max((y[1] - y[0]) / (bottom - top));
+        let widen = |r: [f64; 2], pixels: f64| {
+            let (mid, half) = ((r[0] + r[1]) / 2.0, unit * pixels / 2.0);
+            [mid - half, mid + half]
+        };
+        x = widen(x, right - left);
+        y = widen(y, bottom - top);
+    }
+    let (x_step, x_ticks) = ticks(x[0], x[1], 8);
+    let (y_step, _) = ticks(y[0], y[1], 6);
+    if frame.y.is_none() && !frame.equal {
+        // y ends on ticks
+        y = [
+            (y[0] / y_step - 1e-9).floor() * y_step,
+            (y[1] / y_step + 1e-9).ceil() * y_step,
+        ];
+    }
+    let y_ticks = multiples(y[0], y[1], y_step);
+    // pixels, kept near the plot so far points stay drawable
+    let px = |v: f64| (left + (v - x[0]) / (x[1] - x[0]) * (right - left)).clamp(-1e4, 1e4);
+    let py = |v: f64| (bottom - (v - y[0]) / (y[1] - y[0]) * (bottom - top)).clamp(-1e4, 1e4);
+
+    let mut res = String::new();
+    let _ = write!(
+        res,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}" viewBox="0 0 {WIDTH} {HEIGHT}" font-family="sans-serif" font-size="12">"#
+    );
+    let _ = write!(
+        res,
+        r##"<rect width="{WIDTH}" height="{HEIGHT}" fill="#fff"/><clipPath id="plot-area"><rect x="{left}" y="{top}" width="{}" height="{}"/></clipPath>"##,
+        right - left,
+        bottom - top
+    );
+    // grid, the axes through 0 darker, and labels
+    for &t in &x_ticks {
+        let (p, color) = (px(t), if t == 0.0 { "#999" } else { "#eee" });
+        let _ = write!(
+            res,
+            r#"<line x1="{p:.2}" y1="{top}" x2="{p:.2}" y2="{bottom}" stroke="{color}"/><text x="{p:.2}" y="{}" text-anchor="middle">{}</text>"#,
+            bottom + 16.0,
+            tick_label(t, x_step)
+        );
+    }
+    for &t in &y_ticks {
+        let (p, color) = (py(t), if t == 0.0 { "#999" } else { "#eee" });
+        let _ = write!(
+            res,
+            r#"<line x1="{left}" y1="{p:.2}" x2="{right}" y2="{p:.2}" stroke="{color}"/><text x="{}" y="{:.2}" text-anchor="end">{}</text>"#,
+            left - 6.0,
+            p + 4.0,
+            tick_label(t, y_step)
+        );
+    }
+    let _ = write!(
+        res,
+        r##"<rect x="{left}" y="{top}" width="{}" height="{}" fill="none" stroke="#333"/>"##,
+        right - left,
+        bottom - top
+    );
+    res += r#"<g clip-path="url(#plot-area)" fill="none" stroke-width="1.5">"#;
+    for (i, s) in series.iter().enumerate() {
+        let mut d = String::new();
+        for line in &s.lines {
+            for (j, p) in line.iter().enumerate() {
+                let cmd = if j == 0 { 'M' } else { 'L' };
+                let _ = write!(d, "{cmd}{:.2},{:.2}", px(p[0]), py(p[1]));
+            }
+        }
+        let color = COLORS[i % COLORS.len()];
+        let _ = write!(res, r#"<path d="{d}" stroke="{color}"/>"#);
+    }
+    res += "</g>";
+    // the legend, top right
+    let width = series
+        .iter()
+        .map(|s| s.label.chars().count())
+        .max()
+        .unwrap_or(0) as f64
+        * 7.0
+        + 36.0;
+    let x0 = right - width - 8.0;
+    let _ = write!(
+        res,
+        r##"<rect x="{x0}" y="{}" width="{width}" height="{}" fill="#fff" fill-opacity="0.8" stroke="#ccc"/>"##,
+        top + 8.0,
+        series.len() as f64 * 18.0 + 6.0
+    );
+    for (i, s) in series.iter().enumerate() {
+        let y = top + 23.0 + i as f64 * 18.0;
+        let color = COLORS[i % COLORS.len()];
+        let _ = write!(
+            res,
+            r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{color}" stroke-width="2"/><text x="{}" y="{y}">{}</text>"#,
+            x0 + 6.0,
+            y - 4.0,
+            x0 + 24.0,
+            y - 4.0,
+            x0 + 30.0,
+            escape_html(&s.label)
+        );
+    }
+    res + "</svg>"
+}
+
+#[test]
+fn test_plot() {
+    // a pole: broken in two, refined near it
+    let lines = sample(&mut |x| 1.0 / x, -1.0, 1.0);
+    assert_eq!(lines.len(), 2);
+    assert!(lines.iter().all(|l| l.iter().all(|p| p[1].is_finite())));
+    assert!(lines[0].last().unwrap()[0] > -1e-3);
+    // undefined for x < 0
+    let lines = sample(&mut f64::sqrt, -1.0, 1.0);
+    assert_eq!(lines.len(), 1);
+    assert!(lines[0][0][0] < 1e-2);
+    // jumps, but not a steep slope
+    assert_eq!(sample(&mut f64::floor, -0.5, 2.5).len(), 4);
+    assert_eq!(sample(&mut |x| (100.0 * x).atan(), -1.0, 1.0).len(), 1);
+    // a straight line needs no more points
+    assert_eq!(sample(&mut |x| 2.0 * x, 0.0, 1.0)[0].len(), 2 * SAMPLES + 1);
+
+    assert_eq!(
+        ticks(0.0, 1.0, 5),
+        (0.2, vec![0.0, 0.2, 0.4, 0.6000000000000001, 0.8, 1.0])
+    );
+    assert_eq!(ticks(-3.2, 7.9, 6).1, [-2.0, 0.0, 2.0, 4.0, 6.0]);
+    assert_eq!(tick_label(0.6000000000000001, 0.2), "0.6");
+    assert_eq!(tick_label(250.0, 50.0), "250");
+    assert_eq!(
+        range([0.0, 1.0, 2.0].map(|v| (v, 1.0)).into_iter()),
+        [0.0, 2.0]
+    );
+    let pole = [
+        (-1e6, 0.01),
+        (-1.0, 1.0),
+        (0.0, 1.0),
+        (1.0, 1.0),
+        (1e6, 0.01),
+    ];
+    assert_eq!(range(pole.into_iter()), [-2.0, 2.0]);
+
+    let mut c = crate::Caculator::new("");
+    c.update("f(x) = x^2; plot((f, sin, \"x - 1\"), -2, 2)");
+    let out = c.outputs();
+    assert_eq!(out.len(), 1);
+    let svg = &out[0].text;
+    assert!(svg.starts_with("<svg") && svg.ends_with("</svg>"));
+    assert_eq!(svg.matches("<path").count(), 3);
+    assert!(svg.contains(">x ^ 2</text>") && svg.contains(">x - 1</text>"));
+    assert!(c.call_errors().is_empty());
+    let svg = c.plot(vec!["tan".to_string(), "f".to_string()], -3.0, 3.0);
+    assert!(svg.unwrap().contains(">tan</text>"));
+    assert_eq!(
+        c.plot(vec!["x +".to_string()], 0.0, 1.0),
+        Err("not an expression: x +".to_string())
+    );
+    assert_eq!(
+        c.plot(vec!["sin".to_string()], 1.0, 0.0),
+        Err("expects a < b".to_string())
+    );
+
+    // closed curves, in one line each
+    let circle = |lines: &[Vec<[f64; 2]>]| {
+        lines.len() == 1
+            && lines[0]
+                .iter()
+                .all(|p| (p[0].hypot(p[1]) - 1.0).abs() < 1e-2)
+            && (lines[0][0][0] - lines[0].last().unwrap()[0]).abs() < 1e-3
+    };
+    let tau = std::f64::consts::TAU;
+    assert!(circle(&sample_path(&mut |t| [t.cos(), t.sin()], 0.0, tau)));
+    let lines = contour(
+        &mut |x, y| x * x + y * y - 1.0,
+        [-2.0, 2.0],
+        [-2.0, 2.0],
+        [40, 40],
+    );
+    assert!(circle(&lines));
+    assert!(lines[0].len() > 40);
+    // a sign change at a pole is no line
+    let lines = contour(&mut |x, _| 1.0 / x, [-1.0, 1.0], [-1.0, 1.0], [9, 9]);
+    assert!(lines.is_empty());
+    // crossing lines, turned into two corners at the saddle
+    let lines = contour(&mut |x, y| x * y, [-1.1, 1.0], [-1.1, 1.0], [20, 20]);
+    assert_eq!(lines.len(), 2);
+
+    c.update(
+        "r(t) = 1; parametric((cos, sin), 0, 2 pi); parametric(\"(t, t^2)\", -1, 1);\
+         polar((r, \"θ / 4\"), 0, 2 pi); implicit(\"x^2 + y^2 - 1\", -2, 2, -2, 2)",
+    );
+    let out = c.outputs();
+    assert_eq!(out.len(), 4);
+    assert!(out.iter().all(|o| o.text.starts_with("<svg")));
+    assert!(out[0].text.contains(">(cos, sin)</text>"));
+    assert!(out[1].text.contains(">(t, t^2)</text>"));
+    assert_eq!(out[2].text.matches("<path").count(), 2);
+    assert_eq!(out[3].text.matches("<path").count(), 1);
+    assert!(c.call_errors().is_empty());
+}
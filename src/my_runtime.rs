@@ -1,6 +1,29 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    rc::Rc,
+};
 
-use crate::my_math::{Fun, Re, Tuple, Val};
+use crate::{
+    my_include::Includes,
+    my_json::Json,
+    my_math::{
+        intial_system,
+        my_builtins::{builtins, NativeFunction},
+        my_format::{Notation, NumFormat},
+        Closure, Fun, Tuple, Val,
+    },
+    my_parser::Expression,
+    my_suggest::suggestions,
+};
+
+/// version written by `export_state`, older ones can still be imported
+pub const STATE_VERSION: u32 = 2;
+const STATE_FORMAT: &str = "web-caculator-state";
+/// user functions called deeper than this, counting the expressions they are
+/// nested in, fail with "recursion too deep", so calculating never overflows
+/// the stack: the parser nests a body at most `MAX_NESTING` deeper
+const MAX_DEPTH: usize = 512;
 
 /// no clonable
 pub struct Runtime {
@@ -13,6 +36,41 @@ pub struct Runtime {
     pub sys: System,
 
     current: usize,
+
+    /// every output so far, `out[1]` is the first
+    history: Vec<Val>,
+
+    /// envs and history that `restart` goes back to
+    base: (Vec<Env>, Vec<Val>),
+
+    /// nested expressions and calls of user functions being calculated
+    depth: usize,
+
+    /// how `include` finds files, and its errors
+    pub include: Includes,
+
+    /// failed calls of native functions since the last `restart`
+    call_errors: Vec<CallError>,
+}
+
+/// a function that failed, was called with wrong arguments, or a name not defined
+#[derive(Debug, Clone, PartialEq)]
+pub struct CallError {
+    pub name: String,
+    pub message: String,
+    /// defined names like an unknown one, closest first
+    pub suggestions: Vec<String>,
+}
+
+impl fmt::Display for CallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.name, self.message)?;
+        match &self.suggestions[..] {
+            [] => Ok(()),
+            [one] => write!(f, ", did you mean {one}?"),
+            [rest @ .., last] => write!(f, ", did you mean {} or {last}?", rest.join(", ")),
+        }
+    }
 }
 
 impl Runtime {
@@ -22,6 +80,11 @@ impl Runtime {
             envs: vec![Env::new(0, 0), Env::new(1, 0)],
             sys,
             current: 1,
+            history: Vec::new(),
+            base: (vec![Env::new(0, 0), Env::new(1, 0)], Vec::new()),
+            depth: 0,
+            include: Includes::new(),
+            call_errors: vec![],
         }
     }
     /// check validity
@@ -40,6 +103,7 @@ impl Runtime {
         let id = self.envs.len();
         let parent = self.current;
         self.envs.push(Env::new(id, parent));
+        self.envs[parent].children.push(id);
         self.current = id;
         id
     }
@@ -53,15 +117,17 @@ impl Runtime {
             return 0;
         }
         self.current = id;
-        return id;
+        id
     }
     /// restart kernel
+    /// * back to the last imported state, if any
     pub fn restart(&mut self) {
-        self.current = 0;
-        self.envs.clear();
-        self.envs.push(Env::new(0, 0));
-        self.envs.push(Env::new(1, 0));
+        self.envs = self.base.0.clone();
+        self.history = self.base.1.clone();
         self.current = 1;
+        self.depth = 0;
+        self.include.restart();
+        self.call_errors.clear();
     }
     /// clear all vals
     pub fn clear_all(&mut self) {
@@ -70,12 +136,13 @@ impl Runtime {
     /// clear current vals
     pub fn clear(&mut self) {
         if self.is_valid() {
-            self.envs.clear();
+            self.envs[self.current].clear();
         }
     }
 
     /// get a copy of most relative val from current env
-    pub fn get_val(&self, name: &String) -> Val {
+    /// * an unknown name is `NONE`, and kept in `call_errors`
+    pub fn get_val(&mut self, name: &String) -> Val {
         let mut id = self.current;
         while self.id_valid(id) {
             let e = &self.envs[id];
@@ -84,15 +151,39 @@ impl Runtime {
             }
             id = e.parent;
         }
-        self.get_sys_val(name)
+        if let Some(v) = self.get_history_val(name) {
+            return v;
+        }
+        match self.get_sys_val(name) {
+            Some(v) => v,
+            None => {
+                self.unknown(name, false);
+                Val::NONE
+            }
+        }
+    }
+    /// `x[i]`, where `out[i]` reads the history unless `out` is defined
+    pub fn get_index(&mut self, name: &String, i: Val) -> Val {
+        self.get_val(name).index(&i)
+    }
+    /// the val of a variable, without falling back to history or system
+    pub fn lookup(&self, name: &str) -> Option<Val> {
+        let mut id = self.current;
+        while self.id_valid(id) {
+            let e = &self.envs[id];
+            if let Some(v) = e.vals.get(name) {
+                return Some(v.to_owned());
+            }
+            id = e.parent;
+        }
+        None
     }
     /// set the val to target value
     pub fn set_val(&mut self, name: String, val: Val) {
         let mut id = self.current;
         while self.id_valid(id) {
-            let e = &mut self.envs[id].vals;
-            if e.contains_key(&name) {
-                e.insert(name, val);
+            if let Some(v) = self.envs[id].vals.get_mut(&name) {
+                *v = val;
                 return;
             }
             id = self.envs[id].parent;
@@ -102,9 +193,17 @@ impl Runtime {
         }
         self.envs[self.current].vals.insert(name, val);
     }
+    /// set a variable of the root env, which `restart` keeps
+    pub fn define(&mut self, name: String, val: Val) {
+        self.base.0[1].vals.insert(name.clone(), val.clone());
+        self.envs[1].vals.insert(name, val);
+    }
     /// copy all related vars
     pub fn copy_env(&self) -> HashMap<String, Val> {
-        self.copy_env_id(self.current)
+        match self.envs.get(self.current) {
+            Some(env) if self.is_valid() => env.get_copy(self),
+            _ => HashMap::new(),
+        }
     }
     /// copy all related vars of some id
     pub fn copy_env_id(&self, id: usize) -> HashMap<String, Val> {
@@ -117,16 +216,330 @@ impl Runtime {
                     res.insert(k.to_owned(), v.to_owned());
                 }
             }
+            id = self.envs[id].parent;
         }
         res
     }
 
-    pub fn get_sys_val(&self, name: &String) -> Val {
+    /// add the outputs of a sentence to history
+    pub fn record(&mut self, val: &Val) {
+        self.history.extend(val.leaves());
+    }
+    /// count of outputs so far
+    pub fn history_len(&self) -> usize {
+        self.history.len()
+    }
+    pub fn history(&self) -> &[Val] {
+        &self.history
+    }
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+    /// forget the outputs after the first `len`
+    pub fn truncate_history(&mut self, len: usize) {
+        self.history.truncate(len);
+    }
+    /// the history as it is after `restart`
+    pub fn restart_history(&mut self) {
+        self.history = self.base.1.clone();
+    }
+    /// `ans` is the last output, `_3` the third, `out` all of them
+    pub fn get_history_val(&self, name: &str) -> Option<Val> {
+        match name {
+            "ans" => self.history.last().cloned(),
+            "out" => Some(Val::Vars(Tuple(self.history.clone()))),
+            _ => {
+                let n: usize = name.strip_prefix('_')?.parse().ok()?;
+                self.history.get(n.checked_sub(1)?).cloned()
+            }
+        }
+    }
+
+    /// a constant, or a function by its name
+    pub fn get_sys_val(&self, name: &str) -> Option<Val> {
         if let Some(val) = self.sys.get_sys_val(name) {
-            val.to_owned()
+            Some(val.to_owned())
+        } else {
+            self.sys
+                .function(name)
+                .map(|_| Val::Fun(self.sys.get_sys_fun(name)))
+        }
+    }
+
+    /// keep an error for a name neither defined nor builtin,
+    /// with the names like it, functions only if it is `called`
+    pub fn unknown(&mut self, name: &str, called: bool) {
+        let mut vars: Vec<(String, Val)> = self.copy_env().into_iter().collect();
+        vars.sort_by(|a, b| a.0.cmp(&b.0));
+        let vars = vars
+            .iter()
+            .filter(|(_, v)| !called || matches!(v, Val::Fun(_)))
+            .map(|(k, _)| k.as_str());
+        let functions = self.sys.function_list();
+        let functions = functions.iter().flat_map(|f| f.names()).map(|n| n.as_str());
+        let constants = self.sys.constant_list();
+        let constants = constants.iter().filter(|_| !called).map(|c| c.0);
+        let error = CallError {
+            name: name.to_string(),
+            message: if called {
+                "unknown function"
+            } else {
+                "unknown name"
+            }
+            .to_string(),
+            suggestions: suggestions(name, vars.chain(functions).chain(constants)),
+        };
+        // a name in a function is unknown at each call
+        self.add_call_error(error);
+    }
+
+    /// a user function defined in the current env: variables of the
+    /// root env are read when it is called, those of blocks now
+    pub fn closure(&self, name: String, params: Vec<String>, body: Expression) -> Val {
+        let mut reads = HashSet::new();
+        body.reads(&mut reads);
+        let mut captured: Vec<(String, Val)> = reads
+            .into_iter()
+            .filter(|n| *n != name && !params.contains(n))
+            .filter_map(|n| {
+                let mut id = self.current;
+                while self.id_valid(id) && id != 1 {
+                    if let Some(v) = self.envs[id].vals.get(&n) {
+                        return Some((n, v.clone()));
+                    }
+                    id = self.envs[id].parent;
+                }
+                None
+            })
+            .collect();
+        captured.sort_by(|a, b| a.0.cmp(&b.0));
+        Val::Fun(Fun::User(Rc::new(Closure {
+            name,
+            params,
+            body,
+            captured,
+        })))
+    }
+    /// `f` one level deeper in the nested expressions
+    pub fn nested(&mut self, f: impl FnOnce(&mut Self) -> Val) -> Val {
+        self.depth += 1;
+        let res = f(self);
+        self.depth -= 1;
+        res
+    }
+    /// call a user function in a new env under the root one
+    pub fn call_closure(&mut self, c: &Rc<Closure>, vars: Val) -> Val {
+        let args = match vars.reduce() {
+            Val::Vars(Tuple(v)) if c.params.len() != 1 => v,
+            v => vec![v],
+        };
+        if args.len() != c.params.len() {
+            return Val::NONE;
+        }
+        if self.depth >= MAX_DEPTH {
+            let error = CallError {
+                name: c.name.clone(),
+                message: "recursion too deep".to_string(),
+                suggestions: vec![],
+            };
+            self.add_call_error(error);
+            return Val::NONE;
+        }
+        let caller = self.current;
+        let id = self.envs.len();
+        let mut env = Env::new(id, 1);
+        env.vals = c.captured.iter().cloned().collect();
+        if !c.name.is_empty() {
+            env.vals
+                .insert(c.name.clone(), Val::Fun(Fun::User(c.clone())));
+        }
+        env.vals.extend(c.params.iter().cloned().zip(args));
+        self.envs.push(env);
+        self.current = id;
+        self.depth += 1;
+        let res = c.body.calc(self);
+        self.depth -= 1;
+        self.current = caller;
+        // nothing can keep a reference to the env of a finished call
+        self.envs.truncate(id);
+        res
+    }
+
+    /// `f` in a new env under the current one, which is dropped after
+    pub fn scoped<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        let id = self.envs.len();
+        let parent = self.current;
+        self.envs.push(Env::new(id, parent));
+        self.current = id;
+        let res = f(self);
+        self.current = parent;
+        self.envs.truncate(id);
+        res
+    }
+    /// set a variable of the current env, shadowing those outside it
+    pub fn bind(&mut self, name: &str, val: Val) {
+        if self.is_valid() {
+            self.envs[self.current].vals.insert(name.to_string(), val);
+        }
+    }
+
+    /// call a native function, failures are kept in `call_errors`
+    pub fn call_native(&mut self, name: &str, f: &NativeFunction, vars: Val) -> Val {
+        let args = match vars.reduce() {
+            Val::Vars(Tuple(v)) if f.arity != (1..=1) => v,
+            v => vec![v],
+        };
+        let res = if f.arity.contains(&args.len()) {
+            match f.runtime.clone() {
+                Some(call) => call(self, &args),
+                None => (f.call)(&self.sys, &args),
+            }
         } else {
-            Val::Fun(self.sys.get_sys_fun(name))
+            let (min, max) = (f.arity.start(), f.arity.end());
+            let expected = if min == max {
+                min.to_string()
+            } else {
+                format!("{min} to {max}")
+            };
+            Err(format!(
+                "expects {expected} arguments, found {}",
+                args.len()
+            ))
+        };
+        res.unwrap_or_else(|message| {
+            let error = CallError {
+                name: name.to_string(),
+                message,
+                suggestions: vec![],
+            };
+            // a plot calls a function for each point
+            self.add_call_error(error);
+            Val::NONE
+        })
+    }
+    pub fn call_errors(&self) -> &[CallError] {
+        &self.call_errors
+    }
+    pub fn clear_call_errors(&mut self) {
+        self.call_errors.clear();
+    }
+    /// keep a failed call, unless it is kept already
+    pub fn add_call_error(&mut self, error: CallError) {
+        if !self.call_errors.contains(&error) {
+            self.call_errors.push(error);
+        }
+    }
+    /// the failed calls so far, which are forgotten
+    pub fn take_call_errors(&mut self) -> Vec<CallError> {
+        std::mem::take(&mut self.call_errors)
+    }
+
+    /// Save vars, user functions, settings and history as JSON:
+    ///
+    /// ```text
+    /// {
+    ///   "format": "web-caculator-state",
+    ///   "version": 2,
+    ///   "system": {"epsilon": 1e-9, "notation": "auto", "digits": 0,
+    ///              "separator": null, "rounding": true, "pi": false},
+    ///   "envs": [{"id": 1, "parent": 0, "vals": {"x": {"re": 5}}}, ...],
+    ///   "history": [{"re": 5}, ...]
+    /// }
+    /// ```
+    ///
+    /// `envs` lists every env but the null one, in order of id,
+    /// values are written by `Val::to_json`.
+    pub fn export_state(&self) -> String {
+        let envs = self.envs[1..]
+            .iter()
+            .map(|e| {
+                let mut vals: Vec<(&String, &Val)> = e.vals.iter().collect();
+                vals.sort_by(|a, b| a.0.cmp(b.0));
+                Json::obj([
+                    ("id", Json::Num(e.id as f64)),
+                    ("parent", Json::Num(e.parent as f64)),
+                    (
+                        "vals",
+                        Json::Obj(
+                            vals.into_iter()
+                                .map(|(k, v)| (k.clone(), v.to_json()))
+                                .collect(),
+                        ),
+                    ),
+                ])
+            })
+            .collect();
+        let (notation, digits) = self.sys.format.notation.name();
+        let system = Json::obj([
+            ("epsilon", Json::Num(self.sys.epsilon_)),
+            ("notation", Json::str(notation)),
+            ("digits", Json::Num(digits as f64)),
+            (
+                "separator",
+                match self.sys.format.separator {
+                    Some(c) => Json::Str(c.to_string()),
+                    None => Json::Null,
+                },
+            ),
+            ("rounding", Json::Bool(self.sys.format.rounding)),
+            ("pi", Json::Bool(self.sys.format.pi)),
+        ]);
+        Json::obj([
+            ("format", Json::str(STATE_FORMAT)),
+            ("version", Json::Num(STATE_VERSION as f64)),
+            ("system", system),
+            ("envs", Json::Arr(envs)),
+            (
+                "history",
+                Json::Arr(self.history.iter().map(|v| v.to_json()).collect()),
+            ),
+        ])
+        .to_string()
+    }
+    /// Load a state written by `export_state`, it becomes the state `restart` goes back to.
+    /// * keys unknown to this version are ignored
+    /// * nothing changes on error
+    pub fn import_state(&mut self, text: &str) -> Result<(), String> {
+        let j = Json::parse(text)?;
+        if j.get("format").and_then(|f| f.as_str()) != Some(STATE_FORMAT) {
+            return Err("not a saved calculator state".to_string());
+        }
+        let version = j.get("version").and_then(|v| v.as_f64()).unwrap_or(0.0);
+        if version > STATE_VERSION as f64 {
+            return Err(format!(
+                "state version {version} is newer than supported version {STATE_VERSION}"
+            ));
+        }
+        let mut envs = vec![Env::new(0, 0)];
+        for e in j.get("envs").and_then(|e| e.as_arr()).unwrap_or(&[]) {
+            let id = envs.len();
+            let parent = e.get("parent").and_then(|p| p.as_f64()).unwrap_or(0.0) as usize;
+            if e.get("id").and_then(|i| i.as_f64()) != Some(id as f64) || parent >= id {
+                return Err(format!("bad env: {e}"));
+            }
+            let mut env = Env::new(id, parent);
+            for (k, v) in e.get("vals").and_then(|v| v.as_obj()).unwrap_or(&[]) {
+                env.vals.insert(k.clone(), Val::from_json(v)?);
+            }
+            envs[parent].children.push(id);
+            envs.push(env);
         }
+        if envs.len() < 2 {
+            envs.push(Env::new(1, 0));
+        }
+        let history = j
+            .get("history")
+            .and_then(|h| h.as_arr())
+            .unwrap_or(&[])
+            .iter()
+            .map(Val::from_json)
+            .collect::<Result<Vec<_>, _>>()?;
+        if let Some(sys) = j.get("system") {
+            self.sys.import(sys);
+        }
+        self.base = (envs, history);
+        self.restart();
+        Ok(())
     }
 
     /// try to call the fun
@@ -143,7 +556,7 @@ impl Runtime {
     }
 }
 
-/// no clonable
+#[derive(Clone)]
 pub struct Env {
     // relations
     id: usize,
@@ -175,97 +588,121 @@ impl Env {
 pub struct System {
     // constants
     vals: HashMap<String, Val>,
+    /// what the constants are
+    notes: HashMap<String, String>,
 
     // statics
     epsilon_: f64,
+
+    /// functions by their names and aliases
+    functions: HashMap<String, Rc<NativeFunction>>,
+
+    /// how results are written out
+    pub format: NumFormat,
 }
 
 impl System {
     fn new() -> Self {
-        Self {
+        let mut sys = Self {
             vals: HashMap::new(),
+            notes: HashMap::new(),
             epsilon_: 1.0e-9f64,
+            functions: HashMap::new(),
+            format: NumFormat::default(),
+        };
+        for (name, val, doc) in intial_system() {
+            sys.set_constant(name.to_string(), val, doc);
         }
+        builtins().into_iter().for_each(|f| sys.register(f));
+        sys
     }
 
-    pub fn get_sys_val(&self, name: &String) -> Option<&Val> {
+    pub fn set_epsilon(&mut self, epsilon: f64) {
+        self.epsilon_ = epsilon.abs();
+    }
+    /// settings saved by `Runtime::export_state`, missing ones are kept
+    fn import(&mut self, j: &Json) {
+        if let Some(e) = j.get("epsilon").and_then(|e| e.as_f64()) {
+            self.set_epsilon(e);
+        }
+        if let Some(n) = j.get("notation").and_then(|n| n.as_str()) {
+            let digits = j.get("digits").and_then(|d| d.as_f64()).unwrap_or(0.0);
+            if let Some(n) = Notation::from_name(n, digits as usize) {
+                self.format.notation = n;
+            }
+        }
+        match j.get("separator") {
+            Some(Json::Str(s)) => self.format.separator = s.chars().next(),
+            Some(Json::Null) => self.format.separator = None,
+            _ => {}
+        }
+        if let Some(r) = j.get("rounding").and_then(|r| r.as_bool()) {
+            self.format.rounding = r;
+        }
+        if let Some(p) = j.get("pi").and_then(|p| p.as_bool()) {
+            self.format.pi = p;
+        }
+    }
+    pub fn format_re(&self, v: f64) -> String {
+        self.format.real(v, self.epsilon_)
+    }
+    pub fn format_im(&self, u: f64, v: f64) -> String {
+        self.format.complex(u, v, self.epsilon_)
+    }
+
+    pub fn get_sys_val(&self, name: &str) -> Option<&Val> {
         self.vals.get(name)
     }
-    pub fn get_sys_fun(&self, name: &String) -> Fun {
-        Fun::System(name.clone())
+    pub fn get_sys_fun(&self, name: &str) -> Fun {
+        Fun::System(name.to_string())
     }
 
-    pub fn calls(rt: &mut Runtime, name: &String, vars: Val) -> Val {
-        match vars.reduce() {
-            Val::Re(v) => Self::call_Re1(rt, name, v),
-            Val::Vars(Tuple(mut vars)) if vars.len() == 2 => {
-                let v2 = vars.pop().unwrap();
-                let v1 = vars.pop().unwrap();
-                match (v1, v2) {
-                    (Val::Re(v1), Val::Re(v2)) => Self::call_Re2(rt, name, v1, v2),
-                    _ => Val::NONE,
-                }
-            }
-            _ => Val::NONE,
+    /// a function for the sentences to call by its name and aliases,
+    /// replacing the functions of those names
+    pub fn register(&mut self, f: NativeFunction) {
+        let f = Rc::new(f);
+        for name in f.names() {
+            self.functions.insert(name.clone(), f.clone());
         }
     }
+    /// a constant for the sentences to read, variables may shadow it
+    pub fn set_constant(&mut self, name: String, val: Val, doc: &str) {
+        self.notes.insert(name.clone(), doc.to_string());
+        self.vals.insert(name, val);
+    }
+    /// the function called by a name or an alias
+    pub fn function(&self, name: &str) -> Option<&NativeFunction> {
+        self.functions.get(name).map(|f| &**f)
+    }
+    /// every function once, by name
+    pub fn function_list(&self) -> Vec<&NativeFunction> {
+        let mut res: Vec<_> = self
+            .functions
+            .iter()
+            .filter(|(name, f)| **name == f.name)
+            .map(|(_, f)| &**f)
+            .collect();
+        res.sort_by(|a, b| a.name.cmp(&b.name));
+        res
+    }
+    /// every constant with what it is, by name
+    pub fn constant_list(&self) -> Vec<(&str, &Val, &str)> {
+        let mut res: Vec<_> = self
+            .vals
+            .iter()
+            .map(|(name, val)| (name.as_str(), val, self.notes[name].as_str()))
+            .collect();
+        res.sort_by_key(|c| c.0);
+        res
+    }
 
-    fn call_Re1(rt: &mut Runtime, name: &str, v: Re) -> Val {
-        match name {
-            "absolute" | "abs" => Val::Re(Re(Self::_abs(v.0))),
-            "negative" | "neg" => Val::Re(Re(Self::_neg(v.0))),
-            "round" => Val::Re(Re(Self::_round(v.0))),
-            "ceil" => Val::Re(Re(Self::_ceil(v.0))),
-            "floor" | "int" => Val::Re(Re(Self::_floor(v.0))),
-            "sin" => Val::Re(Re(Self::_sin(v.0))),
-            "cos" => Val::Re(Re(Self::_cos(v.0))),
-            "tan" => Val::Re(Re(Self::_tan(v.0))),
-            "cot" => Val::Re(Re(Self::_cot(v.0))),
-            "sec" => Val::Re(Re(Self::_sec(v.0))),
-            "csc" => Val::Re(Re(Self::_csc(v.0))),
-            "asin" | "arcsin" => Val::Re(Re(Self::_arcsin(v.0))),
-            "acos" | "arccos" => Val::Re(Re(Self::_arccos(v.0))),
-            "atan" | "arctan" => Val::Re(Re(Self::_arctan(v.0))),
-            "acot" | "arccot" => Val::Re(Re(Self::_arccot(v.0))),
-            "asec" | "arcsec" => Val::Re(Re(Self::_arcsec(v.0))),
-            "acsc" | "arccsc" => Val::Re(Re(Self::_arccsc(v.0))),
-            "sinh" => Val::Re(Re(Self::_sinh(v.0))),
-            "cosh" => Val::Re(Re(Self::_cosh(v.0))),
-            "tanh" => Val::Re(Re(Self::_tanh(v.0))),
-            "coth" => Val::Re(Re(Self::_coth(v.0))),
-            "sech" => Val::Re(Re(Self::_sech(v.0))),
-            "csch" => Val::Re(Re(Self::_csch(v.0))),
-            "asinh" | "arcsinh" => Val::Re(Re(Self::_arcsinh(v.0))),
-            "acosh" | "arccosh" => Val::Re(Re(Self::_arccosh(v.0))),
-            "atanh" | "arctanh" => Val::Re(Re(Self::_arctanh(v.0))),
-            "acoth" | "arccoth" => Val::Re(Re(Self::_arccoth(v.0))),
-            "asech" | "arcsech" => Val::Re(Re(Self::_arcsech(v.0))),
-            "acsch" | "arccsch" => Val::Re(Re(Self::_arccsch(v.0))),
-            "raddegree" | "todegree" => Val::Re(Re(Self::_rad_to_deg(v.0))),
-            "degreerad" | "torad" => Val::Re(Re(Self::_deg_to_rad(v.0))),
-            "square" => Val::Re(Re(Self::_square(v.0))),
-            "cube" => Val::Re(Re(Self::_cube(v.0))),
-            "sqrt" | "sqr" => Val::Re(Re(Self::_sqrt(v.0))),
-            "cbrt" | "cbr" => Val::Re(Re(Self::_cbrt(v.0))),
-            "exp" => Val::Re(Re(Self::_exp(v.0))),
-            "log10" => Val::Re(Re(Self::_log10(v.0))),
-            "loge" | "ln" | "log" => Val::Re(Re(Self::_ln(v.0))),
-            "log2" => Val::Re(Re(Self::_log2(v.0))),
-            // need instance
-            "zero" => Val::Re(Re(rt.sys._iszero(v.0) as i32 as f64)),
-            _ => Val::NONE,
-        }
-    }
-    fn call_Re2(rt: &mut Runtime, name: &str, v1: Re, v2: Re) -> Val {
-        match name {
-            "add" | "plus" => Val::Re(Re(Self::_add(v1.0, v2.0))),
-            "substract" | "minus" => Val::Re(Re(Self::_sub(v1.0, v2.0))),
-            "multiply" | "dot" => Val::Re(Re(Self::_mulitply(v1.0, v2.0))),
-            "devide" | "frac" => Val::Re(Re(Self::_devide(v1.0, v2.0))),
-            "arctan2" | "atan2" | "arctan" | "atan" => Val::Re(Re(Self::_arctan2(v1.0, v2.0))),
-            "pow" | "power" => Val::Re(Re(Self::_pow(v1.0, v2.0))),
-            "log" | "logarithm" => Val::Re(Re(Self::_log(v1.0, v2.0))),
-            _ => Val::NONE,
+    pub fn calls(rt: &mut Runtime, name: &str, vars: Val) -> Val {
+        match rt.sys.functions.get(name).cloned() {
+            Some(f) => rt.call_native(name, &f, vars),
+            None => {
+                rt.unknown(name, true);
+                Val::NONE
+            }
         }
     }
 }
@@ -0,0 +1,86 @@
+/// words often written for the names of this calculator
+const MISSPELLINGS: &[(&str, &str)] = &[
+    ("divide", "devide"),
+    ("div", "devide"),
+    ("subtract", "substract"),
+    ("sub", "substract"),
+    ("mul", "multiply"),
+    ("times", "multiply"),
+    ("sum", "add"),
+    ("arcsine", "asin"),
+    ("arccosine", "acos"),
+    ("arctangent", "atan"),
+    ("squareroot", "sqrt"),
+    ("root", "sqrt"),
+];
+
+/// most names suggested for one
+const MAX_SUGGESTIONS: usize = 3;
+
+/// the names of `candidates` close to `name`, closest first
+/// * a known misspelling of a candidate comes first
+/// * then by edit distance, where swapping two letters counts once,
+///   ties kept in the order of `candidates`
+pub fn suggestions<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let lower = name.to_lowercase();
+    let len = name.chars().count();
+    // a third of the name, keeping at least a letter of it
+    let max = len.div_ceil(3).min(len.saturating_sub(1)).min(3);
+    let mut res: Vec<(usize, &str)> = vec![];
+    for c in candidates {
+        if c == name || res.iter().any(|r| r.1 == c) {
+            continue;
+        }
+        let d = if MISSPELLINGS.contains(&(lower.as_str(), c)) {
+            0
+        } else {
+            distance(&lower, &c.to_lowercase())
+        };
+        if d <= max {
+            res.push((d, c));
+        }
+    }
+    res.sort_by_key(|r| r.0);
+    res.into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|r| r.1.to_string())
+        .collect()
+}
+
+/// insertions, deletions, replacements and swaps of neighbours from `a` to `b`
+fn distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // rows for a[..i - 2], a[..i - 1] and a[..i]
+    let mut prev2 = vec![0; b.len() + 1];
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = (a[i - 1] != b[j - 1]) as usize;
+            row[j] = (prev[j] + 1).min(row[j - 1] + 1).min(prev[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(prev2[j - 2] + 1);
+            }
+        }
+        prev2 = std::mem::replace(&mut prev, row);
+    }
+    prev[b.len()]
+}
+
+#[test]
+fn test_suggestions() {
+    assert_eq!(distance("sqtr", "sqrt"), 1);
+    assert_eq!(distance("subtract", "substract"), 1);
+    assert_eq!(distance("", "ab"), 2);
+    assert_eq!(distance("kitten", "sitting"), 3);
+
+    let names = ["sqrt", "sqr", "square", "sin", "devide", "substract", "x1"];
+    assert_eq!(suggestions("sqtr", names), ["sqrt", "sqr"]);
+    assert_eq!(suggestions("divide", names), ["devide"]);
+    assert_eq!(suggestions("sub", names), ["substract"]);
+    assert_eq!(suggestions("Sin", names), ["sin"]);
+    assert_eq!(suggestions("x2", names), ["x1"]);
+    assert!(suggestions("banana", names).is_empty());
+    assert!(suggestions("y", names).is_empty());
+}